@@ -0,0 +1,10 @@
+CREATE TYPE announcement_mode AS ENUM ('post', 'live');
+
+ALTER TABLE guild_settings
+ADD COLUMN announcement_mode announcement_mode;
+
+CREATE TABLE live_messages (
+    guild_id bigint PRIMARY KEY,
+    channel_id bigint NOT NULL,
+    message_id bigint NOT NULL
+);
@@ -0,0 +1,15 @@
+CREATE TABLE status_changes (
+    id bigserial PRIMARY KEY,
+    floor_start smallint NOT NULL,
+    floor_end smallint NOT NULL,
+    status escalator_status NOT NULL,
+    reporter_id bigint,
+    changed_at timestamptz NOT NULL DEFAULT now(),
+    FOREIGN KEY (floor_start, floor_end) REFERENCES escalators
+);
+
+CREATE INDEX status_changes_changed_at ON status_changes (changed_at);
+
+ALTER TABLE guild_settings
+ADD COLUMN daily_summary boolean,
+ADD COLUMN weekly_summary boolean;
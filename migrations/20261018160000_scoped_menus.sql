@@ -0,0 +1,14 @@
+ALTER TABLE menu_messages
+DROP CONSTRAINT menu_messages_pkey,
+ADD PRIMARY KEY (message_id);
+
+CREATE INDEX menu_messages_guild_id ON menu_messages (guild_id);
+
+-- a menu without any rows here displays every escalator
+CREATE TABLE menu_escalators (
+    message_id bigint NOT NULL REFERENCES menu_messages ON DELETE CASCADE,
+    floor_start smallint NOT NULL,
+    floor_end smallint NOT NULL,
+    PRIMARY KEY (message_id, floor_start, floor_end),
+    FOREIGN KEY (floor_start, floor_end) REFERENCES escalators
+);
@@ -0,0 +1,10 @@
+-- menus get recreated with a new message id, so the scope has to follow it
+ALTER TABLE menu_escalators
+DROP CONSTRAINT menu_escalators_message_id_fkey,
+ADD FOREIGN KEY (message_id) REFERENCES menu_messages
+    ON DELETE CASCADE
+    ON UPDATE CASCADE;
+
+ALTER TABLE menu_messages
+ADD COLUMN missing_permissions boolean NOT NULL DEFAULT false,
+ADD COLUMN last_error text;
@@ -0,0 +1,10 @@
+ALTER TABLE escalators
+ADD COLUMN status_changed_at timestamptz;
+
+UPDATE escalators e
+SET status_changed_at = (
+    SELECT MAX(c.changed_at)
+    FROM status_changes c
+    WHERE c.floor_start = e.floor_start
+    AND c.floor_end = e.floor_end
+);
@@ -0,0 +1,9 @@
+CREATE TYPE locale AS ENUM ('en', 'es');
+
+ALTER TABLE guild_settings
+ADD COLUMN locale locale;
+
+CREATE TABLE user_locales (
+    user_id bigint PRIMARY KEY,
+    locale locale NOT NULL
+);
@@ -0,0 +1,6 @@
+ALTER TABLE guild_settings
+ADD COLUMN reports_open_hour smallint,
+ADD COLUMN reports_close_hour smallint,
+ADD COLUMN lock_weekends boolean,
+ADD COLUMN show_diagram boolean,
+ADD COLUMN show_ages boolean;
@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+ADD COLUMN admin_role_id bigint,
+ADD COLUMN moderator_role_id bigint;
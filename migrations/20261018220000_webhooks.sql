@@ -0,0 +1,30 @@
+CREATE TABLE webhooks (
+    webhook_id serial PRIMARY KEY,
+    guild_id bigint NOT NULL,
+    url text NOT NULL,
+    secret text,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX webhooks_guild_id ON webhooks (guild_id);
+
+-- a webhook without any rows here is sent reports for every escalator
+CREATE TABLE webhook_escalators (
+    webhook_id integer NOT NULL REFERENCES webhooks ON DELETE CASCADE,
+    floor_start smallint NOT NULL,
+    floor_end smallint NOT NULL,
+    PRIMARY KEY (webhook_id, floor_start, floor_end),
+    FOREIGN KEY (floor_start, floor_end) REFERENCES escalators
+);
+
+-- every attempt to deliver a report, with the response status or why it couldn't be sent
+CREATE TABLE webhook_deliveries (
+    id bigserial PRIMARY KEY,
+    webhook_id integer NOT NULL REFERENCES webhooks ON DELETE CASCADE,
+    attempt smallint NOT NULL,
+    status_code smallint,
+    error text,
+    attempted_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX webhook_deliveries_webhook_id ON webhook_deliveries (webhook_id, attempted_at);
@@ -0,0 +1,24 @@
+-- every report, kept so each of its consumers can catch up on the ones it hasn't handled
+CREATE TABLE outbox (
+    id bigserial PRIMARY KEY,
+    reporter_id bigint,
+    -- the escalators as they were reported (eg. `4/6` or `all`)
+    escalators text NOT NULL,
+    status escalator_status NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+-- the escalators whose status a report changed, in the order they were changed
+CREATE TABLE outbox_escalators (
+    event_id bigint NOT NULL REFERENCES outbox ON DELETE CASCADE,
+    position smallint NOT NULL,
+    floor_start smallint NOT NULL,
+    floor_end smallint NOT NULL,
+    PRIMARY KEY (event_id, position)
+);
+
+-- the last report each consumer (eg. `alert`) has handled
+CREATE TABLE outbox_cursors (
+    consumer text PRIMARY KEY,
+    last_event_id bigint NOT NULL
+);
@@ -0,0 +1,5 @@
+-- the last summary posted in each guild, so a missed one is caught up on without posting it twice
+CREATE TABLE posted_summaries (
+    guild_id bigint PRIMARY KEY,
+    summary_time timestamptz NOT NULL
+);
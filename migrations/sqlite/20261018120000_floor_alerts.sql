@@ -0,0 +1,5 @@
+CREATE TABLE floor_alerts (
+    user_id bigint NOT NULL,
+    floor smallint NOT NULL,
+    PRIMARY KEY (user_id, floor)
+);
@@ -0,0 +1,8 @@
+-- a NULL column means the guild uses the bot's default for that setting
+CREATE TABLE guild_settings (
+    guild_id bigint PRIMARY KEY,
+    announce_delay_secs integer,
+    max_reports_displayed smallint,
+    crosspost boolean,
+    include_gist boolean
+);
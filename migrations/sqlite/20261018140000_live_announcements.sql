@@ -0,0 +1,8 @@
+ALTER TABLE guild_settings
+ADD COLUMN announcement_mode text CHECK (announcement_mode IN ('post', 'live'));
+
+CREATE TABLE live_messages (
+    guild_id bigint PRIMARY KEY,
+    channel_id bigint NOT NULL,
+    message_id bigint NOT NULL
+);
@@ -0,0 +1,17 @@
+CREATE TABLE status_changes (
+    id integer PRIMARY KEY,
+    floor_start smallint NOT NULL,
+    floor_end smallint NOT NULL,
+    status text NOT NULL CHECK (status IN ('open', 'down', 'blocked')),
+    reporter_id bigint,
+    changed_at timestamp NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    FOREIGN KEY (floor_start, floor_end) REFERENCES escalators
+);
+
+CREATE INDEX status_changes_changed_at ON status_changes (changed_at);
+
+ALTER TABLE guild_settings
+ADD COLUMN daily_summary boolean;
+
+ALTER TABLE guild_settings
+ADD COLUMN weekly_summary boolean;
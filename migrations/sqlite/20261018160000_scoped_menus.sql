@@ -0,0 +1,26 @@
+-- SQLite can't change a primary key, so the table is recreated
+CREATE TABLE new_menu_messages (
+    guild_id bigint NOT NULL,
+    channel_id bigint NOT NULL,
+    message_id bigint PRIMARY KEY
+);
+
+INSERT INTO new_menu_messages (guild_id, channel_id, message_id)
+SELECT guild_id, channel_id, message_id
+FROM menu_messages;
+
+DROP TABLE menu_messages;
+
+ALTER TABLE new_menu_messages
+RENAME TO menu_messages;
+
+CREATE INDEX menu_messages_guild_id ON menu_messages (guild_id);
+
+-- a menu without any rows here displays every escalator
+CREATE TABLE menu_escalators (
+    message_id bigint NOT NULL REFERENCES menu_messages ON DELETE CASCADE,
+    floor_start smallint NOT NULL,
+    floor_end smallint NOT NULL,
+    PRIMARY KEY (message_id, floor_start, floor_end),
+    FOREIGN KEY (floor_start, floor_end) REFERENCES escalators
+);
@@ -0,0 +1,26 @@
+-- menus get recreated with a new message id, so the scope has to follow it
+-- (SQLite can't change a foreign key, so the table is recreated)
+CREATE TABLE new_menu_escalators (
+    message_id bigint NOT NULL REFERENCES menu_messages
+        ON DELETE CASCADE
+        ON UPDATE CASCADE,
+    floor_start smallint NOT NULL,
+    floor_end smallint NOT NULL,
+    PRIMARY KEY (message_id, floor_start, floor_end),
+    FOREIGN KEY (floor_start, floor_end) REFERENCES escalators
+);
+
+INSERT INTO new_menu_escalators (message_id, floor_start, floor_end)
+SELECT message_id, floor_start, floor_end
+FROM menu_escalators;
+
+DROP TABLE menu_escalators;
+
+ALTER TABLE new_menu_escalators
+RENAME TO menu_escalators;
+
+ALTER TABLE menu_messages
+ADD COLUMN missing_permissions boolean NOT NULL DEFAULT false;
+
+ALTER TABLE menu_messages
+ADD COLUMN last_error text;
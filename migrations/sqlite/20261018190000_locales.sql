@@ -0,0 +1,7 @@
+ALTER TABLE guild_settings
+ADD COLUMN locale text CHECK (locale IN ('en', 'es'));
+
+CREATE TABLE user_locales (
+    user_id bigint PRIMARY KEY,
+    locale text NOT NULL CHECK (locale IN ('en', 'es'))
+);
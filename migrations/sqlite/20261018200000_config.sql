@@ -0,0 +1,14 @@
+ALTER TABLE guild_settings
+ADD COLUMN reports_open_hour smallint;
+
+ALTER TABLE guild_settings
+ADD COLUMN reports_close_hour smallint;
+
+ALTER TABLE guild_settings
+ADD COLUMN lock_weekends boolean;
+
+ALTER TABLE guild_settings
+ADD COLUMN show_diagram boolean;
+
+ALTER TABLE guild_settings
+ADD COLUMN show_ages boolean;
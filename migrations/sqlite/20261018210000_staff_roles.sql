@@ -0,0 +1,5 @@
+ALTER TABLE guild_settings
+ADD COLUMN admin_role_id bigint;
+
+ALTER TABLE guild_settings
+ADD COLUMN moderator_role_id bigint;
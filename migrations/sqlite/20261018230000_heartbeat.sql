@@ -0,0 +1,5 @@
+-- when the bot was last known to be running, so it can tell how long it was offline for
+CREATE TABLE heartbeat (
+    id boolean PRIMARY KEY DEFAULT true CHECK (id),
+    last_seen timestamp NOT NULL
+);
@@ -0,0 +1,164 @@
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use hyper::{HeaderMap, StatusCode};
+use itertools::Itertools;
+
+use crate::{
+    data::{history::Report, status::Status, store::HistoryStore},
+    generate,
+    locale::Locale,
+    prelude::*,
+};
+
+use super::{
+    full,
+    page::{escape, markdown, page_locale},
+    response, ApiError, ApiResponse,
+};
+
+/// How many entries are included in the feed.
+const MAX_ENTRIES: usize = 50;
+/// How many status changes are loaded to group into entries.
+const MAX_CHANGES: i64 = 500;
+
+/// Status changes made by the same report, which are shown as a single entry.
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    escalators: Vec<EscalatorFloors>,
+    status: Status,
+    changed_at: DateTime<Utc>,
+}
+
+/// `GET /feed.atom`, an Atom feed with an entry for every report from the past month.
+pub async fn feed(
+    store: &dyn HistoryStore,
+    query: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<ApiResponse, ApiError> {
+    let locale = page_locale(query, headers);
+
+    let since = Utc::now() - TimeDelta::days(30);
+    let reports = store.reports(since, MAX_CHANGES).await?;
+
+    let entries = group_reports(&reports)
+        .into_iter()
+        .take(MAX_ENTRIES)
+        .collect_vec();
+
+    Ok(response(
+        StatusCode::OK,
+        "application/atom+xml; charset=utf-8",
+        full(render_feed(&entries, locale)),
+    ))
+}
+
+/// Groups status changes into the reports that made them,
+/// since every change made by a report is saved at the same time.
+fn group_reports(reports: &[Report]) -> Vec<Entry> {
+    reports
+        .iter()
+        .group_by(|report| (report.changed_at, report.status, report.user_reported))
+        .into_iter()
+        .map(|((changed_at, status, _), changes)| Entry {
+            escalators: changes.map(|change| change.floors).collect(),
+            status,
+            changed_at,
+        })
+        .collect()
+}
+
+fn render_feed(entries: &[Entry], locale: Locale) -> String {
+    let catalog = locale.catalog();
+
+    let updated = entries
+        .first()
+        .map_or_else(Utc::now, |entry| entry.changed_at);
+
+    let entries = entries
+        .iter()
+        .map(|entry| {
+            let message = generate::status_message(&entry.escalators, entry.status, locale);
+            let title = message.replace(['`', '*'], "");
+
+            indoc::formatdoc! {r#"
+                <entry>
+                <id>urn:escalator-status:report:{id}:{floors}</id>
+                <title>{title}</title>
+                <updated>{updated}</updated>
+                <content type="html">{content}</content>
+                </entry>"#,
+                id = entry.changed_at.timestamp_micros(),
+                // reports made at the same time never change the same escalators
+                floors = entry.escalators[0],
+                title = escape(&title),
+                updated = atom_date(entry.changed_at),
+                content = escape(&markdown(&message)),
+            }
+        })
+        .join("\n");
+
+    indoc::formatdoc! {r#"
+        <?xml version="1.0" encoding="utf-8"?>
+        <feed xmlns="http://www.w3.org/2005/Atom" xml:lang="{lang}">
+        <id>urn:escalator-status:feed</id>
+        <title>{title}</title>
+        <updated>{updated}</updated>
+        <author><name>{author}</name></author>
+        <link href="/"/>
+        {entries}
+        </feed>
+        "#,
+        lang = locale.code(),
+        title = escape(catalog.feed_title()),
+        updated = atom_date(updated),
+        author = escape(catalog.statuses_title()),
+    }
+}
+
+fn atom_date(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn report(floors: (u8, u8), status: Status, minute: u32) -> Report {
+        Report {
+            floors: EscalatorFloors::new(floors.0, floors.1),
+            status,
+            user_reported: true,
+            changed_at: Utc.with_ymd_and_hms(2026, 10, 18, 12, minute, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn changes_are_grouped_by_report() {
+        let reports = [
+            report((4, 6), Status::Down, 30),
+            report((6, 4), Status::Down, 30),
+            report((2, 3), Status::Open, 30),
+            report((4, 6), Status::Open, 10),
+        ];
+
+        let entries = group_reports(&reports);
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[0].escalators,
+            [EscalatorFloors::new(4, 6), EscalatorFloors::new(6, 4)]
+        );
+        assert_eq!(entries[1].escalators, [EscalatorFloors::new(2, 3)]);
+        assert_eq!(entries[2].status, Status::Open);
+    }
+
+    #[test]
+    fn entries_are_escaped() {
+        let entries = group_reports(&[report((4, 6), Status::Down, 30)]);
+        let feed = render_feed(&entries, Locale::En);
+
+        assert!(feed.contains("<title>🔴 The 4-6 escalator is DOWN</title>"));
+        assert!(feed.contains("<content type=\"html\">&lt;code&gt;🔴&lt;/code&gt;"));
+        assert!(feed.contains("<updated>2026-10-18T12:30:00Z</updated>"));
+    }
+}
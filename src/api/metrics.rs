@@ -0,0 +1,16 @@
+use hyper::StatusCode;
+
+use crate::{data::store::EscalatorStore, metrics};
+
+use super::{full, response, ApiError, ApiResponse};
+
+/// `GET /metrics`, every metric in the Prometheus text format.
+pub async fn metrics(store: &dyn EscalatorStore) -> Result<ApiResponse, ApiError> {
+    let escalators = store.escalators().await?;
+
+    Ok(response(
+        StatusCode::OK,
+        "text/plain; version=0.0.4",
+        full(metrics::render(&escalators)),
+    ))
+}
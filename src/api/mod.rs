@@ -0,0 +1,159 @@
+mod feed;
+mod metrics;
+mod page;
+mod status;
+mod stream;
+
+use std::{convert::Infallible, fmt::Display, net::SocketAddr, time::Duration};
+
+use http_body_util::{combinators::UnsyncBoxBody, BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    header, Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use tokio::net::TcpListener;
+
+use crate::prelude::*;
+
+type ApiBody = UnsyncBoxBody<Bytes, Infallible>;
+type ApiResponse = Response<ApiBody>;
+
+/// How long to wait after failing to accept a connection, doubling after every failure in a row.
+/// Errors like running out of file descriptors don't go away by retrying straight away.
+const INITIAL_ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Serves the HTTP API on the given address, running until the task is aborted.
+pub async fn serve(addr: SocketAddr, data: Data) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+
+    log::info!("Serving the HTTP API on {addr}");
+
+    let mut backoff = INITIAL_ACCEPT_BACKOFF;
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => {
+                backoff = INITIAL_ACCEPT_BACKOFF;
+                stream
+            }
+            Err(err) => {
+                log::warn!("An error ocurred trying to accept an API connection: {err}");
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_ACCEPT_BACKOFF);
+                continue;
+            }
+        };
+
+        let data = data.clone();
+
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(|req| {
+                let data = data.clone();
+                async move { Ok::<_, Infallible>(route(&data, req).await) }
+            });
+
+            let res = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+
+            if let Err(err) = res {
+                log::debug!("An API connection closed with an error: {err}");
+            }
+        });
+    }
+}
+
+/// Dispatches a request to the endpoint matching its path.
+async fn route(data: &Data, req: Request<Incoming>) -> ApiResponse {
+    if req.method() != Method::GET {
+        return ApiError::MethodNotAllowed.into_response();
+    }
+
+    let path = req.uri().path().trim_matches('/');
+    let segments = path.split('/').collect::<Vec<_>>();
+
+    let res = match segments.as_slice() {
+        [""] => page::status_page(&*data.store, req.uri().query(), req.headers()).await,
+        ["escalators"] => status::escalators(&*data.store).await,
+        ["escalators", floors] => status::escalator(&*data.store, floors).await,
+        ["reports"] => status::reports(&*data.store, req.uri().query()).await,
+        ["events"] => stream::events(data).await,
+        ["feed.atom"] => feed::feed(&*data.store, req.uri().query(), req.headers()).await,
+        ["metrics"] => metrics::metrics(&*data.store).await,
+        _ => Err(ApiError::NotFound),
+    };
+
+    res.unwrap_or_else(ApiError::into_response)
+}
+
+/// Serializes a value as the body of a successful response.
+fn json(value: &impl Serialize) -> Result<ApiResponse, ApiError> {
+    let body = serde_json::to_vec(value).map_err(|err| {
+        log::warn!("An error ocurred trying to serialize an API response: {err}");
+        ApiError::Internal
+    })?;
+
+    Ok(response(StatusCode::OK, "application/json", full(body)))
+}
+
+fn response(status: StatusCode, content_type: &str, body: ApiBody) -> ApiResponse {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        // the API is read-only and public, so any site can use it
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .expect("Response should be valid")
+}
+
+/// A body that's sent all at once.
+fn full(body: impl Into<Bytes>) -> ApiBody {
+    Full::new(body.into()).boxed_unsync()
+}
+
+/// The ways a request to the API can fail, sent back as a JSON error.
+#[derive(Debug)]
+enum ApiError {
+    NotFound,
+    MethodNotAllowed,
+    BadRequest(String),
+    /// The details are only logged, since they could leak how the bot works.
+    Internal,
+}
+
+impl ApiError {
+    fn into_response(self) -> ApiResponse {
+        let status = match self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = serde_json::json!({ "error": self.to_string() });
+
+        response(status, "application/json", full(body.to_string()))
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Not found"),
+            Self::MethodNotAllowed => write!(f, "Only GET requests are supported"),
+            Self::BadRequest(reason) => write!(f, "{reason}"),
+            Self::Internal => write!(f, "An internal error ocurred"),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        log::warn!("A database error ocurred while handling an API request: {err}");
+        Self::Internal
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use hyper::{header, HeaderMap, StatusCode};
+use itertools::Itertools;
+
+use crate::{data::store::Store, generate, locale::Locale};
+
+use super::{full, response, ApiError, ApiResponse};
+
+/// How many of the most recent reports are listed on the page.
+const RECENT_REPORTS: i64 = 15;
+/// How often the page reloads itself, in seconds.
+const REFRESH_SECS: u32 = 60;
+
+/// `GET /`, a page showing the status of every escalator and the recent reports,
+/// for people without a Discord account.
+pub async fn status_page(
+    store: &dyn Store,
+    query: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<ApiResponse, ApiError> {
+    let locale = page_locale(query, headers);
+    let catalog = locale.catalog();
+    let now = Utc::now();
+
+    let age = |time: DateTime<Utc>| catalog.time_ago(now - time);
+
+    let escalators = store.escalators().await?;
+    let reports = store
+        .reports(now - TimeDelta::weeks(1), RECENT_REPORTS)
+        .await?;
+
+    // -- Gist
+
+    let summaries = generate::gist_summaries(&escalators, locale, age);
+    let (gist_class, gist) = if summaries.is_empty() {
+        ("open", markdown(&generate::all_open(locale)))
+    } else {
+        (
+            "closed",
+            summaries.iter().map(|line| markdown(line)).join("<br>"),
+        )
+    };
+
+    // -- Statuses
+
+    let statuses = generate::escalator_pairs(&escalators)
+        .map(|pair| {
+            let cells = pair
+                .iter()
+                .map(|escalator| {
+                    let changed = escalator.status_changed_at.map(age).unwrap_or_default();
+
+                    format!(
+                        "<td>{emoji} <code>{floors}</code></td><td class=\"age\">{changed}</td>",
+                        emoji = escalator.status.emoji(),
+                        floors = escalator.floors,
+                        changed = escape(&changed),
+                    )
+                })
+                .join("");
+
+            format!("<tr>{cells}</tr>")
+        })
+        .join("\n");
+
+    // -- Recent Reports
+
+    let reports = if reports.is_empty() {
+        format!("<p>{}</p>", markdown(catalog.no_changes()))
+    } else {
+        let items = reports
+            .iter()
+            .map(|report| {
+                format!(
+                    "<li>{emoji} <code>{floors}</code> {status} <span class=\"age\">{changed}</span></li>",
+                    emoji = report.status.emoji(),
+                    floors = report.floors,
+                    status = escape(catalog.status_label(report.status)),
+                    changed = escape(&age(report.changed_at)),
+                )
+            })
+            .join("\n");
+
+        format!("<ul>\n{items}\n</ul>")
+    };
+
+    let page = indoc::formatdoc! {r#"
+        <!DOCTYPE html>
+        <html lang="{lang}">
+        <head>
+        <meta charset="utf-8">
+        <meta name="viewport" content="width=device-width, initial-scale=1">
+        <meta http-equiv="refresh" content="{REFRESH_SECS}">
+        <title>{title}</title>
+        <style>
+        body {{ font-family: system-ui, sans-serif; margin: 0 auto; max-width: 40rem; padding: 1rem; }}
+        .gist {{ border-left: 0.3rem solid; padding: 0.5rem 1rem; }}
+        .open {{ border-color: rgb(55, 220, 70); }}
+        .closed {{ border-color: rgb(240, 60, 60); }}
+        td {{ padding: 0.2rem 0.5rem; }}
+        .age, footer {{ color: gray; font-size: 0.9em; }}
+        </style>
+        </head>
+        <body>
+        <h1>{title}</h1>
+        <section class="gist {gist_class}">
+        <h2>{gist_title}</h2>
+        <p>{gist}</p>
+        </section>
+        <table>
+        {statuses}
+        </table>
+        <h2>{reports_title}</h2>
+        {reports}
+        <footer>{refresh_note}</footer>
+        </body>
+        </html>
+        "#,
+        lang = locale.code(),
+        title = escape(catalog.statuses_title()),
+        gist_title = escape(catalog.gist_title()),
+        reports_title = escape(catalog.recent_reports()),
+        refresh_note = escape(catalog.page_refresh_note()),
+    };
+
+    Ok(response(
+        StatusCode::OK,
+        "text/html; charset=utf-8",
+        full(page),
+    ))
+}
+
+/// Picks the locale from the `lang` query parameter,
+/// or the browser's preferred language if it isn't given.
+pub(super) fn page_locale(query: Option<&str>, headers: &HeaderMap) -> Locale {
+    let lang = query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|pair| pair.strip_prefix("lang="));
+
+    // eg. `es-419,es;q=0.9,en;q=0.8`
+    let accept_language = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split([',', ';']).next())
+        .map(str::trim);
+
+    lang.or(accept_language)
+        .and_then(Locale::from_discord)
+        .unwrap_or_default()
+}
+
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Converts the Discord markdown used by the generated messages into HTML,
+/// which is only ever code spans, bold and italics.
+pub(super) fn markdown(text: &str) -> String {
+    fn toggle(html: &mut String, is_open: &mut bool, tag: &str) {
+        html.push_str(if *is_open { "</" } else { "<" });
+        html.push_str(tag);
+        html.push('>');
+        *is_open = !*is_open;
+    }
+
+    let mut html = String::new();
+    let (mut code, mut bold, mut italic) = (false, false, false);
+
+    let text = escape(text);
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '`' => toggle(&mut html, &mut code, "code"),
+            '*' if !code && chars.peek() == Some(&'*') => {
+                chars.next();
+                toggle(&mut html, &mut bold, "strong");
+            }
+            '*' if !code => toggle(&mut html, &mut italic, "em"),
+            c => html.push(c),
+        }
+    }
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_is_converted_to_html() {
+        assert_eq!(
+            markdown("`🔴` The `4-6` escalator is `DOWN` *(updated 5 minutes ago)*."),
+            "<code>🔴</code> The <code>4-6</code> escalator is <code>DOWN</code> \
+            <em>(updated 5 minutes ago)</em>."
+        );
+        assert_eq!(
+            markdown("**<@1>** & `*`"),
+            "<strong>&lt;@1&gt;</strong> &amp; <code>*</code>"
+        );
+    }
+
+    #[test]
+    fn page_locale_prefers_the_query() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_LANGUAGE, "es-419,es;q=0.9".parse().unwrap());
+
+        assert_eq!(page_locale(None, &headers), Locale::Es);
+        assert_eq!(page_locale(Some("lang=en"), &headers), Locale::En);
+        assert_eq!(page_locale(None, &HeaderMap::new()), Locale::En);
+    }
+}
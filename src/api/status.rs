@@ -0,0 +1,87 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+
+use crate::{
+    data::{
+        escalator_input::EscalatorInput,
+        store::{EscalatorStore, HistoryStore},
+    },
+    prelude::*,
+};
+
+use super::{json, ApiError, ApiResponse};
+
+const DEFAULT_REPORTS: i64 = 100;
+const MAX_REPORTS: i64 = 500;
+
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+struct ReportsQuery {
+    /// Only include reports made after this time (defaults to the past day).
+    since: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+}
+
+/// `GET /escalators`, the current status of every escalator.
+pub async fn escalators(store: &dyn EscalatorStore) -> Result<ApiResponse, ApiError> {
+    json(&store.escalators().await?)
+}
+
+/// `GET /escalators/{floors}`, the current status of a single escalator (eg. `4-6`).
+pub async fn escalator(store: &dyn EscalatorStore, floors: &str) -> Result<ApiResponse, ApiError> {
+    let floors = match floors.parse::<EscalatorInput>() {
+        Ok(EscalatorInput::Direct(start, end)) => EscalatorFloors::new(start, end),
+        Ok(_) => {
+            let reason = String::from("Only a single escalator can be requested, eg. `4-6`");
+            return Err(ApiError::BadRequest(reason));
+        }
+        Err(err) => return Err(ApiError::BadRequest(err.to_string())),
+    };
+
+    let escalator = store
+        .escalators()
+        .await?
+        .into_iter()
+        .find(|escalator| escalator.floors == floors)
+        .ok_or(ApiError::NotFound)?;
+
+    json(&escalator)
+}
+
+/// `GET /reports?since=&limit=`, the most recent status changes, newest first.
+pub async fn reports(
+    store: &dyn HistoryStore,
+    query: Option<&str>,
+) -> Result<ApiResponse, ApiError> {
+    let query = parse_reports_query(query.unwrap_or_default())?;
+
+    let since = query
+        .since
+        .unwrap_or_else(|| Utc::now() - TimeDelta::days(1));
+    let limit = query.limit.unwrap_or(DEFAULT_REPORTS).clamp(1, MAX_REPORTS);
+
+    json(&store.reports(since, limit).await?)
+}
+
+fn parse_reports_query(query: &str) -> Result<ReportsQuery, ApiError> {
+    serde_urlencoded::from_str(query).map_err(|err| ApiError::BadRequest(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn reports_query_is_parsed() {
+        assert_eq!(parse_reports_query("").unwrap(), ReportsQuery::default());
+
+        let query = parse_reports_query("since=2026-10-18T12:00:00Z&limit=5").unwrap();
+        assert_eq!(
+            query.since,
+            Some(Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap())
+        );
+        assert_eq!(query.limit, Some(5));
+
+        assert!(parse_reports_query("since=yesterday").is_err());
+    }
+}
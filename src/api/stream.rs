@@ -0,0 +1,131 @@
+use std::{convert::Infallible, sync::Arc, time::Duration};
+
+use futures::{stream, StreamExt};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::{
+    body::{Bytes, Frame},
+    header::{self, HeaderValue},
+    StatusCode,
+};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    data::{report::UserReport, status::Status, store::EscalatorStore},
+    metrics,
+    prelude::*,
+};
+
+use super::{response, ApiError, ApiResponse};
+
+/// How often a comment is sent on an idle stream, so proxies don't close it.
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// A report that changed the status of some escalators, as it's sent by `GET /events`.
+#[derive(Serialize)]
+struct ReportEvent<'a> {
+    escalators: &'a [EscalatorFloors],
+    status: Status,
+    /// Whether the report was made by a user, rather than by the bot itself.
+    /// Who made it is left out, since the stream is public.
+    user_reported: bool,
+}
+
+/// `GET /events`, a stream of Server-Sent Events starting with a `snapshot` of every escalator,
+/// followed by a `report` every time the status of any escalator changes.
+pub async fn events(data: &Data) -> Result<ApiResponse, ApiError> {
+    // subscribe before the snapshot is taken, so no reports are missed in between
+    let reports = data.receiver::<UserReport>();
+    let snapshot = snapshot_event(&*data.store).await?;
+
+    let events = stream::unfold(
+        (Arc::clone(&data.store), reports),
+        |(store, mut reports)| async move {
+            let event = next_event(&*store, &mut reports).await?;
+            Some((event, (store, reports)))
+        },
+    );
+
+    let body = stream::once(async { snapshot })
+        .chain(events)
+        .map(|event| Ok::<_, Infallible>(Frame::data(Bytes::from(event))));
+
+    let mut res = response(
+        StatusCode::OK,
+        "text/event-stream",
+        StreamBody::new(body).boxed_unsync(),
+    );
+    res.headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+    Ok(res)
+}
+
+/// Waits for the next event to send, or `None` if the stream should end.
+async fn next_event(
+    store: &dyn EscalatorStore,
+    reports: &mut broadcast::Receiver<UserReport>,
+) -> Option<String> {
+    loop {
+        let report = match tokio::time::timeout(KEEP_ALIVE, reports.recv()).await {
+            Ok(Ok(report)) if report.affected_escalators.is_empty() => continue,
+            Ok(Ok(report)) => report,
+            // the reports that were missed can't be recovered, so a new snapshot is sent instead
+            Ok(Err(RecvError::Lagged(n))) => {
+                log::warn!("Event stream lagged behind by {n} reports.");
+                metrics::receiver_lagged("events");
+
+                return match snapshot_event(store).await {
+                    Ok(event) => Some(event),
+                    Err(err) => {
+                        log::warn!("Failed to send a snapshot on a lagged event stream: {err}");
+                        None
+                    }
+                };
+            }
+            Ok(Err(RecvError::Closed)) => return None,
+            Err(_) => return Some(String::from(": keep-alive\n\n")),
+        };
+
+        let event = ReportEvent {
+            escalators: &report.affected_escalators,
+            status: report.new_status,
+            user_reported: report.reporter.is_some(),
+        };
+
+        return Some(sse_event("report", &event));
+    }
+}
+
+async fn snapshot_event(store: &dyn EscalatorStore) -> Result<String, sqlx::Error> {
+    let escalators = store.escalators().await?;
+    Ok(sse_event("snapshot", &escalators))
+}
+
+/// Formats a named Server-Sent Event with JSON data.
+fn sse_event(name: &str, data: &impl Serialize) -> String {
+    // serialized JSON never contains a raw newline, so it always fits on a single `data` line
+    let data = serde_json::to_string(data).expect("Events should be serializable");
+    format!("event: {name}\ndata: {data}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_formatted_for_sse() {
+        let floors = [EscalatorFloors::new(4, 6)];
+        let event = ReportEvent {
+            escalators: &floors,
+            status: Status::Down,
+            user_reported: true,
+        };
+
+        assert_eq!(
+            sse_event("report", &event),
+            "event: report\n\
+            data: {\"escalators\":[{\"start\":4,\"end\":6}],\"status\":\"down\",\"user_reported\":true}\n\n"
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use escalator_status_bot_rs::{data::db::DbPool, EscalatorBot};
+
+/// Runs the bot without Shuttle, configured by environment variables
+/// (or a `.env` file in the working directory):
+///
+/// - `TOKEN`: the Discord bot token
+/// - `DATABASE_URL`: the Postgres database to connect to,
+///   or a SQLite database (eg. `sqlite://escalators.db?mode=rwc`) when built with the `sqlite` feature
+/// - `BIND_ADDRESS`: the address to serve the HTTP API on (defaults to `127.0.0.1:8000`)
+/// - `RUST_LOG`: which logs to print (defaults to `info`)
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // a missing `.env` file is fine, since the variables can be set directly
+    let _ = dotenvy::dotenv();
+
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let token = env_var("TOKEN")?;
+    let database_url = env_var("DATABASE_URL")?;
+    let addr = std::env::var("BIND_ADDRESS")
+        .unwrap_or_else(|_| String::from("127.0.0.1:8000"))
+        .parse()?;
+
+    let pool = DbPool::connect(&database_url).await?;
+
+    EscalatorBot::new(pool, token).await?.run(addr).await
+}
+
+fn env_var(name: &str) -> anyhow::Result<String> {
+    std::env::var(name).map_err(|_| anyhow::anyhow!("The {name} environment variable isn't set"))
+}
@@ -1,98 +1,171 @@
 use std::sync::Arc;
 
-use crate::{data::report::UserReport, generate, prelude::*};
+use crate::{
+    data::{report::UserReport, store::Store},
+    generate, metrics,
+    prelude::*,
+};
 
-use super::BotTask;
+use super::{outbox::Outbox, BotTask};
 
 use futures::future::join_all;
 use poise::serenity_prelude::CacheHttp;
-use smallvec::{smallvec, SmallVec};
-use tokio::sync::broadcast;
 
+#[derive(Clone)]
 pub struct AlertTask;
 
 pub struct TaskData<T> {
-    pool: sqlx::PgPool,
-    reports: broadcast::Receiver<UserReport>,
+    store: Arc<dyn Store>,
+    outbox: Outbox,
     cache_http: Arc<T>,
 }
 
 impl<T: CacheHttp + 'static> BotTask<T> for AlertTask {
+    const NAME: &'static str = "alert";
     type Data = TaskData<T>;
-    type Term = anyhow::Result<()>;
 
     async fn setup(&self, data: &Data, cache_http: Arc<T>) -> Option<Self::Data> {
         Some(TaskData {
-            pool: data.pool.clone(),
-            reports: data.receiver(),
+            store: Arc::clone(&data.store),
+            outbox: Outbox::new(<Self as BotTask<T>>::NAME, data).await?,
             cache_http,
         })
     }
 
-    async fn run(self, mut data: Self::Data) -> Self::Term {
+    async fn run(self, mut data: Self::Data) -> anyhow::Result<()> {
         loop {
-            let report = match data.reports.recv().await {
-                Ok(report) if report.affected_escalators.is_empty() => continue,
-                Ok(report) => report,
-                // if the channel closed (for some reason) then stop the loop
-                Err(broadcast::error::RecvError::Closed) => {
-                    anyhow::bail!("Update receiver has closed.");
-                }
-                // if the receiver is lagging beind, restart the loop and try receiving again
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    log::warn!("Update receiver has lagged by {n} updates.");
-                    continue;
-                }
-            };
-
-            let mut starts: SmallVec<[_; 2]> = smallvec![];
-            let mut ends: SmallVec<[_; 2]> = smallvec![];
+            let event = data.outbox.next().await?;
 
-            for &EscalatorFloors { start, end } in &report.affected_escalators {
-                starts.push(start as i16);
-                ends.push(end as i16);
+            if !event.report.affected_escalators.is_empty() {
+                send_alerts(&data, &event.report).await?;
             }
 
-            let users = sqlx::query_as::<_, (i64,)>(
-                "
-                SELECT DISTINCT user_id
-                FROM alerts a
-                WHERE EXISTS (
-                    SELECT 1
-                    FROM UNNEST($1::smallint[], $2::smallint[])
-                        AS r (floor_start, floor_end)
-                    WHERE a.floor_start = r.floor_start
-                    AND a.floor_end = r.floor_end
-                )
-                ",
-            )
-            .bind(&starts[..])
-            .bind(&ends[..])
-            .fetch_all(&data.pool)
-            .await?;
-
-            if users.is_empty() {
-                log::info!("No users watching affected escalators, skipping.");
-                continue;
-            }
+            data.outbox.handled(event.id).await?;
+        }
+    }
+}
 
-            let message = generate::alert(&report);
+/// Sends a DM about the report to every user watching any of the affected escalators.
+async fn send_alerts<T: CacheHttp + 'static>(
+    data: &TaskData<T>,
+    report: &UserReport,
+) -> Result<(), sqlx::Error> {
+    let users = data.store.watchers(&report.affected_escalators).await?;
 
-            log::info!("Sending alert messages...");
+    if users.is_empty() {
+        log::info!("No users watching affected escalators, skipping.");
+        return Ok(());
+    }
 
-            let send_all = users.into_iter().map(|(user_id,)| {
-                let message = message.clone();
-                let cache_http = Arc::clone(&data.cache_http);
-                let user = serenity::UserId::new(user_id as u64);
-                async move {
-                    let Ok(dm) = user.create_dm_channel(&cache_http).await else {
-                        return;
-                    };
-                    let _ = dm.say(&cache_http, message).await.ok();
-                }
-            });
+    log::info!("Sending alert messages...");
 
-            join_all(send_all).await;
+    let send_all = users.into_iter().map(|(user, locale)| {
+        let message = generate::alert(report, locale.unwrap_or_default());
+        let cache_http = Arc::clone(&data.cache_http);
+        async move {
+            let sent = match user.create_dm_channel(&cache_http).await {
+                Ok(dm) => dm.say(&cache_http, message).await.is_ok(),
+                Err(_) => false,
+            };
+            metrics::alert_sent(sent);
         }
+    });
+
+    join_all(send_all).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bot_tasks::fake::{Action, FakeDiscord},
+        data::{
+            escalator_input::EscalatorInput,
+            status::Status,
+            store::{AlertStore, MemoryStore, OutboxStore},
+        },
+    };
+
+    const WATCHER: serenity::UserId = serenity::UserId::new(10);
+
+    /// A store with a user watching the 4-6 escalator, and another watching the 9th floor.
+    async fn store() -> Arc<MemoryStore> {
+        let store = Arc::new(MemoryStore::default());
+        store
+            .set_watchlist(WATCHER, &[EscalatorFloors::new(4, 6)])
+            .await
+            .unwrap();
+        store
+            .toggle_floor(serenity::UserId::new(11), 9)
+            .await
+            .unwrap();
+
+        store
+    }
+
+    /// Reports the 4-6 escalator as down, like the report menu does.
+    async fn report(data: &Data) {
+        let escalators = EscalatorInput::Direct(4, 6);
+        let affected_escalators = data
+            .store
+            .commit_report(None, escalators, Status::Down)
+            .await
+            .unwrap();
+
+        data.send_message(UserReport {
+            reporter: None,
+            escalators,
+            affected_escalators,
+            new_status: Status::Down,
+        });
+    }
+
+    #[tokio::test]
+    async fn watchers_are_sent_alerts() {
+        let data = Data::new(store().await);
+        let discord = Arc::new(FakeDiscord::start().await);
+
+        let task_data = AlertTask.setup(&data, Arc::clone(&discord)).await.unwrap();
+        tokio::spawn(AlertTask.run(task_data));
+
+        report(&data).await;
+
+        let actions = discord.wait_for(1).await;
+
+        // the floor watcher isn't watching either end of the escalator
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(
+            &actions[0],
+            Action::Dm { user_id, content } if *user_id == WATCHER && content.contains("4-6")
+        ));
+    }
+
+    #[tokio::test]
+    async fn reports_made_while_stopped_are_caught_up_on() {
+        let store = store().await;
+        let data = Data::new(Arc::clone(&store) as Arc<dyn Store>);
+        let discord = Arc::new(FakeDiscord::start().await);
+
+        // the task has run before, but nothing is listening when the report is made
+        store.cursor("alert").await.unwrap();
+        report(&data).await;
+
+        let task_data = AlertTask.setup(&data, Arc::clone(&discord)).await.unwrap();
+        tokio::spawn(AlertTask.run(task_data));
+
+        let actions = discord.wait_for(1).await;
+        assert!(matches!(&actions[0], Action::Dm { user_id, .. } if *user_id == WATCHER));
+
+        // the report isn't sent again once it's been handled
+        let handled = async {
+            while store.cursor("alert").await.unwrap() == 0 {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(5), handled)
+            .await
+            .expect("the report should be marked as handled");
     }
 }
@@ -1,149 +1,657 @@
-use crate::{data::report::UserReport, generate, prelude::*};
+use crate::{
+    data::{
+        report::UserReport,
+        settings::AnnouncementMode,
+        store::{AnnouncementChannel, OutboxEvent, Store},
+    },
+    generate,
+    locale::Locale,
+    prelude::*,
+};
 
-use super::BotTask;
+use super::{
+    outbox::{InFlight, Outbox},
+    BotTask, Shutdown,
+};
 
 use futures::future::join_all;
-use poise::serenity_prelude::{CacheHttp, CreateMessage};
-use std::{sync::Arc, time::Duration};
-use tokio::sync::broadcast;
+use poise::serenity_prelude::{CacheHttp, CreateMessage, EditMessage};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tokio::{sync::broadcast, time::Instant};
 
-pub struct AnnounceTask {
-    delay: Duration,
-    max_reports_displayed: usize,
-}
+#[derive(Clone)]
+pub struct AnnounceTask;
 
 pub struct TaskData<T> {
-    pool: sqlx::PgPool,
-    reports: broadcast::Receiver<UserReport>,
+    store: Arc<dyn Store>,
+    outbox: Outbox,
+    shutdown: broadcast::Receiver<Shutdown>,
     cache_http: Arc<T>,
+    /// The most recent reports displayed in each guild's live message.
+    live_reports: HashMap<i64, VecDeque<UserReport>>,
 }
 
-impl Default for AnnounceTask {
-    fn default() -> Self {
-        Self {
-            delay: Duration::from_secs(2 * 60),
-            max_reports_displayed: 8,
-        }
-    }
+/// Reports being pooled for a guild until its deadline is up.
+struct PendingAnnouncement {
+    deadline: Instant,
+    events: Vec<OutboxEvent>,
 }
 
 impl<T: CacheHttp + 'static> BotTask<T> for AnnounceTask {
+    const NAME: &'static str = "announce";
+    const GRACEFUL: bool = true;
     type Data = TaskData<T>;
-    type Term = anyhow::Result<()>;
 
     async fn setup(&self, data: &Data, cache_http: Arc<T>) -> Option<Self::Data> {
+        let outbox = Outbox::new(<Self as BotTask<T>>::NAME, data).await?;
+
+        let live_reports = match load_live_reports(&*data.store, outbox.cursor()).await {
+            Ok(live_reports) => live_reports,
+            Err(err) => {
+                log::warn!("An error ocurred trying to load the reports of live messages: {err}");
+                return None;
+            }
+        };
+
         Some(TaskData {
-            pool: data.pool.clone(),
-            reports: data.receiver(),
+            store: Arc::clone(&data.store),
+            outbox,
+            shutdown: data.receiver(),
             cache_http,
+            live_reports,
         })
     }
 
-    async fn run(self, mut data: Self::Data) -> Self::Term {
+    async fn run(self, mut data: Self::Data) -> anyhow::Result<()> {
+        let mut pending = HashMap::<i64, PendingAnnouncement>::new();
+        let mut in_flight = InFlight::default();
+
         loop {
-            let reports = self.accumulate_reports(&mut data.reports).await?;
+            let next_deadline = pending.values().map(|pending| pending.deadline).min();
 
-            log::info!("Grabbing announcement channels...");
+            let sleep = async {
+                match next_deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
 
-            let channels = sqlx::query_as::<_, (i64,)>(
-                "
-                SELECT channel_id
-                FROM announcement_channels
-                ",
-            )
-            .fetch_all(&data.pool)
-            .await?;
+            tokio::select! {
+                // reports that were already made are pooled first, so they're announced on shutdown
+                biased;
 
-            if channels.is_empty() {
-                log::info!("No announcement channels found, skipping announcement generation.");
-                continue;
+                res = data.outbox.next() => {
+                    let event = res?;
+                    let event_id = event.id;
+
+                    let guilds = self.pool_report(&*data.store, &mut pending, event).await?;
+                    in_flight.start(event_id, guilds);
+
+                    data.outbox.handled(in_flight.handled()).await?;
+                }
+                () = sleep => {
+                    let now = Instant::now();
+
+                    let due = pending
+                        .iter()
+                        .filter(|(_, pending)| pending.deadline <= now)
+                        .map(|(&guild_id, _)| guild_id)
+                        .collect::<Vec<_>>();
+
+                    let due = due
+                        .into_iter()
+                        .filter_map(|guild_id| pending.remove_entry(&guild_id))
+                        .collect::<Vec<_>>();
+
+                    self.announce_pending(&mut data, &mut in_flight, due).await?;
+                }
+                _ = data.shutdown.recv() => {
+                    // reports that haven't been pooled yet are left in the outbox for next time
+                    log::info!("Shutting down, announcing every pending report...");
+
+                    self.announce_pending(&mut data, &mut in_flight, pending).await?;
+
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+impl AnnounceTask {
+    /// Adds a report to every guild's pending announcement,
+    /// starting a new one for guilds which aren't pooling any reports yet.
+    /// Returns how many guilds it was added for.
+    async fn pool_report(
+        &self,
+        store: &dyn Store,
+        pending: &mut HashMap<i64, PendingAnnouncement>,
+        event: OutboxEvent,
+    ) -> Result<usize, sqlx::Error> {
+        let channels = store.announcement_channels(None).await?;
+
+        if channels.is_empty() {
+            log::info!("No announcement channels found, skipping announcement pooling.");
+            return Ok(0);
+        }
+
+        let guilds = channels.len();
+
+        for channel in channels {
+            let delay = channel.settings.delay;
+
+            pending
+                .entry(channel.guild_id)
+                .or_insert_with(|| {
+                    log::info!(
+                        "Received update, pooling for {} seconds before announcing in guild {}.",
+                        delay.as_secs(),
+                        channel.guild_id,
+                    );
+
+                    PendingAnnouncement {
+                        deadline: Instant::now() + delay,
+                        events: vec![],
+                    }
+                })
+                .events
+                .push(event.clone());
+        }
+
+        Ok(guilds)
+    }
+
+    /// Announces the reports pooled for each guild,
+    /// recording them as handled once every guild they were pooled for has announced them.
+    async fn announce_pending<T: CacheHttp + 'static>(
+        &self,
+        data: &mut TaskData<T>,
+        in_flight: &mut InFlight,
+        pending: impl IntoIterator<Item = (i64, PendingAnnouncement)>,
+    ) -> Result<(), sqlx::Error> {
+        let mut announced = vec![];
+
+        let reports = pending
+            .into_iter()
+            .map(|(guild_id, pending)| {
+                let reports = pending
+                    .events
+                    .into_iter()
+                    .map(|event| {
+                        announced.push(event.id);
+                        event.report
+                    })
+                    .collect();
+
+                (guild_id, reports)
+            })
+            .collect();
+
+        self.announce(data, reports).await?;
+
+        for event_id in announced {
+            in_flight.finish(event_id);
+        }
+
+        data.outbox.handled(in_flight.handled()).await
+    }
+
+    /// Sends out the pooled reports of each guild to its announcement channel.
+    async fn announce<T: CacheHttp + 'static>(
+        &self,
+        data: &mut TaskData<T>,
+        reports: HashMap<i64, Vec<UserReport>>,
+    ) -> Result<(), sqlx::Error> {
+        log::info!("Grabbing announcement channels...");
+
+        let guild_ids = reports.keys().copied().collect::<Vec<_>>();
+
+        // the settings are read again in case they changed while pooling
+        let channels = data.store.announcement_channels(Some(&guild_ids)).await?;
+
+        if channels.is_empty() {
+            log::info!("No announcement channels found, skipping announcement generation.");
+            return Ok(());
+        }
+
+        log::info!("Generating announcements...");
+
+        // get summary of the current escalator statuses, in every locale that needs one
+        let mut gists = HashMap::<Locale, serenity::CreateEmbed>::new();
+
+        for channel in &channels {
+            let locale = channel.settings.locale;
+
+            if channel.settings.include_gist && !gists.contains_key(&locale) {
+                gists.insert(locale, generate::gist(&*data.store, locale).await?);
             }
+        }
+
+        // send embeds to history channels
+        log::info!("Sending announcements...");
+
+        let mut send_all = vec![];
+
+        for channel in channels {
+            let Some(reports) = reports.get(&channel.guild_id) else {
+                continue;
+            };
+
+            let settings = channel.settings;
+            let locale = settings.locale;
+
+            let embed = match gists.get(&locale) {
+                Some(gist) if settings.include_gist => gist.clone(),
+                _ => serenity::CreateEmbed::default(),
+            };
 
-            log::info!("Generating announcement...");
+            let reports = match settings.mode {
+                AnnouncementMode::Post => generate::announcement(
+                    settings.max_reports_displayed,
+                    reports.iter().rev(),
+                    locale,
+                ),
+                AnnouncementMode::Live => {
+                    // keep track of the last few reports, since the message gets replaced
+                    let live_reports = data.live_reports.entry(channel.guild_id).or_default();
+                    live_reports.extend(reports.iter().cloned());
 
-            // get summary of the current escalator statuses
-            let embed = generate::gist(&data.pool).await?;
+                    let excess = live_reports
+                        .len()
+                        .saturating_sub(settings.max_reports_displayed);
+                    live_reports.drain(..excess);
 
-            let reports = generate::announcement(self.max_reports_displayed, reports.iter().rev());
+                    generate::announcement(
+                        settings.max_reports_displayed,
+                        live_reports.iter().rev(),
+                        locale,
+                    )
+                }
+            };
 
             let embed = embed.timestamp(chrono::Utc::now()).field(
-                "Recent reports (newest first)",
+                locale.catalog().recent_reports(),
                 reports,
                 false,
             );
 
-            // send embed to history channel
-            log::info!("Sending announcement...");
+            let store = Arc::clone(&data.store);
+            let cache_http = Arc::clone(&data.cache_http);
 
-            let send_all = channels.into_iter()
-                .map(|(channel_id,)| {
-                    let channel = serenity::ChannelId::new(channel_id as u64);
-                    let embed = embed.clone();
-                    let cache_http = Arc::clone(&data.cache_http);
+            send_all.push(async move {
+                match settings.mode {
+                    AnnouncementMode::Post => post_announcement(&cache_http, &channel, embed).await,
+                    AnnouncementMode::Live => {
+                        let res = update_live_message(&*store, &cache_http, &channel, embed).await;
 
-                    async move {
-                        let msg = CreateMessage::new().embed(embed);
-                        let res = channel.send_message(&cache_http, msg)
-                            .await;
+                        if let Err(err) = res {
+                            log::warn!(
+                                "An error ocurred trying to update the live message in the channel <#{}>: {err}",
+                                channel.channel_id,
+                            );
+                        }
+                    }
+                }
+            });
+        }
 
-                        match res {
-                            Ok(msg) => {
-                                let Ok(channel) = msg.channel(&cache_http)
-                                    .await else { return };
+        join_all(send_all).await;
 
-                                let Some(channel) = channel.guild() else { return };
+        Ok(())
+    }
+}
 
-                                if channel.kind == serenity::ChannelType::News {
-                                    let _ = msg.crosspost(&cache_http).await.ok();
-                                }
-                            }
-                            Err(err) => log::warn!("An error ocurred trying to send a message in the channel <#{channel_id}>: {err}"),
-                        }
-                    }
-                });
+/// Loads the last few reports announced in each guild's live message, up to the given one,
+/// since the message is replaced with only the reports it's kept track of.
+async fn load_live_reports(
+    store: &dyn Store,
+    announced: i64,
+) -> Result<HashMap<i64, VecDeque<UserReport>>, sqlx::Error> {
+    let channels = store
+        .announcement_channels(None)
+        .await?
+        .into_iter()
+        .filter(|channel| channel.settings.mode == AnnouncementMode::Live)
+        .collect::<Vec<_>>();
+
+    let Some(max) = channels
+        .iter()
+        .map(|channel| channel.settings.max_reports_displayed)
+        .max()
+    else {
+        return Ok(HashMap::new());
+    };
+
+    let events = store.events_until(announced, max).await?;
+
+    let live_reports = channels
+        .into_iter()
+        .map(|channel| {
+            let skipped = events
+                .len()
+                .saturating_sub(channel.settings.max_reports_displayed);
+
+            let reports = events[skipped..]
+                .iter()
+                .map(|event| event.report.clone())
+                .collect();
+
+            (channel.guild_id, reports)
+        })
+        .collect();
+
+    Ok(live_reports)
+}
+
+/// Sends an announcement as a new message, crossposting it if enabled.
+async fn post_announcement(
+    cache_http: &impl CacheHttp,
+    channel: &AnnouncementChannel,
+    embed: serenity::CreateEmbed,
+) {
+    let channel_id = channel.channel_id;
+    let crosspost = channel.settings.crosspost;
+    let channel = serenity::ChannelId::new(channel_id as u64);
+
+    let msg = CreateMessage::new().embed(embed);
+    let res = channel.send_message(cache_http, msg).await;
 
-            join_all(send_all).await;
+    match res {
+        Ok(msg) => {
+            if !crosspost {
+                return;
+            }
+
+            let Ok(channel) = msg.channel(cache_http).await else {
+                return;
+            };
+
+            let Some(channel) = channel.guild() else {
+                return;
+            };
+
+            if channel.kind == serenity::ChannelType::News {
+                let _ = msg.crosspost(cache_http).await.ok();
+            }
         }
+        Err(err) => log::warn!(
+            "An error ocurred trying to send a message in the channel <#{channel_id}>: {err}"
+        ),
     }
 }
 
-impl AnnounceTask {
-    async fn accumulate_reports(
-        &self,
-        reports: &mut broadcast::Receiver<UserReport>,
-    ) -> anyhow::Result<Vec<UserReport>> {
-        let mut accumulated = loop {
-            match reports.recv().await {
-                Ok(report) => {
-                    break vec![report];
-                }
-                // if the channel closed (for some reason) then stop the loop
-                Err(broadcast::error::RecvError::Closed) => {
-                    anyhow::bail!("Update receiver has closed.");
-                }
-                // if the receiver is lagging beind, restart the loop and try receiving again
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    log::warn!("Update receiver has lagged by {n} updates.");
-                }
-            };
+/// Edits the guild's live message with the announcement,
+/// sending (and pinning) a new one if it doesn't exist or can't be edited.
+async fn update_live_message(
+    store: &dyn Store,
+    cache_http: &impl CacheHttp,
+    channel: &AnnouncementChannel,
+    embed: serenity::CreateEmbed,
+) -> Result<(), Error> {
+    let live_message = store
+        .live_message(channel.guild_id, channel.channel_id)
+        .await?;
+
+    let channel_id = serenity::ChannelId::new(channel.channel_id as u64);
+
+    if let Some(message_id) = live_message {
+        let edit = EditMessage::new().embed(embed.clone());
+
+        match cache_http
+            .http()
+            .edit_message(channel_id, message_id, &edit, vec![])
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(err) => log::info!("Failed to edit live message, sending a new one: {err}"),
+        }
+    }
+
+    let msg = CreateMessage::new().embed(embed);
+    let msg = channel_id.send_message(cache_http, msg).await?;
+
+    if let Err(err) = msg.pin(cache_http).await {
+        log::warn!("An error ocurred trying to pin the live message: {err}");
+    }
+
+    store
+        .set_live_message(channel.guild_id, channel.channel_id, msg.id)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bot_tasks::fake::{Action, FakeDiscord, GUILD_ID},
+        data::{
+            escalator_input::EscalatorInput,
+            settings::GuildSettings,
+            status::Status,
+            store::{ChannelStore, MemoryStore},
+        },
+    };
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+
+    const CHANNEL: serenity::ChannelId = serenity::ChannelId::new(20);
+
+    /// Starts the task for a guild announcing in [`CHANNEL`] without pooling reports.
+    async fn start(mode: AnnouncementMode) -> (Data, Arc<FakeDiscord>) {
+        let (data, discord, _) = start_with_delay(mode, Duration::ZERO).await;
+        (data, discord)
+    }
+
+    /// Starts the task for a guild announcing in [`CHANNEL`], pooling reports for the delay.
+    async fn start_with_delay(
+        mode: AnnouncementMode,
+        delay: Duration,
+    ) -> (Data, Arc<FakeDiscord>, JoinHandle<anyhow::Result<()>>) {
+        let guild_id = serenity::GuildId::new(GUILD_ID);
+
+        let store = Arc::new(MemoryStore::default());
+        store.set_guild_settings(
+            guild_id,
+            GuildSettings {
+                delay,
+                mode,
+                ..Default::default()
+            },
+        );
+        store
+            .set_announcement_channel(guild_id, CHANNEL)
+            .await
+            .unwrap();
+
+        let data = Data::new(store);
+        let discord = Arc::new(FakeDiscord::start().await);
+
+        let task_data = AnnounceTask
+            .setup(&data, Arc::clone(&discord))
+            .await
+            .unwrap();
+        let run = tokio::spawn(AnnounceTask.run(task_data));
+
+        (data, discord, run)
+    }
+
+    /// Reports the 4-6 escalator, like the report menu does.
+    async fn report(data: &Data, status: Status) {
+        let escalators = EscalatorInput::Direct(4, 6);
+        let affected_escalators = data
+            .store
+            .commit_report(Some(serenity::UserId::new(10)), escalators, status)
+            .await
+            .unwrap();
+
+        data.send_message(UserReport {
+            reporter: Some(serenity::UserId::new(10)),
+            escalators,
+            affected_escalators,
+            new_status: status,
+        });
+    }
+
+    #[tokio::test]
+    async fn announcements_are_crossposted_in_news_channels() {
+        let (data, discord) = start(AnnouncementMode::Post).await;
+        discord.news_channel(CHANNEL);
+
+        report(&data, Status::Down).await;
+
+        let actions = discord.wait_for(2).await;
+
+        let Action::Message {
+            channel_id,
+            message_id,
+            payload,
+        } = &actions[0]
+        else {
+            panic!("Expected an announcement, got {actions:?}");
         };
+        assert_eq!(*channel_id, CHANNEL);
+        assert!(payload["embeds"][0]["fields"][0]["value"]
+            .as_str()
+            .unwrap()
+            .contains("4-6"));
 
-        log::info!(
-            "Received update, pooling for {} seconds before announcing.",
-            self.delay.as_secs()
+        assert_eq!(
+            actions[1],
+            Action::Crosspost {
+                channel_id: CHANNEL,
+                message_id: *message_id,
+            }
         );
+    }
 
-        let sleep = tokio::time::sleep(self.delay);
-        tokio::pin!(sleep);
+    #[tokio::test]
+    async fn live_messages_are_edited() {
+        let (data, discord) = start(AnnouncementMode::Live).await;
 
-        // continue to save the updates until the interval is up
-        loop {
-            tokio::select! {
-                Ok(report) = reports.recv() => accumulated.push(report),
-                () = &mut sleep => break,
+        report(&data, Status::Down).await;
+
+        let actions = discord.wait_for(2).await;
+
+        let Action::Message { message_id, .. } = actions[0] else {
+            panic!("Expected a live message, got {actions:?}");
+        };
+        assert_eq!(
+            actions[1],
+            Action::Pin {
+                channel_id: CHANNEL,
+                message_id,
             }
-        }
+        );
+
+        report(&data, Status::Open).await;
+
+        let actions = discord.wait_for(3).await;
+
+        // the live message shows both reports
+        let Action::Edit {
+            message_id: edited,
+            payload,
+            ..
+        } = &actions[2]
+        else {
+            panic!("Expected the live message to be edited, got {actions:?}");
+        };
+        assert_eq!(*edited, message_id);
+        assert_eq!(
+            payload["embeds"][0]["fields"][0]["value"]
+                .as_str()
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn live_messages_keep_their_reports_after_restarting() {
+        let (data, discord, run) = start_with_delay(AnnouncementMode::Live, Duration::ZERO).await;
+
+        report(&data, Status::Down).await;
+        discord.wait_for(2).await;
+
+        data.send_message(Shutdown);
+        run.await.unwrap().unwrap();
 
-        Ok(accumulated)
+        let task_data = AnnounceTask
+            .setup(&data, Arc::clone(&discord))
+            .await
+            .unwrap();
+        tokio::spawn(AnnounceTask.run(task_data));
+
+        report(&data, Status::Open).await;
+
+        let actions = discord.wait_for(3).await;
+
+        // the report from before restarting is still shown
+        let Action::Edit { payload, .. } = &actions[2] else {
+            panic!("Expected the live message to be edited, got {actions:?}");
+        };
+        assert_eq!(
+            payload["embeds"][0]["fields"][0]["value"]
+                .as_str()
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn deleted_live_messages_are_replaced() {
+        let (data, discord) = start(AnnouncementMode::Live).await;
+
+        report(&data, Status::Down).await;
+
+        let actions = discord.wait_for(2).await;
+        let Action::Message { message_id, .. } = actions[0] else {
+            panic!("Expected a live message, got {actions:?}");
+        };
+
+        discord.delete_message(message_id);
+        report(&data, Status::Open).await;
+
+        let actions = discord.wait_for(4).await;
+        let Action::Message {
+            message_id: replacement,
+            ..
+        } = actions[2]
+        else {
+            panic!("Expected a new live message, got {actions:?}");
+        };
+        assert_ne!(replacement, message_id);
+        assert_eq!(
+            actions[3],
+            Action::Pin {
+                channel_id: CHANNEL,
+                message_id: replacement,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn pending_announcements_are_sent_on_shutdown() {
+        let (data, discord, run) =
+            start_with_delay(AnnouncementMode::Post, Duration::from_secs(60 * 60)).await;
+
+        report(&data, Status::Down).await;
+        data.send_message(Shutdown);
+
+        run.await.unwrap().unwrap();
+
+        let actions = discord.actions();
+        assert!(
+            matches!(actions[..], [Action::Message { channel_id, .. }] if channel_id == CHANNEL),
+            "Expected the pending announcement, got {actions:?}"
+        );
     }
 }
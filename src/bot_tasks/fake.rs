@@ -0,0 +1,356 @@
+//! A stand-in for Discord, so tasks can be run end to end without the network.
+//!
+//! Requests are sent to a local server (with serenity's proxy option) which records
+//! what the bot did and answers with just enough for serenity to carry on.
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    sync::Arc,
+    time::Duration,
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    header, Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use parking_lot::Mutex;
+use poise::serenity_prelude::{CacheHttp, ChannelId, Http, HttpBuilder, MessageId, UserId};
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::Notify};
+
+/// How long to wait for the bot to do something before failing the test.
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// The guild every channel is in.
+pub const GUILD_ID: u64 = 1;
+
+/// Something the bot did on Discord.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Dm {
+        user_id: UserId,
+        content: String,
+    },
+    Message {
+        channel_id: ChannelId,
+        message_id: MessageId,
+        payload: Value,
+    },
+    Edit {
+        channel_id: ChannelId,
+        message_id: MessageId,
+        payload: Value,
+    },
+    Crosspost {
+        channel_id: ChannelId,
+        message_id: MessageId,
+    },
+    Pin {
+        channel_id: ChannelId,
+        message_id: MessageId,
+    },
+}
+
+pub struct FakeDiscord {
+    http: Http,
+    state: Arc<Mutex<State>>,
+    changed: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct State {
+    actions: Vec<Action>,
+    news_channels: HashSet<ChannelId>,
+    deleted_messages: HashSet<MessageId>,
+    dm_channels: HashMap<ChannelId, UserId>,
+    next_id: u64,
+}
+
+impl FakeDiscord {
+    /// Starts serving requests in the background, until the test's runtime shuts down.
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let state = Arc::new(Mutex::new(State {
+            // leaves room for the IDs tests pick themselves
+            next_id: 1_000_000,
+            ..Default::default()
+        }));
+        let changed = Arc::new(Notify::new());
+
+        tokio::spawn({
+            let state = Arc::clone(&state);
+            let changed = Arc::clone(&changed);
+
+            async move {
+                loop {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    let state = Arc::clone(&state);
+                    let changed = Arc::clone(&changed);
+
+                    tokio::spawn(async move {
+                        let service = hyper::service::service_fn(|req| {
+                            let state = Arc::clone(&state);
+                            let changed = Arc::clone(&changed);
+                            async move { Ok::<_, Infallible>(route(&state, &changed, req).await) }
+                        });
+
+                        let _ = hyper::server::conn::http1::Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                    });
+                }
+            }
+        });
+
+        let http = HttpBuilder::new("fake-token")
+            .proxy(format!("http://{addr}"))
+            .ratelimiter_disabled(true)
+            .build();
+
+        Self {
+            http,
+            state,
+            changed,
+        }
+    }
+
+    /// Makes a channel a News channel, so announcements sent in it can be crossposted.
+    pub fn news_channel(&self, channel_id: ChannelId) {
+        self.state.lock().news_channels.insert(channel_id);
+    }
+
+    /// Deletes a message, so it can't be edited anymore.
+    pub fn delete_message(&self, message_id: MessageId) {
+        self.state.lock().deleted_messages.insert(message_id);
+    }
+
+    /// Everything the bot has done so far.
+    pub fn actions(&self) -> Vec<Action> {
+        self.state.lock().actions.clone()
+    }
+
+    /// Waits until the bot has done at least `count` things, returning all of them.
+    pub async fn wait_for(&self, count: usize) -> Vec<Action> {
+        let wait = async {
+            loop {
+                let changed = self.changed.notified();
+
+                let actions = self.actions();
+                if actions.len() >= count {
+                    return actions;
+                }
+
+                changed.await;
+            }
+        };
+
+        match tokio::time::timeout(TIMEOUT, wait).await {
+            Ok(actions) => actions,
+            Err(_) => panic!(
+                "Expected {count} actions, but only got {:?}",
+                self.actions()
+            ),
+        }
+    }
+}
+
+impl CacheHttp for FakeDiscord {
+    fn http(&self) -> &Http {
+        &self.http
+    }
+}
+
+/// Handles a request to one of the routes the tasks use, recording what it does.
+async fn route(
+    state: &Mutex<State>,
+    changed: &Notify,
+    req: Request<Incoming>,
+) -> Response<Full<Bytes>> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_owned();
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    let body = req.into_body().collect().await.unwrap().to_bytes();
+    let payload = parse_payload(&content_type, &body);
+
+    let segments = path
+        .trim_start_matches("/api/v10/")
+        .split('/')
+        .collect::<Vec<_>>();
+
+    let mut state = state.lock();
+
+    let (status, res) = match (&method, segments.as_slice()) {
+        (&Method::POST, ["users", "@me", "channels"]) => {
+            let user_id = snowflake(&payload["recipient_id"]);
+            let channel_id = ChannelId::new(state.next_id());
+            state.dm_channels.insert(channel_id, UserId::new(user_id));
+
+            let channel = json!({
+                "id": channel_id.to_string(),
+                "type": 1,
+                "recipients": [user(user_id)],
+            });
+
+            (StatusCode::OK, channel)
+        }
+        (&Method::POST, ["channels", channel_id, "messages"]) => {
+            let channel_id = ChannelId::new(channel_id.parse().unwrap());
+            let message_id = MessageId::new(state.next_id());
+
+            let action = match state.dm_channels.get(&channel_id) {
+                Some(&user_id) => Action::Dm {
+                    user_id,
+                    content: payload["content"].as_str().unwrap_or_default().to_owned(),
+                },
+                None => Action::Message {
+                    channel_id,
+                    message_id,
+                    payload: payload.clone(),
+                },
+            };
+            state.actions.push(action);
+
+            (StatusCode::OK, message(channel_id, message_id, &payload))
+        }
+        (&Method::PATCH, ["channels", channel_id, "messages", message_id]) => {
+            let channel_id = ChannelId::new(channel_id.parse().unwrap());
+            let message_id = MessageId::new(message_id.parse().unwrap());
+
+            if state.deleted_messages.contains(&message_id) {
+                (StatusCode::NOT_FOUND, error(10008, "Unknown Message"))
+            } else {
+                state.actions.push(Action::Edit {
+                    channel_id,
+                    message_id,
+                    payload: payload.clone(),
+                });
+
+                (StatusCode::OK, message(channel_id, message_id, &payload))
+            }
+        }
+        (&Method::POST, ["channels", channel_id, "messages", message_id, "crosspost"]) => {
+            let channel_id = ChannelId::new(channel_id.parse().unwrap());
+            let message_id = MessageId::new(message_id.parse().unwrap());
+
+            state.actions.push(Action::Crosspost {
+                channel_id,
+                message_id,
+            });
+
+            (
+                StatusCode::OK,
+                message(channel_id, message_id, &Value::Null),
+            )
+        }
+        (&Method::PUT, ["channels", channel_id, "pins", message_id]) => {
+            state.actions.push(Action::Pin {
+                channel_id: ChannelId::new(channel_id.parse().unwrap()),
+                message_id: MessageId::new(message_id.parse().unwrap()),
+            });
+
+            (StatusCode::NO_CONTENT, Value::Null)
+        }
+        (&Method::GET, ["channels", channel_id]) => {
+            let channel_id = ChannelId::new(channel_id.parse().unwrap());
+            let news = state.news_channels.contains(&channel_id);
+
+            let channel = json!({
+                "id": channel_id.to_string(),
+                "type": if news { 5 } else { 0 },
+                "guild_id": GUILD_ID.to_string(),
+                "name": "announcements",
+            });
+
+            (StatusCode::OK, channel)
+        }
+        _ => (StatusCode::NOT_FOUND, error(0, "Not handled by the fake")),
+    };
+
+    drop(state);
+    changed.notify_waiters();
+
+    let body = match res {
+        Value::Null => Bytes::new(),
+        res => Bytes::from(res.to_string()),
+    };
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Full::new(body))
+        .unwrap()
+}
+
+impl State {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// Reads the JSON payload of a request, which is in its own part if files are attached.
+fn parse_payload(content_type: &str, body: &[u8]) -> Value {
+    let body = String::from_utf8_lossy(body);
+
+    let json = match content_type.split_once("boundary=") {
+        Some((_, boundary)) => body
+            .split(&format!("--{boundary}"))
+            .find(|part| part.contains("name=\"payload_json\""))
+            .and_then(|part| part.split_once("\r\n\r\n"))
+            .map_or("", |(_, json)| json.trim_end()),
+        None => &body,
+    };
+
+    serde_json::from_str(json).unwrap_or(Value::Null)
+}
+
+/// Reads an ID, which serenity sends as a string.
+fn snowflake(value: &Value) -> u64 {
+    match value {
+        Value::String(id) => id.parse().unwrap(),
+        id => id.as_u64().unwrap(),
+    }
+}
+
+fn user(user_id: u64) -> Value {
+    json!({
+        "id": user_id.to_string(),
+        "username": "user",
+        "avatar": null,
+    })
+}
+
+fn message(channel_id: ChannelId, message_id: MessageId, payload: &Value) -> Value {
+    json!({
+        "id": message_id.to_string(),
+        "channel_id": channel_id.to_string(),
+        "author": user(1),
+        "content": payload["content"].as_str().unwrap_or_default(),
+        "timestamp": "2026-10-18T12:00:00+00:00",
+        "edited_timestamp": null,
+        "tts": false,
+        "mention_everyone": false,
+        "mentions": [],
+        "mention_roles": [],
+        "attachments": [],
+        "embeds": [],
+        "pinned": false,
+        "type": 0,
+    })
+}
+
+fn error(code: u32, message: &str) -> Value {
+    json!({
+        "code": code,
+        "message": message,
+    })
+}
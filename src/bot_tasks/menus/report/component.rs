@@ -3,10 +3,12 @@ use poise::serenity_prelude::{CreateActionRow, CreateButton};
 use crate::{
     data::{escalator_input::EscalatorInput, status::Status},
     generate::REPORT_EMOJI,
+    locale::Locale,
     prelude::*,
 };
 
 use super::Report;
+use itertools::Itertools;
 use std::{ops::BitOr, str::FromStr};
 
 const SELECTED_BUTTON_STYLE: serenity::ButtonStyle = serenity::ButtonStyle::Primary;
@@ -19,10 +21,16 @@ const SUBMIT_BUTTON_ID: &str = "REPORT-SUBMIT";
 const NUMBER_BUTTON_ID_PREFIX: &str = "REPORT-FLOOR-";
 const STATUS_BUTTON_ID_PREFIX: &str = "REPORT-STATUS-";
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct ReportComponent {
     escalators: EscalatorComponent,
     status: Option<Status>,
+    /// The escalators that can be reported.
+    available: Vec<EscalatorFloors>,
+    /// Whether or not `All` can be reported,
+    /// which is only the case if every escalator is available.
+    all_available: bool,
+    locale: Locale,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,6 +42,9 @@ enum EscalatorComponent {
     All,
 }
 
+/// The escalators that can be reported, used for checking floor selections.
+struct Available<'a>(&'a [EscalatorFloors]);
+
 pub enum ComponentStatus<T> {
     Continue,
     Complete(T),
@@ -59,10 +70,13 @@ enum ButtonState {
 }
 
 impl ReportComponent {
-    pub fn new() -> Self {
+    pub fn new(available: Vec<EscalatorFloors>, all_available: bool, locale: Locale) -> Self {
         Self {
             escalators: EscalatorComponent::new(),
             status: None,
+            available,
+            all_available,
+            locale,
         }
     }
 
@@ -70,7 +84,12 @@ impl ReportComponent {
         let mut components = vec![];
 
         // add escalator components
-        components.append(&mut self.escalators.render());
+        let available = Available(&self.available);
+        components.append(
+            &mut self
+                .escalators
+                .render(&available, self.all_available, self.locale),
+        );
 
         // selecting status
         let mut buttons = vec![];
@@ -91,8 +110,12 @@ impl ReportComponent {
 
     pub fn execute(&mut self, command: ComponentAction) -> ComponentStatus<Report> {
         match command {
+            ComponentAction::Escalator(EscalatorAction::All) if !self.all_available => {
+                ComponentStatus::Continue
+            }
             ComponentAction::Escalator(command) => {
-                self.escalators.execute(command);
+                self.escalators
+                    .execute(command, &Available(&self.available));
                 ComponentStatus::Continue
             }
             ComponentAction::Status(status) => {
@@ -114,22 +137,38 @@ impl ReportComponent {
     }
 
     fn try_as_report(&self) -> Option<Report> {
-        let escalators = self.escalators.try_as_escalators()?;
+        let escalators = self.try_as_escalators()?;
         let status = self.status?;
 
         Some(Report { escalators, status })
     }
 
+    /// The selected escalators, as long as every one of them can be reported.
+    fn try_as_escalators(&self) -> Option<EscalatorInput> {
+        let escalators = self.escalators.try_as_escalators()?;
+        let available = Available(&self.available);
+
+        let valid = match escalators {
+            EscalatorInput::All => self.all_available,
+            EscalatorInput::Pair(start, end) => available.allows(start, end, true),
+            EscalatorInput::Direct(start, end) => available.allows(start, end, false),
+        };
+
+        valid.then_some(escalators)
+    }
+
     fn create_submit_button(&self) -> serenity::CreateButton {
-        let Some(escalators) = self.escalators.try_as_escalators() else {
-            return Self::disabled_submit_button("Select Escalator(s)");
+        let catalog = self.locale.catalog();
+
+        let Some(escalators) = self.try_as_escalators() else {
+            return Self::disabled_submit_button(catalog.select_escalators());
         };
 
         if self.status.is_none() {
-            return Self::disabled_submit_button("Select Status");
+            return Self::disabled_submit_button(catalog.select_status());
         }
 
-        let label = format!("Report {}!", escalators.short_noun());
+        let label = catalog.submit_report(&escalators);
 
         CreateButton::new(SUBMIT_BUTTON_ID)
             .label(label)
@@ -170,64 +209,84 @@ impl EscalatorComponent {
         }
     }
 
-    fn render(&self) -> Vec<CreateActionRow> {
-        const NUMBER_PANEL: [[u8; 4]; 2] = [[2, 4, 6, 8], [3, 5, 7, 9]];
-
-        let mut components = vec![];
-
-        for (row, numbers) in NUMBER_PANEL.into_iter().enumerate() {
-            let mut buttons = vec![];
-
-            for floor in numbers {
-                buttons.push(self.create_floor_button(floor));
+    fn render(
+        &self,
+        available: &Available,
+        all_available: bool,
+        locale: Locale,
+    ) -> Vec<CreateActionRow> {
+        const FLOORS_PER_ROW: usize = 4;
+
+        // even floors go on the top rows, and odd floors on the bottom rows
+        let (evens, odds): (Vec<_>, Vec<_>) = available
+            .floors()
+            .into_iter()
+            .partition(|floor| floor % 2 == 0);
+
+        let mut rows = evens
+            .chunks(FLOORS_PER_ROW)
+            .chain(odds.chunks(FLOORS_PER_ROW))
+            .map(|floors| {
+                floors
+                    .iter()
+                    .map(|&floor| self.create_floor_button(floor, available))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        // the pair button goes on the top row, and the all button on the bottom row
+        match rows.as_mut_slice() {
+            [] => rows.push(vec![
+                self.create_pair_button(available, locale),
+                self.create_all_button(all_available, locale),
+            ]),
+            [row] => {
+                row.push(self.create_pair_button(available, locale));
+                rows.push(vec![self.create_all_button(all_available, locale)]);
+            }
+            [first, .., last] => {
+                first.push(self.create_pair_button(available, locale));
+                last.push(self.create_all_button(all_available, locale));
             }
-
-            let button = match row {
-                // top row
-                0 => self.create_pair_button(),
-                // bottom row
-                1 => self.create_all_button(),
-                _ => unreachable!(),
-            };
-
-            buttons.push(button);
-
-            components.push(CreateActionRow::Buttons(buttons));
         }
 
-        components
+        rows.into_iter().map(CreateActionRow::Buttons).collect()
     }
 
-    fn execute(&mut self, command: EscalatorAction) {
+    fn execute(&mut self, command: EscalatorAction, available: &Available) {
         match command {
             EscalatorAction::Pair => {
-                if let Self::Floors { pair, .. } = self {
-                    *pair = !*pair
+                if self.can_toggle_pair(available) {
+                    if let Self::Floors { pair, .. } = self {
+                        *pair = !*pair
+                    }
                 }
             }
             EscalatorAction::All => match self {
                 Self::Floors { .. } => *self = Self::All,
                 Self::All => *self = Self::new(),
             },
-            EscalatorAction::Floor(floor) => self.toggle_floor(floor),
+            EscalatorAction::Floor(floor) => self.toggle_floor(floor, available),
         }
     }
 
-    fn create_pair_button(&self) -> serenity::CreateButton {
-        ButtonState::disabled_if(self.is_all())
-            .or_else(|| ButtonState::selected_if(self.is_pair()))
-            .create_button("Pair", PAIR_BUTTON_ID)
+    fn create_pair_button(&self, available: &Available, locale: Locale) -> serenity::CreateButton {
+        ButtonState::selected_if(self.is_pair())
+            .or_else(|| ButtonState::disabled_if(!self.can_toggle_pair(available)))
+            .create_button(locale.catalog().pair_button(), PAIR_BUTTON_ID)
     }
 
-    fn create_all_button(&self) -> serenity::CreateButton {
-        ButtonState::selected_if(self.is_all()).create_button("All", ALL_BUTTON_ID)
+    fn create_all_button(&self, all_available: bool, locale: Locale) -> serenity::CreateButton {
+        ButtonState::disabled_if(!all_available)
+            .or_else(|| ButtonState::selected_if(self.is_all()))
+            .create_button(locale.catalog().all_button(), ALL_BUTTON_ID)
     }
 
-    fn create_floor_button(&self, floor: u8) -> serenity::CreateButton {
+    fn create_floor_button(&self, floor: u8, available: &Available) -> serenity::CreateButton {
         let id = format!("{}{}", NUMBER_BUTTON_ID_PREFIX, floor);
 
         ButtonState::selected_if(self.is_floor_selected(floor))
-            .or_else(|| ButtonState::disabled_if(!self.is_valid_next_floor(floor)))
+            .or_else(|| ButtonState::disabled_if(!self.is_valid_next_floor(floor, available)))
             .create_button(floor, id)
     }
 
@@ -245,9 +304,23 @@ impl EscalatorComponent {
         }
     }
 
-    fn toggle_floor(&mut self, floor: u8) {
+    /// Checks if the pair can be selected, which is only the case if the escalator
+    /// going the other way is also available. It can always be unselected.
+    fn can_toggle_pair(&self, available: &Available) -> bool {
+        match self {
+            Self::Floors { pair: true, .. } => true,
+            &Self::Floors {
+                floors: Some((start, Some(end))),
+                ..
+            } => available.allows(start, end, true),
+            Self::Floors { .. } => true,
+            Self::All => false,
+        }
+    }
+
+    fn toggle_floor(&mut self, floor: u8, available: &Available) {
         match self {
-            Self::Floors { floors, .. } => {
+            Self::Floors { floors, pair } => {
                 // if no floors are selected, set the start to the selected floor
                 let Some((start, maybe_end)) = floors else {
                     *floors = Some((floor, None));
@@ -271,7 +344,7 @@ impl EscalatorComponent {
                 let Some(end) = maybe_end else {
                     // if the start and selected floor create a valid escalator,
                     // set the end to the selected floor
-                    if is_valid_escalator(*start, floor) {
+                    if available.allows(*start, floor, *pair) {
                         *maybe_end = Some(floor);
                     }
                     return;
@@ -296,10 +369,10 @@ impl EscalatorComponent {
         }
     }
 
-    fn is_valid_next_floor(&self, floor: u8) -> bool {
+    fn is_valid_next_floor(&self, floor: u8, available: &Available) -> bool {
         match self {
-            Self::Floors { floors, .. } => match floors {
-                Some((start, None)) => is_valid_escalator(*start, floor),
+            Self::Floors { floors, pair } => match floors {
+                Some((start, None)) => available.allows(*start, floor, *pair),
                 Some((_, Some(_))) => false,
                 None => true,
             },
@@ -308,16 +381,28 @@ impl EscalatorComponent {
     }
 }
 
-/// Checks if a pair of floors makes a valid escalator.
-/// Currently, this doesn't try to make sure it is accurate
-/// to the "escalators" table, so if that was to ever change,
-/// this would need to be updated.
-fn is_valid_escalator(start: u8, end: u8) -> bool {
-    // if the start and end are valid floors...
-    ((2..=9).contains(&start) && (2..=9).contains(&end))
-        // ...and the distance between the two floors is 2
-        //                           ...or it is the 2-3 or 3-2
-        && (start.abs_diff(end) == 2 || matches!((start, end), (2, 3) | (3, 2)))
+impl Available<'_> {
+    /// Every floor an available escalator starts or ends at, in ascending order.
+    fn floors(&self) -> Vec<u8> {
+        self.0
+            .iter()
+            .flat_map(|floors| [floors.start, floors.end])
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Checks if the escalator from `start` to `end` is available,
+    /// along with the one going the other way if it's being reported as a pair.
+    fn allows(&self, start: u8, end: u8, pair: bool) -> bool {
+        self.contains(start, end) && (!pair || self.contains(end, start))
+    }
+
+    fn contains(&self, start: u8, end: u8) -> bool {
+        self.0
+            .iter()
+            .any(|floors| (floors.start, floors.end) == (start, end))
+    }
 }
 
 impl FromStr for ComponentAction {
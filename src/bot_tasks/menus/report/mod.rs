@@ -2,25 +2,31 @@ mod component;
 
 use crate::{
     bot_tasks::BotTask,
-    data::{escalator_input::EscalatorInput, report::UserReport, status::Status},
+    data::{
+        escalator_input::EscalatorInput, report::UserReport, settings::GuildSettings,
+        status::Status, store::Store,
+    },
     generate::{self, REPORT_BUTTON_ID},
+    locale::Locale,
+    metrics,
     prelude::*,
     ComponentMessage,
 };
 
 use chrono::prelude::*;
 use chrono_tz::America::New_York as NYCTimeZone;
-use futures::{StreamExt, TryStreamExt};
+use futures::StreamExt;
 use poise::serenity_prelude::{
     CacheHttp, CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
 };
 use std::{sync::Arc, time::Duration};
 use tokio::sync::broadcast::{self, error::RecvError};
 
+#[derive(Clone)]
 pub struct ReportTask;
 
 pub struct TaskData<T> {
-    pool: sqlx::PgPool,
+    store: Arc<dyn Store>,
     interactions: broadcast::Receiver<Arc<ComponentMessage>>,
     reporter: broadcast::Sender<UserReport>,
     cache_http: Arc<T>,
@@ -33,19 +39,19 @@ pub struct Report {
 }
 
 impl<T: CacheHttp + 'static> BotTask<T> for ReportTask {
+    const NAME: &'static str = "report";
     type Data = TaskData<T>;
-    type Term = anyhow::Result<()>;
 
     async fn setup(&self, data: &Data, cache_http: Arc<T>) -> Option<Self::Data> {
         Some(TaskData {
-            pool: data.pool.clone(),
+            store: Arc::clone(&data.store),
             interactions: data.receiver(),
             reporter: data.sender(),
             cache_http,
         })
     }
 
-    async fn run(self, mut data: Self::Data) -> Self::Term {
+    async fn run(self, mut data: Self::Data) -> anyhow::Result<()> {
         loop {
             let event = match data.interactions.recv().await {
                 Ok(event) if event.interaction.data.custom_id == REPORT_BUTTON_ID => event,
@@ -53,22 +59,29 @@ impl<T: CacheHttp + 'static> BotTask<T> for ReportTask {
                 Err(RecvError::Closed) => return Ok(()),
                 Err(RecvError::Lagged(n)) => {
                     log::warn!("Interaction receiver lagged behind by {n} values.");
+                    metrics::receiver_lagged("report");
                     continue;
                 }
             };
 
             log::info!("Received REPORT interaction");
 
-            let nyc_now = Utc::now().with_timezone(&NYCTimeZone);
+            let settings = match event.interaction.guild_id {
+                Some(guild_id) => data.store.guild_settings(guild_id).await,
+                None => Ok(GuildSettings::default()),
+            }
+            .unwrap_or_else(|err| {
+                log::warn!("An error ocurred trying to load a guild's settings: {err}");
+                GuildSettings::default()
+            });
+
+            let locale = Locale::resolve(Some(&event.interaction.locale), Some(settings.locale));
 
-            let is_weekday = nyc_now.weekday().num_days_from_monday() < 5;
-            let is_active_time = (7..=18).contains(&nyc_now.hour());
+            let nyc_now = Utc::now().with_timezone(&NYCTimeZone);
 
-            if !(is_weekday && is_active_time) {
+            if !settings.reports_open(&nyc_now) {
                 let msg = CreateInteractionResponseMessage::new()
-                    .content(
-                        "Reports are locked any time before 6 am, after 7 pm, and during weekends.",
-                    )
+                    .content(locale.catalog().reports_locked(&settings))
                     .ephemeral(true);
 
                 let res = CreateInteractionResponse::Message(msg);
@@ -81,12 +94,12 @@ impl<T: CacheHttp + 'static> BotTask<T> for ReportTask {
                 continue;
             }
 
-            let pool = data.pool.clone();
+            let store = Arc::clone(&data.store);
             let http = Arc::clone(&data.cache_http);
             let reporter = data.reporter.clone();
 
             tokio::spawn(async move {
-                if let Err(err) = handle_report(&pool, &http, &event, reporter).await {
+                if let Err(err) = handle_report(&*store, &http, &event, reporter, locale).await {
                     log::warn!("An error ocurred while handling report: {err}");
                 }
             });
@@ -95,17 +108,30 @@ impl<T: CacheHttp + 'static> BotTask<T> for ReportTask {
 }
 
 async fn handle_report(
-    pool: &sqlx::PgPool,
+    store: &dyn Store,
     http: &impl CacheHttp,
     event: &ComponentMessage,
     reporter: broadcast::Sender<UserReport>,
+    locale: Locale,
 ) -> Result<(), Error> {
     const TIMEOUT: Duration = Duration::from_secs(2 * 60);
 
-    let mut report = component::ReportComponent::new();
+    let catalog = locale.catalog();
+
+    // only the escalators displayed by the menu can be reported
+    let menu_id = event.interaction.message.id;
+    let available = store
+        .menu_escalators(menu_id)
+        .await?
+        .into_iter()
+        .map(|escalator| escalator.floors)
+        .collect();
+    let all_available = !store.is_scoped(menu_id).await?;
+
+    let mut report = component::ReportComponent::new(available, all_available, locale);
 
     let msg = CreateInteractionResponseMessage::new()
-        .content(generate::timeout_message(TIMEOUT))
+        .content(generate::timeout_message(TIMEOUT, locale))
         .components(report.render())
         .ephemeral(true);
     let res = CreateInteractionResponse::Message(msg);
@@ -142,7 +168,7 @@ async fn handle_report(
         }
 
         let edit = EditInteractionResponse::new()
-            .content(generate::timeout_message(TIMEOUT))
+            .content(generate::timeout_message(TIMEOUT, locale))
             .components(report.render());
 
         event.interaction.edit_response(http, edit).await?;
@@ -151,45 +177,48 @@ async fn handle_report(
     drop(actions);
 
     let edit = EditInteractionResponse::new()
-        .content("Processing...")
+        .content(catalog.processing())
         .components(vec![]);
     event.interaction.edit_response(http, edit).await?;
 
     let Some(report) = res else {
         log::debug!("Interaction timed out.");
 
-        let edit = EditInteractionResponse::new().content("Interaction timed out, try again...");
+        let edit = EditInteractionResponse::new().content(catalog.timed_out());
         event.interaction.edit_response(http, edit).await?;
 
         return Ok(());
     };
 
-    let affected_escalators = match commit_report(pool, report).await {
+    let reporter_id = event.interaction.user.id;
+
+    let res = store.commit_report(Some(reporter_id), report.escalators, report.status);
+
+    let affected_escalators = match res.await {
         Ok(escalators) => escalators,
         Err(err) => {
             log::error!("An error ocurred trying to update statuses: {err}");
 
-            let edit = EditInteractionResponse::new().content("A database error ocurred.");
+            let edit = EditInteractionResponse::new().content(catalog.database_error());
             event.interaction.edit_response(http, edit).await?;
 
             return Ok(());
         }
     };
 
+    metrics::report_submitted(report.status, "menu");
+
     let message = format!(
-        "`{}` Successfully reported {}.",
+        "`{}` {}",
         report.status.emoji(),
-        report.escalators.message_noun(),
+        catalog.reported(&report.escalators),
     );
 
     let edit = EditInteractionResponse::new().content(message);
-    event
-        .interaction
-        .edit_response(http, edit)
-        .await?;
+    event.interaction.edit_response(http, edit).await?;
 
     let full_report = UserReport {
-        reporter: Some(event.interaction.user.id),
+        reporter: Some(reporter_id),
         affected_escalators,
         escalators: report.escalators,
         new_status: report.status,
@@ -199,85 +228,3 @@ async fn handle_report(
 
     Ok(())
 }
-
-async fn commit_report(
-    pool: &sqlx::PgPool,
-    report: Report,
-) -> Result<smallvec::SmallVec<[EscalatorFloors; 2]>, sqlx::Error> {
-    let status = report.status;
-
-    match report.escalators {
-        EscalatorInput::All => report_all(pool, status).await,
-        EscalatorInput::Direct(start, end) => {
-            let floors = EscalatorFloors::new(start, end);
-            let escalator = Escalator { floors, status };
-
-            Ok(if report_escalator(pool, escalator).await? {
-                smallvec::smallvec![floors]
-            } else {
-                smallvec::smallvec![]
-            })
-        }
-        EscalatorInput::Pair(start, end) => {
-            let mut transaction = pool.begin().await?;
-
-            let mut escalators = smallvec::smallvec![];
-            for (start, end) in [(start, end), (end, start)] {
-                let floors = EscalatorFloors::new(start, end);
-                let escalator = Escalator { floors, status };
-
-                if report_escalator(&mut *transaction, escalator).await? {
-                    escalators.push(floors);
-                }
-            }
-
-            transaction.commit().await?;
-
-            Ok(escalators)
-        }
-    }
-}
-
-/// Updates every escalator's status,
-/// returning all affected escalators.
-async fn report_all(
-    pool: &sqlx::PgPool,
-    status: Status,
-) -> Result<smallvec::SmallVec<[EscalatorFloors; 2]>, sqlx::Error> {
-    sqlx::query_as::<_, EscalatorFloors>(
-        "
-        UPDATE escalators
-        SET current_status = $1
-        WHERE current_status <> $1
-        RETURNING floor_start, floor_end
-        ",
-    )
-    .bind(status)
-    .fetch(pool)
-    .try_collect()
-    .await
-}
-
-/// Attempts to update a specific escalator's status,
-/// returning whether or not the escalator exists and if it changed the status.
-async fn report_escalator(
-    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
-    escalator: Escalator,
-) -> Result<bool, sqlx::Error> {
-    sqlx::query(
-        "
-        UPDATE escalators
-        SET current_status = $1
-        WHERE current_status <> $1
-        AND floor_start = $2
-        AND floor_end = $3
-        RETURNING 1
-        ",
-    )
-    .bind(escalator.status)
-    .bind(escalator.floors.start as i16)
-    .bind(escalator.floors.end as i16)
-    .fetch_optional(executor)
-    .await
-    .map(|opt| opt.is_some())
-}
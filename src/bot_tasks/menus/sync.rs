@@ -1,82 +1,379 @@
-use crate::{bot_tasks::BotTask, data::report::UserReport, generate, prelude::*};
+use crate::{
+    bot_tasks::{outbox::Outbox, BotTask},
+    data::{
+        settings::GuildSettings,
+        store::{Menu, MenuStore, Store},
+    },
+    generate, metrics,
+    prelude::*,
+};
 
 use futures::future::join_all;
 use poise::serenity_prelude::{CacheHttp, ChannelId, MessageId};
 use std::sync::Arc;
-use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::Instant;
 
+#[derive(Clone)]
 pub struct SyncTask;
 
 pub struct TaskData<T> {
-    pool: sqlx::PgPool,
-    reports: broadcast::Receiver<UserReport>,
+    store: Arc<dyn Store>,
+    outbox: Outbox,
     cache_http: Arc<T>,
 }
 
 impl<T: CacheHttp + 'static> BotTask<T> for SyncTask {
+    const NAME: &'static str = "sync";
     type Data = TaskData<T>;
-    type Term = anyhow::Result<()>;
 
     async fn setup(&self, data: &Data, cache_http: Arc<T>) -> Option<Self::Data> {
         Some(TaskData {
-            pool: data.pool.clone(),
-            reports: data.receiver(),
+            store: Arc::clone(&data.store),
+            outbox: Outbox::new(<Self as BotTask<T>>::NAME, data).await?,
             cache_http,
         })
     }
 
-    async fn run(self, mut data: Self::Data) -> Self::Term {
-        sync_menus(&data).await?;
+    async fn run(self, mut data: Self::Data) -> anyhow::Result<()> {
+        sync_menus(&data, None).await?;
 
         loop {
-            match data.reports.recv().await {
-                Ok(report) if report.affected_escalators.is_empty() => continue,
-                Ok(_) | Err(RecvError::Lagged(_)) => (),
-                Err(RecvError::Closed) => return Ok(()),
+            let event = data.outbox.next().await?;
+            let affected = &event.report.affected_escalators;
+
+            if !affected.is_empty() {
+                sync_menus(&data, Some(affected)).await?;
             }
 
-            sync_menus(&data).await?;
+            data.outbox.handled(event.id).await?;
         }
     }
 }
 
-async fn sync_menus(data: &TaskData<impl CacheHttp>) -> Result<(), sqlx::Error> {
-    let menus = sqlx::query_as::<_, (i64, i64)>(
-        "
-        SELECT channel_id, message_id
-        FROM menu_messages
-        ",
-    )
-    .fetch_all(&data.pool)
-    .await?;
+/// Syncs the menus displaying any of the affected escalators,
+/// or every menu if no escalators are given.
+async fn sync_menus(
+    data: &TaskData<impl CacheHttp>,
+    affected: Option<&[EscalatorFloors]>,
+) -> Result<(), sqlx::Error> {
+    let menus = data.store.menus(affected).await?;
 
     if menus.is_empty() {
         log::debug!("No menu messages to sync, skipping.");
         return Ok(());
     }
 
-    let statuses = generate::menu_status(&data.pool).await?;
+    let mut update_all = vec![];
 
-    let edit = serenity::EditMessage::default()
-        .content(statuses)
-        .components(vec![generate::menu_buttons()]);
+    for menu in menus {
+        let channel_id = ChannelId::new(menu.channel_id as u64);
+        let message_id = MessageId::new(menu.message_id as u64);
 
-    let update_all = menus.into_iter().map(|(channel_id, message_id)| {
-        let cache_http = Arc::clone(&data.cache_http);
-        let channel_id = ChannelId::new(channel_id as u64);
-        let message_id = MessageId::new(message_id as u64);
-        let edit = edit.clone();
-
-        async move {
-            let _ = cache_http
-                .http()
-                .edit_message(channel_id, message_id, &edit, vec![])
-                .await
-                .ok();
+        let escalators = data.store.menu_escalators(message_id).await?;
+        let settings = data
+            .store
+            .guild_settings(serenity::GuildId::new(menu.guild_id as u64))
+            .await?;
+
+        let mut edit = serenity::EditMessage::default()
+            .content(generate::menu_status(&escalators, &settings))
+            .components(vec![generate::menu_buttons(settings.locale)])
+            .remove_all_attachments();
+
+        if let Some(diagram) = generate::menu_diagram(&escalators, &settings) {
+            edit = edit.new_attachment(diagram);
         }
-    });
+
+        let store = Arc::clone(&data.store);
+        let cache_http = Arc::clone(&data.cache_http);
+
+        update_all.push(async move {
+            let start = Instant::now();
+            let res = channel_id.edit_message(&cache_http, message_id, edit).await;
+            metrics::menu_synced(start.elapsed());
+
+            let res = check_health(&*store, &cache_http, menu, &settings, &escalators, res).await;
+
+            if let Err(err) = res {
+                log::warn!("An error ocurred trying to check the health of a menu: {err}");
+            }
+        });
+    }
 
     join_all(update_all).await;
 
     Ok(())
 }
+
+/// The reasons a menu could fail to sync.
+enum SyncFailure {
+    UnknownMessage,
+    UnknownChannel,
+    MissingPermissions,
+    Other,
+}
+
+impl SyncFailure {
+    const fn label(&self) -> &'static str {
+        match self {
+            Self::UnknownMessage => "unknown_message",
+            Self::UnknownChannel => "unknown_channel",
+            Self::MissingPermissions => "missing_permissions",
+            Self::Other => "other",
+        }
+    }
+
+    fn classify(err: &serenity::Error) -> Self {
+        use serenity::{Error, HttpError};
+
+        // https://discord.com/developers/docs/topics/opcodes-and-status-codes#json
+        match err {
+            Error::Http(HttpError::UnsuccessfulRequest(res)) => match res.error.code {
+                10008 => Self::UnknownMessage,
+                10003 => Self::UnknownChannel,
+                50001 | 50013 => Self::MissingPermissions,
+                _ => Self::Other,
+            },
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Updates the health of a menu after trying to sync it.
+/// Deleted menus are recreated in the same channel, or removed if that isn't possible.
+async fn check_health(
+    store: &dyn Store,
+    cache_http: &impl CacheHttp,
+    menu: Menu,
+    settings: &GuildSettings,
+    escalators: &[Escalator],
+    res: serenity::Result<serenity::Message>,
+) -> Result<(), Error> {
+    let err = match res {
+        Ok(_) => {
+            store.menu_synced(message_id(menu)).await?;
+
+            return Ok(());
+        }
+        Err(err) => err,
+    };
+
+    let failure = SyncFailure::classify(&err);
+    metrics::menu_sync_failed(failure.label());
+
+    match failure {
+        SyncFailure::UnknownMessage => {
+            log::info!("Menu {} was deleted, recreating it.", menu.message_id);
+
+            match recreate_menu(store, cache_http, menu, settings, escalators).await {
+                Ok(()) => Ok(()),
+                Err(err) => {
+                    log::warn!("Failed to recreate menu {}: {err}", menu.message_id);
+                    remove_menu(store, cache_http, menu, settings).await
+                }
+            }
+        }
+        SyncFailure::UnknownChannel => {
+            log::info!("Menu {}'s channel was deleted.", menu.message_id);
+            remove_menu(store, cache_http, menu, settings).await
+        }
+        SyncFailure::MissingPermissions => {
+            log::warn!(
+                "Missing permissions to sync menu {}: {err}",
+                menu.message_id
+            );
+
+            store
+                .menu_sync_failed(message_id(menu), &err.to_string(), true)
+                .await?;
+
+            Ok(())
+        }
+        SyncFailure::Other => {
+            log::warn!(
+                "An error ocurred trying to sync menu {}: {err}",
+                menu.message_id
+            );
+
+            store
+                .menu_sync_failed(message_id(menu), &err.to_string(), false)
+                .await?;
+
+            Ok(())
+        }
+    }
+}
+
+/// Sends a new menu in the same channel, replacing the old one.
+async fn recreate_menu(
+    store: &dyn MenuStore,
+    cache_http: &impl CacheHttp,
+    menu: Menu,
+    settings: &GuildSettings,
+    escalators: &[Escalator],
+) -> Result<(), Error> {
+    let channel_id = ChannelId::new(menu.channel_id as u64);
+
+    let mut msg = serenity::CreateMessage::new()
+        .content(generate::menu_status(escalators, settings))
+        .components(vec![generate::menu_buttons(settings.locale)]);
+
+    if let Some(diagram) = generate::menu_diagram(escalators, settings) {
+        msg = msg.add_file(diagram);
+    }
+
+    let new_menu = channel_id.send_message(cache_http, msg).await?;
+
+    store.replace_menu(message_id(menu), new_menu.id).await?;
+
+    Ok(())
+}
+
+/// Removes a menu that can't be synced anymore, and lets the guild's admins know
+/// in its announcement channel (pinging the admin role), or its owner if that isn't possible.
+async fn remove_menu(
+    store: &dyn Store,
+    cache_http: &impl CacheHttp,
+    menu: Menu,
+    settings: &GuildSettings,
+) -> Result<(), Error> {
+    store.remove_menu(message_id(menu)).await?;
+
+    let guild = serenity::GuildId::new(menu.guild_id as u64)
+        .to_partial_guild(cache_http)
+        .await?;
+
+    let message = settings
+        .locale
+        .catalog()
+        .menu_removed(ChannelId::new(menu.channel_id as u64), &guild.name);
+
+    let announcement_channel = store
+        .announcement_channels(Some(&[menu.guild_id]))
+        .await?
+        .into_iter()
+        .next()
+        // the menu's channel is the one that's broken
+        .filter(|channel| channel.channel_id != menu.channel_id);
+
+    if let Some(channel) = announcement_channel {
+        let (content, mentions) = match settings.admin_role {
+            Some(role_id) => (
+                format!("<@&{role_id}> {message}"),
+                serenity::CreateAllowedMentions::new().roles([role_id]),
+            ),
+            None => (message.clone(), serenity::CreateAllowedMentions::new()),
+        };
+
+        let msg = serenity::CreateMessage::new()
+            .content(content)
+            .allowed_mentions(mentions);
+
+        let res = ChannelId::new(channel.channel_id as u64)
+            .send_message(cache_http, msg)
+            .await;
+
+        match res {
+            Ok(_) => return Ok(()),
+            Err(err) => log::warn!(
+                "An error ocurred trying to let the admins of {} know a menu was removed: {err}",
+                menu.guild_id
+            ),
+        }
+    }
+
+    guild
+        .owner_id
+        .create_dm_channel(cache_http)
+        .await?
+        .say(cache_http, message)
+        .await?;
+
+    Ok(())
+}
+
+fn message_id(menu: Menu) -> MessageId {
+    MessageId::new(menu.message_id as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bot_tasks::fake::{Action, FakeDiscord, GUILD_ID},
+        data::{
+            escalator_input::EscalatorInput, report::UserReport, status::Status, store::MemoryStore,
+        },
+    };
+
+    #[tokio::test]
+    async fn deleted_menus_are_recreated() {
+        let data = Data::new(Arc::new(MemoryStore::default()));
+        let menu = Menu {
+            guild_id: GUILD_ID as i64,
+            channel_id: 20,
+            message_id: 30,
+        };
+        data.store.add_menu(menu, &[]).await.unwrap();
+
+        let discord = Arc::new(FakeDiscord::start().await);
+
+        let task_data = SyncTask.setup(&data, Arc::clone(&discord)).await.unwrap();
+        tokio::spawn(SyncTask.run(task_data));
+
+        let old_message_id = message_id(menu);
+
+        // every menu is synced when the task starts
+        let actions = discord.wait_for(1).await;
+        assert!(matches!(
+            &actions[0],
+            Action::Edit { message_id, payload, .. }
+                if *message_id == old_message_id && payload["content"].is_string()
+        ));
+
+        discord.delete_message(old_message_id);
+        data.store
+            .commit_report(None, EscalatorInput::Direct(4, 6), Status::Down)
+            .await
+            .unwrap();
+        data.sender()
+            .send(UserReport {
+                reporter: None,
+                escalators: EscalatorInput::Direct(4, 6),
+                affected_escalators: smallvec::smallvec![EscalatorFloors::new(4, 6)],
+                new_status: Status::Down,
+            })
+            .unwrap();
+
+        let actions = discord.wait_for(2).await;
+        let Action::Message {
+            channel_id,
+            message_id: new_message_id,
+            ..
+        } = actions[1]
+        else {
+            panic!("Expected the menu to be recreated, got {actions:?}");
+        };
+        assert_eq!(channel_id, ChannelId::new(20));
+
+        // the menu is moved to the new message after it's sent
+        let moved = async {
+            loop {
+                let menus = data.store.menus(None).await.unwrap();
+                if menus
+                    .iter()
+                    .any(|menu| menu.message_id == new_message_id.get() as i64)
+                {
+                    return menus;
+                }
+
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        };
+
+        let menus = tokio::time::timeout(std::time::Duration::from_secs(5), moved)
+            .await
+            .expect("the menu should be moved to the new message");
+        assert_eq!(menus.len(), 1);
+    }
+}
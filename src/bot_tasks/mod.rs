@@ -1,16 +1,35 @@
 pub mod alert;
 pub mod announce;
+#[cfg(test)]
+pub mod fake;
 pub mod menus;
+pub mod outbox;
+pub mod summary;
+pub mod supervisor;
+pub mod uptime;
+pub mod webhook;
 
 use crate::prelude::*;
 
 use poise::serenity_prelude::CacheHttp;
-use std::{future::Future, process::Termination, sync::Arc};
+use std::{future::Future, sync::Arc};
+
+/// Sent to every task when the bot is shutting down.
+#[derive(Debug, Clone, Copy)]
+pub struct Shutdown;
 
 pub trait BotTask<T: CacheHttp>: Send + Sync {
+    /// A short name for the task, used in logs and metrics.
+    const NAME: &'static str;
+    /// Whether the task finishes what it's doing and stops by itself once it receives [`Shutdown`].
+    /// Otherwise, it's aborted as soon as the bot starts shutting down.
+    const GRACEFUL: bool = false;
     type Data: Send + 'static;
-    type Term: Termination;
 
-    async fn setup(&self, data: &Data, cache_http: Arc<T>) -> Option<Self::Data>;
-    fn run(self, data: Self::Data) -> impl Future<Output = Self::Term> + Send;
+    fn setup(
+        &self,
+        data: &Data,
+        cache_http: Arc<T>,
+    ) -> impl Future<Output = Option<Self::Data>> + Send;
+    fn run(self, data: Self::Data) -> impl Future<Output = anyhow::Result<()>> + Send;
 }
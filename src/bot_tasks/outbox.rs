@@ -0,0 +1,219 @@
+//! Reads reports from the outbox, which they're added to along with their status changes,
+//! so every consumer handles each report at least once, even if it was lagging behind
+//! or wasn't running when the report was made.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use poise::serenity_prelude::CacheHttp;
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+};
+
+use crate::{
+    data::{
+        report::UserReport,
+        settings::MAX_REPORTS_DISPLAYED,
+        store::{OutboxEvent, Store},
+    },
+    metrics,
+    prelude::*,
+};
+
+use super::BotTask;
+
+/// How many reports are loaded from the outbox at once.
+const BATCH_SIZE: usize = 50;
+/// How often the reports every consumer has handled are removed.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// A consumer's place in the outbox.
+pub struct Outbox {
+    consumer: &'static str,
+    store: Arc<dyn Store>,
+    /// Only used to know when a report is made, since they're all read from the outbox.
+    reports: broadcast::Receiver<UserReport>,
+    /// The last report that was loaded.
+    position: i64,
+    /// The last report the consumer has handled, as it's saved in the store.
+    cursor: i64,
+    loaded: VecDeque<OutboxEvent>,
+}
+
+impl Outbox {
+    /// Starts reading from wherever the consumer (eg. `alert`) left off,
+    /// or after the latest report if it's new. Returns `None` if the cursor couldn't be loaded.
+    pub async fn new(consumer: &'static str, data: &Data) -> Option<Self> {
+        // subscribed first, so a report made while the cursor is loading isn't waited for
+        let reports = data.receiver();
+
+        let cursor = match data.store.cursor(consumer).await {
+            Ok(cursor) => cursor,
+            Err(err) => {
+                log::warn!(
+                    "An error ocurred trying to load the outbox cursor of {consumer}: {err}"
+                );
+                return None;
+            }
+        };
+
+        Some(Self {
+            consumer,
+            store: Arc::clone(&data.store),
+            reports,
+            position: cursor,
+            cursor,
+            loaded: VecDeque::new(),
+        })
+    }
+
+    /// Waits for the next report the consumer hasn't handled yet.
+    /// Nothing is skipped if the future is dropped, so it can be used in `tokio::select!`.
+    pub async fn next(&mut self) -> anyhow::Result<OutboxEvent> {
+        loop {
+            if let Some(event) = self.loaded.pop_front() {
+                return Ok(event);
+            }
+
+            // every report made so far is about to be loaded, so they don't need to be waited for
+            while let Ok(_) | Err(TryRecvError::Lagged(_)) = self.reports.try_recv() {}
+
+            let events = self.store.events_after(self.position, BATCH_SIZE).await?;
+
+            if let Some(last) = events.last() {
+                self.position = last.id;
+                self.loaded.extend(events);
+                continue;
+            }
+
+            match self.reports.recv().await {
+                Ok(_) => {}
+                // the reports are read from the outbox, so falling behind doesn't lose any
+                Err(RecvError::Lagged(_)) => metrics::receiver_lagged(self.consumer),
+                Err(RecvError::Closed) => anyhow::bail!("Update receiver has closed."),
+            }
+        }
+    }
+
+    /// The last report the consumer has handled.
+    pub fn cursor(&self) -> i64 {
+        self.cursor
+    }
+
+    /// Records that the consumer has handled every report up to the given one,
+    /// so they aren't handled again when it restarts.
+    pub async fn handled(&mut self, event_id: i64) -> Result<(), sqlx::Error> {
+        if event_id <= self.cursor {
+            return Ok(());
+        }
+
+        self.store.set_cursor(self.consumer, event_id).await?;
+        self.cursor = event_id;
+
+        Ok(())
+    }
+}
+
+/// The reports a consumer is still handling in the background,
+/// so a report only counts as handled once every report before it is too.
+#[derive(Debug, Default)]
+pub struct InFlight {
+    /// How many parts of each report are left to handle.
+    remaining: BTreeMap<i64, usize>,
+    latest: i64,
+}
+
+impl InFlight {
+    /// Starts handling a report in some number of parts (eg. one for each webhook),
+    /// which is handled right away if there aren't any.
+    pub fn start(&mut self, event_id: i64, parts: usize) {
+        self.latest = self.latest.max(event_id);
+
+        if parts > 0 {
+            self.remaining.insert(event_id, parts);
+        }
+    }
+
+    /// Finishes handling one part of a report.
+    pub fn finish(&mut self, event_id: i64) {
+        if let Some(remaining) = self.remaining.get_mut(&event_id) {
+            *remaining -= 1;
+
+            if *remaining == 0 {
+                self.remaining.remove(&event_id);
+            }
+        }
+    }
+
+    /// The last report which has been handled, along with every report before it.
+    pub fn handled(&self) -> i64 {
+        match self.remaining.first_key_value() {
+            Some((&event_id, _)) => event_id - 1,
+            None => self.latest,
+        }
+    }
+}
+
+/// Removes the reports every consumer has handled from the outbox, so it doesn't grow forever.
+#[derive(Clone)]
+pub struct PruneTask {
+    /// The tasks reading from the outbox, since a cursor left by any other consumer
+    /// (eg. one that was renamed or removed) would keep anything from being removed.
+    consumers: Vec<&'static str>,
+}
+
+impl PruneTask {
+    pub fn new(consumers: Vec<&'static str>) -> Self {
+        Self { consumers }
+    }
+}
+
+impl<T: CacheHttp + 'static> BotTask<T> for PruneTask {
+    const NAME: &'static str = "prune";
+    type Data = Arc<dyn Store>;
+
+    async fn setup(&self, data: &Data, _cache_http: Arc<T>) -> Option<Self::Data> {
+        Some(Arc::clone(&data.store))
+    }
+
+    async fn run(self, store: Self::Data) -> anyhow::Result<()> {
+        loop {
+            // the last few reports are kept, so live messages can be rebuilt from them
+            let pruned = store
+                .prune_events(&self.consumers, MAX_REPORTS_DISPLAYED)
+                .await?;
+            if pruned > 0 {
+                log::debug!("Removed {pruned} handled reports from the outbox.");
+            }
+
+            tokio::time::sleep(PRUNE_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_are_handled_in_order() {
+        let mut in_flight = InFlight::default();
+
+        in_flight.start(1, 2);
+        in_flight.start(2, 0);
+        in_flight.start(3, 1);
+        assert_eq!(in_flight.handled(), 0);
+
+        // the third report is done, but the first one isn't yet
+        in_flight.finish(3);
+        in_flight.finish(1);
+        assert_eq!(in_flight.handled(), 0);
+
+        in_flight.finish(1);
+        assert_eq!(in_flight.handled(), 3);
+    }
+}
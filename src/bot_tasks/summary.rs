@@ -0,0 +1,436 @@
+use crate::{
+    data::{history, store::Store},
+    generate,
+    locale::Locale,
+    prelude::*,
+    render,
+};
+
+use super::{BotTask, Shutdown};
+
+use chrono::prelude::*;
+use chrono_tz::{America::New_York as NYCTimeZone, Tz};
+use futures::future::join_all;
+use poise::serenity_prelude::{CacheHttp, CreateAttachment, CreateEmbed, CreateMessage};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::broadcast;
+
+/// Posts a summary to the announcement channels every weekday morning,
+/// including the most and least reliable escalators once a week.
+#[derive(Clone)]
+pub struct SummaryTask;
+
+pub struct TaskData<T> {
+    store: Arc<dyn Store>,
+    shutdown: broadcast::Receiver<Shutdown>,
+    cache_http: Arc<T>,
+}
+
+const SUMMARY_HOUR: u32 = 7;
+const WEEKLY_SUMMARY_DAY: Weekday = Weekday::Mon;
+
+const MAX_CHANGES_DISPLAYED: usize = 15;
+const RELIABILITY_COUNT: usize = 3;
+
+impl<T: CacheHttp + 'static> BotTask<T> for SummaryTask {
+    const NAME: &'static str = "summary";
+    const GRACEFUL: bool = true;
+    type Data = TaskData<T>;
+
+    async fn setup(&self, data: &Data, cache_http: Arc<T>) -> Option<Self::Data> {
+        Some(TaskData {
+            store: Arc::clone(&data.store),
+            shutdown: data.receiver(),
+            cache_http,
+        })
+    }
+
+    async fn run(self, mut data: Self::Data) -> anyhow::Result<()> {
+        // the last summary that was due is posted wherever it was missed,
+        // eg. while the bot was down or after the task failed partway through
+        let now = Utc::now().with_timezone(&NYCTimeZone);
+        summarize(&data, previous_summary_time(next_summary_time(now)), true).await?;
+
+        loop {
+            let now = Utc::now().with_timezone(&NYCTimeZone);
+            let next = next_summary_time(now);
+
+            log::info!("Next summary will be posted at {next}.");
+
+            let until = (next - now).to_std().unwrap_or_default();
+
+            // a summary that's being sent is finished first, so it's never posted in only some channels
+            tokio::select! {
+                () = tokio::time::sleep(until) => summarize(&data, next, false).await?,
+                _ = data.shutdown.recv() => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Posts the summary due at the given time in every channel it hasn't been posted in yet.
+/// Guilds which have never had one posted aren't caught up on,
+/// so they aren't sent a late summary right after adding their channel.
+async fn summarize<T: CacheHttp>(
+    data: &TaskData<T>,
+    now: DateTime<Tz>,
+    catching_up: bool,
+) -> Result<(), sqlx::Error> {
+    log::info!("Grabbing summary channels...");
+
+    let previous = previous_summary_time(now);
+    let weekly = now.weekday() == WEEKLY_SUMMARY_DAY;
+
+    let posted = data.store.posted_summaries().await?;
+    let summary_time = now.with_timezone(&Utc);
+
+    let channels = data
+        .store
+        .announcement_channels(None)
+        .await?
+        .into_iter()
+        .filter(|channel| {
+            channel.settings.daily_summary || (weekly && channel.settings.weekly_summary)
+        })
+        .filter(|channel| match posted.get(&channel.guild_id) {
+            Some(&posted) => posted < summary_time,
+            None => !catching_up,
+        })
+        .collect::<Vec<_>>();
+
+    if channels.is_empty() {
+        log::info!("No summary channels found, skipping summary generation.");
+        return Ok(());
+    }
+
+    log::info!("Generating summary...");
+
+    let previous = previous.with_timezone(&Utc);
+    let now = now.with_timezone(&Utc);
+
+    let changes = data.store.changes(previous, now).await?;
+
+    let weekly = if weekly {
+        let start = now - chrono::Duration::weeks(1);
+
+        let uptimes = history::load_uptimes(&*data.store, start, now).await?;
+        let before = data.store.last_changes(start).await?;
+        let changes = data.store.changes(start, now).await?;
+
+        Some(WeeklyHistory {
+            uptimes,
+            before,
+            changes,
+        })
+    } else {
+        None
+    };
+
+    // the summaries are generated once for every locale used by the channels
+    let mut summaries = HashMap::<Locale, Summary>::new();
+
+    for channel in &channels {
+        let locale = channel.settings.locale;
+
+        if summaries.contains_key(&locale) {
+            continue;
+        }
+
+        let summary =
+            Summary::generate(&*data.store, &changes, weekly.as_ref(), now, locale).await?;
+        summaries.insert(locale, summary);
+    }
+
+    if catching_up {
+        log::info!("Posting the missed summary for {now}...");
+    }
+
+    log::info!("Sending summary...");
+
+    let send_all = channels.into_iter().map(|channel| {
+        let summary = &summaries[&channel.settings.locale];
+        let catalog = channel.settings.locale.catalog();
+
+        let mut msg = CreateMessage::new();
+        let mut embed = summary.daily.clone();
+
+        if let Some((most, least, charts)) = &summary.weekly {
+            if channel.settings.weekly_summary {
+                embed = embed
+                    .field(catalog.most_reliable_this_week(), most, true)
+                    .field(catalog.least_reliable_this_week(), least, true);
+
+                msg = msg.add_files(charts.clone());
+            }
+        }
+
+        let guild_id = channel.guild_id;
+        let channel_id = channel.channel_id;
+        let channel = serenity::ChannelId::new(channel_id as u64);
+        let store = Arc::clone(&data.store);
+        let cache_http = Arc::clone(&data.cache_http);
+
+        async move {
+            let msg = msg.embed(embed);
+
+            if let Err(err) = channel.send_message(&cache_http, msg).await {
+                log::warn!(
+                    "An error ocurred trying to send a summary in the channel <#{channel_id}>: {err}"
+                );
+                return;
+            }
+
+            if let Err(err) = store.set_posted_summary(guild_id, summary_time).await {
+                log::warn!(
+                    "An error ocurred trying to record the summary posted in the channel <#{channel_id}>: {err}"
+                );
+            }
+        }
+    });
+
+    join_all(send_all).await;
+
+    Ok(())
+}
+
+/// The history a weekly summary is generated from.
+struct WeeklyHistory {
+    uptimes: Vec<(EscalatorFloors, f64)>,
+    before: Vec<history::StatusChange>,
+    changes: Vec<history::StatusChange>,
+}
+
+/// A summary generated in a single locale.
+struct Summary {
+    daily: CreateEmbed,
+    /// The most and least reliable escalators, and the charts, if it's a weekly summary.
+    weekly: Option<(String, String, Vec<CreateAttachment>)>,
+}
+
+impl Summary {
+    async fn generate(
+        store: &dyn Store,
+        changes: &[history::StatusChange],
+        weekly: Option<&WeeklyHistory>,
+        now: DateTime<Utc>,
+        locale: Locale,
+    ) -> Result<Self, sqlx::Error> {
+        let catalog = locale.catalog();
+
+        let daily = generate::gist(store, locale)
+            .await?
+            .title(catalog.summary_title())
+            .timestamp(now)
+            .field(
+                catalog.changes_since_summary(),
+                generate::status_changes(MAX_CHANGES_DISPLAYED, changes, locale),
+                false,
+            );
+
+        let weekly = weekly.map(|weekly| {
+            let WeeklyHistory {
+                uptimes,
+                before,
+                changes,
+            } = weekly;
+
+            let most = generate::uptimes(uptimes.iter().take(RELIABILITY_COUNT));
+            let least = generate::uptimes(uptimes.iter().rev().take(RELIABILITY_COUNT));
+
+            let outages = history::outages_by_hour(before, changes, &NYCTimeZone);
+
+            // the charts are left out if they fail to render, rather than skipping the summary
+            let charts =
+                render::reliability_charts(&outages, uptimes, locale).unwrap_or_else(|err| {
+                    log::warn!("An error ocurred trying to render the weekly charts: {err}");
+                    vec![]
+                });
+
+            (most, least, charts)
+        });
+
+        Ok(Self { daily, weekly })
+    }
+}
+
+/// Finds the next weekday morning after the given time.
+fn next_summary_time(now: DateTime<Tz>) -> DateTime<Tz> {
+    let mut date = now.date_naive();
+
+    loop {
+        let time = summary_time_on(date);
+
+        if time > now && is_weekday(time.weekday()) {
+            return time;
+        }
+
+        date = date.succ_opt().expect("Date out of range");
+    }
+}
+
+/// Finds the weekday morning before the given summary time.
+fn previous_summary_time(next: DateTime<Tz>) -> DateTime<Tz> {
+    let mut date = next.date_naive();
+
+    loop {
+        date = date.pred_opt().expect("Date out of range");
+
+        if is_weekday(date.weekday()) {
+            return summary_time_on(date);
+        }
+    }
+}
+
+fn summary_time_on(date: NaiveDate) -> DateTime<Tz> {
+    let time = date
+        .and_hms_opt(SUMMARY_HOUR, 0, 0)
+        .expect("Summary hour is a valid time");
+
+    // the summary hour is never skipped by daylight saving time
+    NYCTimeZone
+        .from_local_datetime(&time)
+        .earliest()
+        .expect("Summary time exists in local time")
+}
+
+fn is_weekday(weekday: Weekday) -> bool {
+    weekday.num_days_from_monday() < 5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bot_tasks::fake::{Action, FakeDiscord, GUILD_ID},
+        data::{
+            settings::GuildSettings,
+            store::{ChannelStore, MemoryStore},
+        },
+    };
+
+    const CHANNEL: serenity::ChannelId = serenity::ChannelId::new(20);
+
+    /// Sets up a guild which has daily summaries posted in [`CHANNEL`].
+    async fn setup() -> (TaskData<FakeDiscord>, Arc<FakeDiscord>) {
+        let guild_id = serenity::GuildId::new(GUILD_ID);
+
+        let store = Arc::new(MemoryStore::default());
+        store.set_guild_settings(
+            guild_id,
+            GuildSettings {
+                daily_summary: true,
+                ..Default::default()
+            },
+        );
+        store
+            .set_announcement_channel(guild_id, CHANNEL)
+            .await
+            .unwrap();
+
+        let discord = Arc::new(FakeDiscord::start().await);
+        let data = SummaryTask
+            .setup(&Data::new(store), Arc::clone(&discord))
+            .await
+            .unwrap();
+
+        (data, discord)
+    }
+
+    fn summary_time(day: u32) -> DateTime<Tz> {
+        summary_time_on(NaiveDate::from_ymd_opt(2026, 10, day).unwrap())
+    }
+
+    /// A time in New York.
+    fn local(month: u32, day: u32, hour: u32) -> DateTime<Tz> {
+        NYCTimeZone
+            .with_ymd_and_hms(2026, month, day, hour, 0, 0)
+            .unwrap()
+    }
+
+    fn utc(month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, month, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn summaries_are_posted_on_weekday_mornings() {
+        // Thursday, before and at the summary hour
+        assert_eq!(next_summary_time(local(10, 15, 6)), local(10, 15, 7));
+        assert_eq!(next_summary_time(local(10, 15, 7)), local(10, 16, 7));
+
+        // Friday evening and Saturday skip to Monday
+        assert_eq!(next_summary_time(local(10, 16, 18)), local(10, 19, 7));
+        assert_eq!(next_summary_time(local(10, 17, 12)), local(10, 19, 7));
+        assert_eq!(next_summary_time(local(10, 18, 12)), local(10, 19, 7));
+    }
+
+    #[test]
+    fn summaries_cover_the_weekend_on_mondays() {
+        assert_eq!(previous_summary_time(local(10, 19, 7)), local(10, 16, 7));
+        assert_eq!(previous_summary_time(local(10, 20, 7)), local(10, 19, 7));
+    }
+
+    #[test]
+    fn summaries_follow_daylight_saving_time() {
+        // clocks go forward on Sunday, March 8th, so the weekend is an hour shorter
+        assert_eq!(summary_time_on(local(3, 8, 12).date_naive()), utc(3, 8, 11));
+        let next = next_summary_time(local(3, 7, 12));
+        assert_eq!(next, utc(3, 9, 11));
+        assert_eq!(previous_summary_time(next), utc(3, 6, 12));
+
+        // and back on Sunday, November 1st, so it's an hour longer
+        assert_eq!(
+            summary_time_on(local(11, 1, 12).date_naive()),
+            utc(11, 1, 12)
+        );
+        let next = next_summary_time(local(10, 31, 12));
+        assert_eq!(next, utc(11, 2, 12));
+        assert_eq!(previous_summary_time(next), utc(10, 30, 11));
+    }
+
+    #[tokio::test]
+    async fn missed_summaries_are_posted_once() {
+        let (data, discord) = setup().await;
+
+        summarize(&data, summary_time(13), false).await.unwrap();
+        assert_eq!(discord.wait_for(1).await.len(), 1);
+
+        // the next day's summary was missed, and is caught up on after restarting twice
+        summarize(&data, summary_time(14), true).await.unwrap();
+        summarize(&data, summary_time(14), true).await.unwrap();
+
+        let actions = discord.actions();
+        assert_eq!(
+            actions.len(),
+            2,
+            "Expected one missed summary, got {actions:?}"
+        );
+        assert!(matches!(actions[1], Action::Message { channel_id, .. } if channel_id == CHANNEL));
+
+        // a summary that was already posted isn't posted again, even on schedule
+        summarize(&data, summary_time(14), false).await.unwrap();
+        assert_eq!(discord.actions().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn shutting_down_stops_the_task() {
+        let data = Data::new(Arc::new(MemoryStore::default()));
+        let discord = Arc::new(FakeDiscord::start().await);
+
+        let task_data = SummaryTask.setup(&data, discord).await.unwrap();
+        let run = tokio::spawn(SummaryTask.run(task_data));
+
+        data.send_message(Shutdown);
+        run.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn new_guilds_arent_caught_up_on() {
+        let (data, discord) = setup().await;
+
+        summarize(&data, summary_time(14), true).await.unwrap();
+        assert!(discord.actions().is_empty());
+
+        summarize(&data, summary_time(15), false).await.unwrap();
+        assert_eq!(discord.wait_for(1).await.len(), 1);
+    }
+}
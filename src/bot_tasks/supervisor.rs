@@ -0,0 +1,185 @@
+//! Keeps bot tasks running, restarting them (with a growing delay) whenever they fail.
+
+use std::{sync::Arc, time::Duration};
+
+use poise::serenity_prelude::CacheHttp;
+use tokio::{
+    sync::broadcast::{self, error::TryRecvError},
+    task::JoinSet,
+    time::Instant,
+};
+
+use crate::{metrics, prelude::*};
+
+use super::{BotTask, Shutdown};
+
+/// How many failures in a row before a task is reported as crashing repeatedly.
+const CRASH_LOOP_FAILURES: u32 = 5;
+
+/// How long to wait before restarting a failed task.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// The delay after the first failure, doubled after every failure in a row.
+    pub initial: Duration,
+    pub max: Duration,
+    /// How long a task has to run for its failures to stop counting towards the delay.
+    pub reset_after: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(5 * 60),
+            reset_after: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Runs a task that's already been set up, setting it up again and restarting it
+/// every time it returns an error or panics. Only returns once the task stops by itself,
+/// or once the bot is shutting down.
+pub async fn supervise<T, B>(
+    task: B,
+    data: Data,
+    cache_http: Arc<T>,
+    task_data: B::Data,
+    mut shutdown: broadcast::Receiver<Shutdown>,
+    backoff: Backoff,
+) where
+    T: CacheHttp + 'static,
+    B: BotTask<T> + Clone + 'static,
+{
+    let mut task_data = Some(task_data);
+    let mut failures = 0;
+
+    loop {
+        let started = Instant::now();
+
+        let err = match task_data.take() {
+            Some(task_data) => {
+                // the run is aborted along with the supervisor, since it's in its own set
+                let mut run = JoinSet::new();
+                run.spawn(task.clone().run(task_data));
+
+                let reset_at = started + backoff.reset_after;
+
+                metrics::task_running(B::NAME, true);
+                let res = loop {
+                    tokio::select! {
+                        Some(res) = run.join_next() => break res,
+                        _ = shutdown.recv(), if !B::GRACEFUL => {
+                            metrics::task_running(B::NAME, false);
+                            return;
+                        }
+                        // the task has stayed up long enough for its failures to stop counting
+                        () = tokio::time::sleep_until(reset_at), if failures > 0 => {
+                            failures = 0;
+                            metrics::task_failures(B::NAME, 0);
+                        }
+                    }
+                };
+                metrics::task_running(B::NAME, false);
+
+                match res {
+                    Ok(Ok(())) => {
+                        log::info!("Task {} stopped.", B::NAME);
+                        return;
+                    }
+                    Ok(Err(err)) => err,
+                    // the task panicked
+                    Err(err) => anyhow::anyhow!("{err}"),
+                }
+            }
+            None => anyhow::anyhow!("setup failed"),
+        };
+
+        failures += 1;
+        metrics::task_failures(B::NAME, failures);
+
+        if failures >= CRASH_LOOP_FAILURES {
+            log::error!(
+                "Task {} keeps crashing, it has failed {failures} times in a row: {err:#}",
+                B::NAME
+            );
+        } else {
+            log::warn!("Task {} failed: {err:#}", B::NAME);
+        }
+
+        let delay = backoff
+            .initial
+            .saturating_mul(2u32.saturating_pow(failures - 1))
+            .min(backoff.max);
+
+        tokio::select! {
+            () = tokio::time::sleep(delay) => {}
+            _ = shutdown.recv() => return,
+        }
+
+        // a shutdown could have started while the task was failing, and it shouldn't be restarted
+        if !matches!(shutdown.try_recv(), Err(TryRecvError::Empty)) {
+            return;
+        }
+
+        log::info!("Restarting task {} (after {failures} failures)...", B::NAME);
+        metrics::task_restarted(B::NAME);
+
+        task_data = task.setup(&data, Arc::clone(&cache_http)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{bot_tasks::fake::FakeDiscord, data::store::MemoryStore};
+
+    /// Panics on its first run and fails on its second, then stops by itself.
+    #[derive(Clone, Default)]
+    struct FlakyTask {
+        runs: Arc<AtomicUsize>,
+    }
+
+    impl<T: CacheHttp + 'static> BotTask<T> for FlakyTask {
+        const NAME: &'static str = "flaky";
+        type Data = ();
+
+        async fn setup(&self, _data: &Data, _cache_http: Arc<T>) -> Option<Self::Data> {
+            Some(())
+        }
+
+        async fn run(self, _data: Self::Data) -> anyhow::Result<()> {
+            match self.runs.fetch_add(1, Ordering::SeqCst) {
+                0 => panic!("the first run panics"),
+                1 => anyhow::bail!("the second run fails"),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_tasks_are_restarted() {
+        let data = Data::new(Arc::new(MemoryStore::default()));
+        let discord = Arc::new(FakeDiscord::start().await);
+        let task = FlakyTask::default();
+        let backoff = Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(10),
+            reset_after: Duration::from_secs(60),
+        };
+
+        let shutdown = data.receiver();
+        let supervised = supervise(task.clone(), data, discord, (), shutdown, backoff);
+        tokio::time::timeout(Duration::from_secs(5), supervised)
+            .await
+            .expect("the task should stop after its third run");
+
+        assert_eq!(task.runs.load(Ordering::SeqCst), 3);
+
+        let metrics = metrics::render(&[]);
+        assert!(metrics.contains("escalator_task_restarts_total{task=\"flaky\"} 2\n"));
+        assert!(metrics.contains("escalator_task_up{task=\"flaky\"} 0\n"));
+        assert!(metrics.contains("escalator_task_consecutive_failures{task=\"flaky\"} 2\n"));
+    }
+}
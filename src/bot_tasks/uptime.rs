@@ -0,0 +1,149 @@
+use crate::{data::store::Store, generate, prelude::*};
+
+use super::{BotTask, Shutdown};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::future::join_all;
+use poise::serenity_prelude::CacheHttp;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// How often the bot records that it's still running.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+/// How long the bot has to have been gone for to let the announcement channels know,
+/// which leaves room for quick restarts.
+const OFFLINE_THRESHOLD: TimeDelta = TimeDelta::minutes(5);
+
+/// Keeps track of when the bot was last running, and lets the announcement channels know
+/// when it comes back after being offline. (The menus are synced by `SyncTask` when it starts.)
+#[derive(Clone)]
+pub struct UptimeTask {
+    started_at: DateTime<Utc>,
+}
+
+pub struct TaskData<T> {
+    store: Arc<dyn Store>,
+    shutdown: broadcast::Receiver<Shutdown>,
+    cache_http: Arc<T>,
+}
+
+impl Default for UptimeTask {
+    /// Counts the bot as started now.
+    fn default() -> Self {
+        Self {
+            started_at: Utc::now(),
+        }
+    }
+}
+
+impl<T: CacheHttp + 'static> BotTask<T> for UptimeTask {
+    const NAME: &'static str = "uptime";
+    const GRACEFUL: bool = true;
+    type Data = TaskData<T>;
+
+    async fn setup(&self, data: &Data, cache_http: Arc<T>) -> Option<Self::Data> {
+        Some(TaskData {
+            store: Arc::clone(&data.store),
+            shutdown: data.receiver(),
+            cache_http,
+        })
+    }
+
+    async fn run(self, mut data: Self::Data) -> anyhow::Result<()> {
+        // a heartbeat from after the bot started is from before the task was restarted,
+        // rather than from before the bot was offline
+        if let Some(last_seen) = data.store.last_seen().await? {
+            if last_seen < self.started_at && self.started_at - last_seen >= OFFLINE_THRESHOLD {
+                announce_downtime(&data, last_seen, self.started_at).await?;
+            }
+        }
+
+        loop {
+            data.store.set_last_seen(Utc::now()).await?;
+
+            tokio::select! {
+                () = tokio::time::sleep(HEARTBEAT_INTERVAL) => {}
+                _ = data.shutdown.recv() => {
+                    data.store.set_last_seen(Utc::now()).await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Posts a notice in every announcement channel that the bot was offline, and could've missed reports.
+async fn announce_downtime<T: CacheHttp>(
+    data: &TaskData<T>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    log::info!("The bot was offline from {from} to {to}, sending notices...");
+
+    let channels = data.store.announcement_channels(None).await?;
+
+    let send_all = channels.into_iter().map(|channel| {
+        let notice = generate::offline_notice(from, to, channel.settings.locale);
+        let channel_id = serenity::ChannelId::new(channel.channel_id as u64);
+
+        async move {
+            if let Err(err) = channel_id.say(&data.cache_http, notice).await {
+                log::warn!(
+                    "An error ocurred trying to send the offline notice in the channel <#{channel_id}>: {err}"
+                );
+            }
+        }
+    });
+
+    join_all(send_all).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bot_tasks::fake::{Action, FakeDiscord, GUILD_ID},
+        data::store::{ChannelStore, MemoryStore, UptimeStore},
+    };
+
+    const CHANNEL: serenity::ChannelId = serenity::ChannelId::new(20);
+
+    #[tokio::test]
+    async fn downtime_is_announced() {
+        let store = Arc::new(MemoryStore::default());
+        store
+            .set_announcement_channel(serenity::GuildId::new(GUILD_ID), CHANNEL)
+            .await
+            .unwrap();
+
+        let task = UptimeTask::default();
+        let last_seen = task.started_at - TimeDelta::hours(1);
+        store.set_last_seen(last_seen).await.unwrap();
+
+        let data = Data::new(Arc::clone(&store) as Arc<dyn Store>);
+        let discord = Arc::new(FakeDiscord::start().await);
+
+        let task_data = task.setup(&data, Arc::clone(&discord)).await.unwrap();
+        let run = tokio::spawn(task.run(task_data));
+
+        let actions = discord.wait_for(1).await;
+        let Action::Message {
+            channel_id,
+            payload,
+            ..
+        } = &actions[0]
+        else {
+            panic!("Expected an offline notice, got {actions:?}");
+        };
+        assert_eq!(*channel_id, CHANNEL);
+        assert!(payload["content"].as_str().unwrap().contains("offline"));
+
+        data.send_message(Shutdown);
+        run.await.unwrap().unwrap();
+
+        // the heartbeat is kept up to date, and saved once more when shutting down
+        assert!(store.last_seen().await.unwrap() > Some(last_seen));
+    }
+}
@@ -0,0 +1,493 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use poise::serenity_prelude::CacheHttp;
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::task::{self, JoinSet};
+
+use crate::{
+    data::{
+        status::Status,
+        store::{DeliveryAttempt, Store, Webhook},
+    },
+    prelude::*,
+};
+
+use super::{
+    outbox::{InFlight, Outbox},
+    BotTask,
+};
+
+/// How many times a report is sent to a webhook before giving up on it.
+const MAX_ATTEMPTS: usize = 5;
+/// How long to wait before the first retry, doubling after every failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The header containing the hex encoded HMAC-SHA256 of the body, if the webhook has a secret.
+pub const SIGNATURE_HEADER: &str = "X-Escalator-Signature";
+
+#[derive(Clone)]
+pub struct WebhookTask;
+
+pub struct TaskData {
+    store: Arc<dyn Store>,
+    outbox: Outbox,
+    client: reqwest::Client,
+}
+
+/// A report that changed the status of some escalators, as it's posted to webhooks.
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: &'static str,
+    escalators: &'a [EscalatorFloors],
+    status: Status,
+    /// Whether the report was made by a user, rather than by the bot itself.
+    /// Who made it is left out, since webhooks can be run by anyone.
+    user_reported: bool,
+    reported_at: DateTime<Utc>,
+}
+
+impl<T: CacheHttp + 'static> BotTask<T> for WebhookTask {
+    const NAME: &'static str = "webhook";
+    type Data = TaskData;
+
+    async fn setup(&self, data: &Data, _cache_http: Arc<T>) -> Option<Self::Data> {
+        // redirects and private addresses could be used to reach the bot's own network
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .ok()?;
+
+        Some(TaskData {
+            store: Arc::clone(&data.store),
+            outbox: Outbox::new(<Self as BotTask<T>>::NAME, data).await?,
+            client,
+        })
+    }
+
+    async fn run(self, mut data: Self::Data) -> anyhow::Result<()> {
+        // retries can take a while, so each webhook is delivered to in the background
+        let mut deliveries = JoinSet::new();
+        let mut delivering = HashMap::<task::Id, i64>::new();
+        let mut in_flight = InFlight::default();
+
+        loop {
+            tokio::select! {
+                res = data.outbox.next() => {
+                    let event = res?;
+                    let report = &event.report;
+
+                    let webhooks = if report.affected_escalators.is_empty() {
+                        vec![]
+                    } else {
+                        data.store.webhooks(&report.affected_escalators).await?
+                    };
+
+                    in_flight.start(event.id, webhooks.len());
+
+                    if !webhooks.is_empty() {
+                        let payload = Payload {
+                            event: "report",
+                            escalators: &report.affected_escalators,
+                            status: report.new_status,
+                            user_reported: report.reporter.is_some(),
+                            reported_at: event.created_at,
+                        };
+                        let body = serde_json::to_string(&payload)?;
+
+                        log::info!("Sending report to {} webhooks...", webhooks.len());
+
+                        for webhook in webhooks {
+                            let store = Arc::clone(&data.store);
+                            let client = data.client.clone();
+                            let body = body.clone();
+
+                            let delivery = deliveries.spawn(deliver_report(store, client, webhook, body));
+
+                            delivering.insert(delivery.id(), event.id);
+                        }
+                    }
+                }
+                Some(res) = deliveries.join_next_with_id() => {
+                    let delivery = match res {
+                        Ok((delivery, ())) => delivery,
+                        Err(err) => {
+                            log::warn!("A webhook delivery stopped unexpectedly: {err}");
+                            err.id()
+                        }
+                    };
+
+                    if let Some(event_id) = delivering.remove(&delivery) {
+                        in_flight.finish(event_id);
+                    }
+                }
+            }
+
+            data.outbox.handled(in_flight.handled()).await?;
+        }
+    }
+}
+
+/// Delivers a report to a webhook, and records how every attempt went.
+async fn deliver_report(
+    store: Arc<dyn Store>,
+    client: reqwest::Client,
+    webhook: Webhook,
+    body: String,
+) {
+    // hosts are checked when they're resolved, but IP addresses never are
+    let attempts = if literal_ip(&webhook.url).is_some_and(|ip| !is_public(ip)) {
+        vec![DeliveryAttempt {
+            status_code: None,
+            error: Some(String::from("private address")),
+        }]
+    } else {
+        deliver(
+            &client,
+            &webhook.url,
+            webhook.secret.as_deref(),
+            &body,
+            INITIAL_BACKOFF,
+        )
+        .await
+    };
+
+    let res = store.record_deliveries(webhook.webhook_id, &attempts).await;
+
+    if let Err(err) = res {
+        log::warn!("An error ocurred trying to record webhook deliveries: {err}");
+    }
+}
+
+/// Posts the body to the url until it's accepted, or it runs out of attempts,
+/// waiting longer after each failed attempt. Returns the outcome of every attempt.
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    secret: Option<&str>,
+    body: &str,
+    initial_backoff: Duration,
+) -> Vec<DeliveryAttempt> {
+    let mut attempts = vec![];
+    let mut backoff = initial_backoff;
+
+    loop {
+        let mut req = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_owned());
+
+        if let Some(secret) = secret {
+            req = req.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, body)));
+        }
+
+        let (attempt, retry) = match req.send().await {
+            Ok(res) => {
+                let status = res.status();
+                let attempt = DeliveryAttempt {
+                    status_code: Some(status.as_u16()),
+                    error: None,
+                };
+
+                // other client errors won't go away by trying again
+                let retry = status.is_server_error()
+                    || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    || status == reqwest::StatusCode::REQUEST_TIMEOUT;
+
+                (attempt, retry)
+            }
+            Err(err) => {
+                let attempt = DeliveryAttempt {
+                    status_code: None,
+                    error: Some(err.without_url().to_string()),
+                };
+
+                (attempt, true)
+            }
+        };
+
+        attempts.push(attempt);
+
+        if !retry || attempts.len() >= MAX_ATTEMPTS {
+            return attempts;
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
+/// Checks that a URL's host is on the public internet, resolving it if it isn't an IP address,
+/// so webhooks can't be used to reach the bot's own network (eg. `127.0.0.1`).
+pub async fn is_public_url(url: &reqwest::Url) -> bool {
+    if let Some(ip) = literal_ip(url.as_str()) {
+        return is_public(ip);
+    }
+
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return false;
+    };
+
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(addrs) => {
+            let ips = addrs.map(|addr| addr.ip()).collect::<Vec<_>>();
+            !ips.is_empty() && ips.into_iter().all(is_public)
+        }
+        Err(_) => false,
+    }
+}
+
+/// The URL's host, if it's an IP address rather than a domain.
+fn literal_ip(url: &str) -> Option<IpAddr> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?;
+
+    // IPv6 hosts are in brackets, like `[::1]`
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// Checks that an address isn't loopback, private, link-local, unspecified or otherwise reserved,
+/// including IPv6 addresses which embed an IPv4 address that isn't public.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // "this network", which reaches localhost on Linux
+                || a == 0
+                // shared address space (CGNAT)
+                || (a == 100 && b & 0b1100_0000 == 64)
+                // IETF protocol assignments
+                || (a == 192 && b == 0 && c == 0)
+                // benchmarking
+                || (a == 198 && b & 0b1111_1110 == 18)
+                // reserved
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            // IPv4-mapped `::ffff:a.b.c.d` and IPv4-compatible `::a.b.c.d`
+            if let Some(embedded) = ip.to_ipv4() {
+                return is_public(IpAddr::V4(embedded));
+            }
+
+            let segments = ip.segments();
+            let embedded = match segments {
+                // NAT64, with the address in the last 32 bits
+                [0x64, 0xff9b, 0, 0, 0, 0, high, low] => Some((high, low)),
+                // 6to4, with the address right after the prefix
+                [0x2002, high, low, ..] => Some((high, low)),
+                _ => None,
+            };
+
+            if let Some((high, low)) = embedded {
+                let embedded = Ipv4Addr::from((u32::from(high) << 16) | u32::from(low));
+                return is_public(IpAddr::V4(embedded));
+            }
+
+            !(ip.is_loopback()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // documentation
+                || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+        }
+    }
+}
+
+/// Resolves hosts like usual, leaving out any addresses that aren't public,
+/// so a host can't be pointed at the bot's own network after its webhook was added.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect::<Vec<SocketAddr>>();
+
+            if addrs.is_empty() {
+                return Err(
+                    format!("{} doesn't resolve to a public address", name.as_str()).into(),
+                );
+            }
+
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Signs the body with the webhook's secret, so receivers can check it came from the bot.
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body.as_bytes());
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use http_body_util::{BodyExt, Full};
+    use hyper::{
+        body::{Bytes, Incoming},
+        Request, Response, StatusCode,
+    };
+    use hyper_util::rt::TokioIo;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn bodies_are_signed_with_hmac_sha256() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    /// Serves a single connection that fails the first request, then accepts the rest,
+    /// sending the signature and body of every request it receives.
+    async fn stand_in() -> (String, mpsc::UnboundedReceiver<(Option<String>, Bytes)>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+
+            let service = hyper::service::service_fn(move |req: Request<Incoming>| {
+                let sender = sender.clone();
+                let first = count.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    let signature = req
+                        .headers()
+                        .get(SIGNATURE_HEADER)
+                        .map(|value| value.to_str().unwrap().to_owned());
+                    let body = req.into_body().collect().await.unwrap().to_bytes();
+
+                    sender.send((signature, body)).unwrap();
+
+                    let status = if first {
+                        StatusCode::SERVICE_UNAVAILABLE
+                    } else {
+                        StatusCode::OK
+                    };
+
+                    let mut res = Response::new(Full::new(Bytes::new()));
+                    *res.status_mut() = status;
+
+                    Ok::<_, Infallible>(res)
+                }
+            });
+
+            hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+                .unwrap();
+        });
+
+        (format!("http://{addr}/hook"), receiver)
+    }
+
+    #[tokio::test]
+    async fn private_addresses_are_rejected() {
+        for url in [
+            "http://127.0.0.1:8000/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.0.0.1/",
+            "http://[::1]/",
+            "http://[::ffff:192.168.0.1]/",
+            "http://0.0.0.0/",
+            "http://0.1.2.3/",
+            "http://100.64.0.1/",
+            "http://100.127.255.254/",
+            "http://192.0.0.8/",
+            "http://198.18.0.1/",
+            "http://198.19.255.1/",
+            "http://192.0.2.1/",
+            "http://198.51.100.1/",
+            "http://203.0.113.1/",
+            "http://240.0.0.1/",
+            "http://255.255.255.255/",
+            "http://[64:ff9b::a9fe:a9fe]/",
+            "http://[::10.0.0.1]/",
+            "http://[::7f00:1]/",
+            "http://[2002:a00:1::]/",
+            "http://[2002:7f00:1::1]/",
+            "http://[2001:db8::1]/",
+            "http://[fd00::1]/",
+            "http://localhost/",
+        ] {
+            let url = reqwest::Url::parse(url).unwrap();
+            assert!(!is_public_url(&url).await, "{url} should be rejected");
+        }
+
+        for url in [
+            "https://93.184.215.14/hook",
+            "http://100.128.0.1/",
+            "http://198.20.0.1/",
+            "http://[2606:4700::1111]/",
+            "http://[64:ff9b::5db8:d70e]/",
+            "http://[2002:5db8:d70e::]/",
+        ] {
+            let url = reqwest::Url::parse(url).unwrap();
+            assert!(is_public_url(&url).await, "{url} should be allowed");
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_are_retried() {
+        let (url, mut requests) = stand_in().await;
+        let client = reqwest::Client::new();
+
+        let attempts = deliver(
+            &client,
+            &url,
+            Some("secret"),
+            "{}",
+            Duration::from_millis(1),
+        )
+        .await;
+
+        let status_codes = attempts
+            .iter()
+            .map(|attempt| attempt.status_code)
+            .collect::<Vec<_>>();
+        assert_eq!(status_codes, [Some(503), Some(200)]);
+
+        let expected = format!("sha256={}", sign("secret", "{}"));
+
+        for _ in 0..2 {
+            let (signature, body) = requests.recv().await.unwrap();
+            assert_eq!(signature.as_deref(), Some(expected.as_str()));
+            assert_eq!(&body[..], b"{}");
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::{str::FromStr, time::Duration};
 
-use futures::{StreamExt, TryStreamExt};
+use futures::StreamExt;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use poise::{
@@ -8,12 +8,13 @@ use poise::{
     CreateReply,
 };
 
-use crate::{generate, prelude::*};
+use crate::{data::store::AlertStore, generate, locale::Locale, prelude::*};
 
 type Watchlist = IndexMap<EscalatorFloors, Subscription>;
 
 struct WatchlistComponent {
     watchlist: Watchlist,
+    locale: Locale,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -22,7 +23,7 @@ enum Subscription {
     Ignore,
 }
 
-#[poise::command(slash_command, subcommands("edit", "list"))]
+#[poise::command(slash_command, subcommands("edit", "floor", "list"))]
 pub async fn alerts(_ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
@@ -34,20 +35,25 @@ pub async fn edit(ctx: Context<'_>) -> Result<(), Error> {
 
     ctx.defer_ephemeral().await?;
 
-    let watchlist = match load_watchlist(&ctx.data().pool, ctx.author().id).await {
+    let locale = super::reply_locale(ctx).await;
+    let catalog = locale.catalog();
+
+    save_locale(ctx).await;
+
+    let watchlist = match load_watchlist(&*ctx.data().store, ctx.author().id).await {
         Ok(watchlist) => watchlist,
         Err(err) => {
             log::error!("An error ocurred trying to load watchlist: {err}");
-            ctx.say("A database error ocurred.").await?;
+            ctx.say(catalog.database_error()).await?;
 
             return Ok(());
         }
     };
 
-    let mut watchlist = WatchlistComponent { watchlist };
+    let mut watchlist = WatchlistComponent { watchlist, locale };
 
     let reply = CreateReply::default()
-        .content(generate::timeout_message(TIMEOUT))
+        .content(generate::timeout_message(TIMEOUT, locale))
         .components(watchlist.render());
     let handle = ctx.send(reply).await?;
 
@@ -81,7 +87,7 @@ pub async fn edit(ctx: Context<'_>) -> Result<(), Error> {
         }
 
         let edit = CreateReply::default()
-            .content(generate::timeout_message(TIMEOUT))
+            .content(generate::timeout_message(TIMEOUT, locale))
             .components(watchlist.render());
         handle.edit(ctx, edit).await?;
     };
@@ -90,26 +96,26 @@ pub async fn edit(ctx: Context<'_>) -> Result<(), Error> {
 
     // clear the components
     let edit = CreateReply::default()
-        .content("Processing...")
+        .content(catalog.processing())
         .components(vec![]);
     handle.edit(ctx, edit).await?;
 
     let Some(watchlist) = res else {
-        let edit = CreateReply::default().content("Interaction timed out, try again...");
+        let edit = CreateReply::default().content(catalog.timed_out());
         handle.edit(ctx, edit).await?;
 
         return Ok(());
     };
 
-    if let Err(err) = update_watchlist(&ctx.data().pool, ctx.author().id, watchlist).await {
+    if let Err(err) = update_watchlist(&*ctx.data().store, ctx.author().id, watchlist).await {
         log::error!("An error ocurred trying to update watchlist: {err}");
-        let edit = CreateReply::default().content("A database error ocurred.");
+        let edit = CreateReply::default().content(catalog.database_error());
         handle.edit(ctx, edit).await?;
 
         return Ok(());
     }
 
-    let edit = CreateReply::default().content("Watchlist updated.");
+    let edit = CreateReply::default().content(catalog.watchlist_updated());
     handle
         .edit(ctx, edit)
         .await?;
@@ -117,35 +123,71 @@ pub async fn edit(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Toggle alerts for every escalator that starts or ends at a floor.
+#[poise::command(slash_command, ephemeral = true)]
+pub async fn floor(
+    ctx: Context<'_>,
+    #[description = "The floor to watch (or stop watching)"]
+    #[min = 1]
+    #[max = 9]
+    floor: u8,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    save_locale(ctx).await;
+
+    let msg = match ctx.data().store.toggle_floor(ctx.author().id, floor).await {
+        Ok(true) => catalog.floor_watched(floor),
+        Ok(false) => catalog.floor_unwatched(floor),
+        Err(err) => {
+            log::error!("An error ocurred trying to toggle a floor alert: {err}");
+            String::from(catalog.database_error())
+        }
+    };
+
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
 /// Check your watch list
 #[poise::command(slash_command, ephemeral = true)]
 pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
-    let res = sqlx::query_as::<_, Escalator>(
-        "
-        SELECT e.floor_start, e.floor_end, e.current_status
-        FROM alerts a
-        INNER JOIN escalators e
-            ON a.floor_start = e.floor_start
-            AND a.floor_end = e.floor_end
-        WHERE a.user_id = $1
-        ORDER BY a.floor_start, a.floor_end
-        ",
-    )
-    .bind(ctx.author().id.get() as i64)
-    .fetch_all(&ctx.data().pool)
-    .await;
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let floors = match ctx.data().store.watched_floors(ctx.author().id).await {
+        Ok(floors) => floors,
+        Err(err) => {
+            log::error!("An error ocurred trying to load watched floors: {err}");
+            ctx.say(catalog.database_error()).await?;
+
+            return Ok(());
+        }
+    };
+
+    let res = ctx.data().store.watched_escalators(ctx.author().id).await;
 
     let msg = match res {
         Ok(watchlist) => {
             let body = watchlist.iter().map(Escalator::to_string).join("\n");
 
-            format!("**Your Watch List:**```\n{body}```")
+            let mut msg = format!("**{}:**```\n{body}```", catalog.watch_list_title());
+
+            if !floors.is_empty() {
+                let floors = floors.iter().map(|floor| format!("`{floor}`")).join(", ");
+                msg.push('\n');
+                msg.push_str(&format!("**{}:** {floors}", catalog.watched_floors_title()));
+            }
+
+            msg
         }
         Err(err) => {
             log::error!("An error ocurred generating the watchlist status: {err}");
-            String::from("A database error ocurred.")
+            String::from(catalog.database_error())
         }
     };
 
@@ -154,98 +196,47 @@ pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Remembers the user's locale, so their alerts can be sent in it.
+async fn save_locale(ctx: Context<'_>) {
+    let Some(locale) = ctx.locale().and_then(Locale::from_discord) else {
+        return;
+    };
+
+    if let Err(err) = ctx
+        .data()
+        .store
+        .set_user_locale(ctx.author().id, locale)
+        .await
+    {
+        log::warn!("An error ocurred trying to save a user's locale: {err}");
+    }
+}
+
 async fn load_watchlist(
-    pool: &sqlx::PgPool,
+    store: &dyn AlertStore,
     user_id: serenity::UserId,
 ) -> Result<Watchlist, sqlx::Error> {
-    #[derive(sqlx::FromRow)]
-    struct WatchlistEntry {
-        #[sqlx(flatten)]
-        floors: EscalatorFloors,
-        #[sqlx(flatten)]
-        subscription: Subscription,
-    }
-
-    let mut watchlist = IndexMap::new();
-
-    let mut entries = sqlx::query_as::<_, WatchlistEntry>(
-        "
-        SELECT e.floor_start, e.floor_end, (a.user_id IS NOT NULL) as watching
-        FROM escalators e
-        LEFT OUTER JOIN alerts a
-            ON e.floor_start = a.floor_start
-            AND e.floor_end = a.floor_end
-            AND a.user_id = $1
-        ORDER BY e.floor_start + e.floor_end, e.floor_start
-        ",
-    )
-    .bind(user_id.get() as i64)
-    .fetch(pool);
-
-    while let Some(entry) = entries.try_next().await? {
-        watchlist.insert(entry.floors, entry.subscription);
-    }
+    let watchlist = store
+        .watchlist(user_id)
+        .await?
+        .into_iter()
+        .map(|(floors, watching)| (floors, Subscription::from(watching)))
+        .collect();
 
     Ok(watchlist)
 }
 
 async fn update_watchlist(
-    pool: &sqlx::PgPool,
+    store: &dyn AlertStore,
     user_id: serenity::UserId,
     watchlist: &Watchlist,
 ) -> Result<(), sqlx::Error> {
-    let mut transaction = pool.begin().await?;
-
-    let mut starts = vec![];
-    let mut ends = vec![];
-
     let watching = watchlist
         .iter()
-        .filter_map(|(floors, sub)| (sub.is_watching()).then_some(*floors));
-
-    for EscalatorFloors { start, end } in watching {
-        starts.push(start as i16);
-        ends.push(end as i16);
-    }
+        .filter_map(|(floors, sub)| (sub.is_watching()).then_some(*floors))
+        .collect_vec();
 
-    sqlx::query(
-        "
-        DELETE FROM alerts a
-        WHERE user_id = $1
-        AND NOT EXISTS (
-            SELECT FROM UNNEST($2::smallint[], $3::smallint[])
-                AS w (floor_start, floor_end)
-            WHERE a.floor_start = w.floor_start
-            AND a.floor_end = w.floor_end
-        )
-        ",
-    )
-    .bind(user_id.get() as i64)
-    .bind(&starts)
-    .bind(&ends)
-    .execute(&mut *transaction)
-    .await?;
-
-    sqlx::query(
-        "
-        INSERT INTO alerts (user_id, floor_start, floor_end)
-        SELECT $1 as user_id, w.floor_start, w.floor_end
-        FROM UNNEST($2::smallint[], $3::smallint[])
-            AS w (floor_start, floor_end)
-        LEFT OUTER JOIN alerts a
-            ON $1 = a.user_id
-            AND w.floor_start = a.floor_start
-            AND w.floor_end = a.floor_end
-        WHERE a.user_id IS NULL
-        ",
-    )
-    .bind(user_id.get() as i64)
-    .bind(&starts)
-    .bind(&ends)
-    .execute(&mut *transaction)
-    .await?;
-
-    transaction.commit().await
+    store.set_watchlist(user_id, &watching).await
 }
 
 const ESCALATOR_BUTTON_ID_PREFIX: &str = "ALERTS-ESCALATOR-";
@@ -291,7 +282,7 @@ impl WatchlistComponent {
 
         action_rows.last_mut().unwrap().push(
             CreateButton::new(SUBMIT_BUTTON_ID)
-                .label("Save List")
+                .label(self.locale.catalog().save_list_button())
                 .style(serenity::ButtonStyle::Success)
                 .emoji(SUBMIT_BUTTON_EMOJI),
         );
@@ -332,17 +323,13 @@ impl Subscription {
     }
 }
 
-impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for Subscription {
-    fn from_row(row: &'r sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
-        use sqlx::Row;
-
-        let sub = if row.try_get("watching")? {
+impl From<bool> for Subscription {
+    fn from(watching: bool) -> Self {
+        if watching {
             Subscription::Watch
         } else {
             Subscription::Ignore
-        };
-
-        Ok(sub)
+        }
     }
 }
 
@@ -0,0 +1,121 @@
+use crate::{data::settings::Setting, prelude::*};
+
+#[poise::command(
+    slash_command,
+    subcommands("view", "set", "reset"),
+    check = "super::admin_check"
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// (admin-only) View this server's settings.
+#[poise::command(slash_command, ephemeral = true)]
+async fn view(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(catalog.guild_only("/config view")).await?;
+        return Ok(());
+    };
+
+    let msg = match ctx.data().store.guild_settings(guild_id).await {
+        Ok(settings) => catalog.config_view(&settings),
+        Err(err) => {
+            log::warn!("An error ocurred while loading the server's settings: {err}");
+            String::from(catalog.database_error())
+        }
+    };
+
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// (admin-only) Change one of this server's settings.
+#[poise::command(slash_command, ephemeral = true)]
+async fn set(
+    ctx: Context<'_>,
+    #[description = "The setting to change"] setting: Setting,
+    #[description = "The new value, eg. `120`, `yes`, `live` or `es` (see `/config view`)"]
+    value: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let Some(guild_id) = ctx.guild_id() else {
+        let catalog = super::reply_locale(ctx).await.catalog();
+        ctx.say(catalog.guild_only("/config set")).await?;
+        return Ok(());
+    };
+
+    let value = match setting.parse(&value) {
+        Ok(value) => value,
+        Err(err) => {
+            let catalog = super::reply_locale(ctx).await.catalog();
+            ctx.say(catalog.invalid_value(setting, err)).await?;
+            return Ok(());
+        }
+    };
+
+    let res = ctx
+        .data()
+        .store
+        .save_setting(guild_id, setting, value)
+        .await;
+
+    // the locale is resolved after saving, so changing the language is reflected right away
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let msg = match res {
+        Ok(settings) => format!(
+            "{}\n\n{}",
+            catalog.setting_updated(setting),
+            catalog.config_view(&settings)
+        ),
+        Err(err) => {
+            log::warn!("An error ocurred while updating the server's settings: {err}");
+            String::from(catalog.database_error())
+        }
+    };
+
+    ctx.say(msg).await?;
+
+    Ok(())
+}
+
+/// (admin-only) Reset one of this server's settings, or every setting, to the default.
+#[poise::command(slash_command, ephemeral = true)]
+async fn reset(
+    ctx: Context<'_>,
+    #[description = "The setting to reset (defaults to every setting)"] setting: Option<Setting>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let Some(guild_id) = ctx.guild_id() else {
+        let catalog = super::reply_locale(ctx).await.catalog();
+        ctx.say(catalog.guild_only("/config reset")).await?;
+        return Ok(());
+    };
+
+    let res = ctx.data().store.reset_settings(guild_id, setting).await;
+
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let msg = match res {
+        Ok(settings) => format!(
+            "{}\n\n{}",
+            catalog.setting_reset(setting),
+            catalog.config_view(&settings)
+        ),
+        Err(err) => {
+            log::warn!("An error ocurred while resetting the server's settings: {err}");
+            String::from(catalog.database_error())
+        }
+    };
+
+    ctx.say(msg).await?;
+
+    Ok(())
+}
@@ -1,38 +1,37 @@
 use crate::prelude::*;
 
-#[poise::command(slash_command, subcommands("set", "remove"), owners_only)]
+#[poise::command(
+    slash_command,
+    subcommands("set", "remove"),
+    check = "super::admin_check"
+)]
 pub async fn history(_ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-/// (dev-only) Set the announcements channel for this server.
+/// (admin-only) Set the announcements channel for this server.
 #[poise::command(slash_command, ephemeral = true)]
 async fn set(ctx: Context<'_>, channel: serenity::Channel) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
+    let catalog = super::reply_locale(ctx).await.catalog();
+
     let Some(guild_id) = ctx.guild_id() else {
-        ctx.say("This command must be used in a server.").await?;
+        ctx.say(catalog.guild_only("/history set")).await?;
         return Ok(());
     };
 
-    let res = sqlx::query(
-        "
-        INSERT INTO announcement_channels (guild_id, channel_id)
-        VALUES ($1, $2)
-        ON CONFLICT (guild_id)
-            DO UPDATE SET channel_id = $2
-        ",
-    )
-    .bind(guild_id.get() as i64)
-    .bind(channel.id().get() as i64)
-    .execute(&ctx.data().pool)
-    .await;
+    let res = ctx
+        .data()
+        .store
+        .set_announcement_channel(guild_id, channel.id())
+        .await;
 
     let msg = if let Err(err) = res {
         log::warn!("An error ocurred while updating the history channel: {err}");
-        String::from("A database error ocurred.")
+        String::from(catalog.database_error())
     } else {
-        format!("Set history channel to <#{}>.", channel.id())
+        catalog.history_channel_set(channel.id())
     };
 
     ctx.say(msg).await?;
@@ -40,31 +39,25 @@ async fn set(ctx: Context<'_>, channel: serenity::Channel) -> Result<(), Error>
     Ok(())
 }
 
-/// (dev-only) Remove the announcements channel for this server.
+/// (admin-only) Remove the announcements channel for this server.
 #[poise::command(slash_command, ephemeral = true)]
 async fn remove(ctx: Context<'_>) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
+    let catalog = super::reply_locale(ctx).await.catalog();
+
     let Some(guild_id) = ctx.guild_id() else {
-        ctx.say("This command must be used in a server.").await?;
+        ctx.say(catalog.guild_only("/history remove")).await?;
         return Ok(());
     };
 
-    let res = sqlx::query(
-        "
-        DELETE FROM announcement_channels
-        WHERE guild_id = $1
-        ",
-    )
-    .bind(guild_id.get() as i64)
-    .execute(&ctx.data().pool)
-    .await;
+    let res = ctx.data().store.remove_announcement_channel(guild_id).await;
 
     let msg = match res {
-        Ok(_) => String::from("Removed history channel"),
+        Ok(_) => catalog.history_channel_removed(),
         Err(err) => {
             log::warn!("An error ocurred while removing the history channel: {err}");
-            String::from("A database error ocurred.")
+            catalog.database_error()
         }
     };
 
@@ -1,110 +1,182 @@
+use itertools::Itertools;
 use poise::serenity_prelude::{ChannelId, CreateMessage, MessageId};
 
-use crate::{generate, prelude::*};
+use crate::{data::store::Menu, generate, prelude::*};
 
-#[poise::command(slash_command, subcommands("init", "clear"), owners_only)]
+#[poise::command(
+    slash_command,
+    subcommands("init", "status", "clear"),
+    check = "super::moderator_check"
+)]
 pub async fn menu(_ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-/// (dev-only) Initialize the status menu in the current channel.
+/// (moderator-only) Initialize a status menu in the current channel.
 #[poise::command(slash_command, ephemeral = true)]
-async fn init(ctx: Context<'_>) -> Result<(), Error> {
+async fn init(
+    ctx: Context<'_>,
+    #[description = "Only show these escalators, eg. `2-4 3/5` (defaults to all escalators)"]
+    escalators: Option<String>,
+) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
+    let catalog = super::reply_locale(ctx).await.catalog();
+
     let Some(guild_id) = ctx.guild_id() else {
-        ctx.say("`/menu init` must be used in a guild.").await?;
+        ctx.say(catalog.guild_only("/menu init")).await?;
         return Ok(());
     };
 
-    let mut transaction = ctx.data().pool.begin().await?;
-
-    let menu_exists = sqlx::query(
-        "
-        SELECT 1
-        FROM menu_messages
-        WHERE guild_id = $1
-        ",
-    )
-    .bind(guild_id.get() as i64)
-    .fetch_optional(&mut *transaction)
-    .await?
-    .is_some();
-
-    if menu_exists {
-        ctx.say("Menu already exists, use `/menu clear` to delete it.")
-            .await?;
+    let scope = match super::parse_scope(escalators.as_deref().unwrap_or("")) {
+        Ok(scope) => scope,
+        Err(err) => {
+            ctx.say(format!("{}.", catalog.input_error(err))).await?;
+            return Ok(());
+        }
+    };
+
+    // the menu is shown to everyone, so it uses the server's settings
+    let settings = ctx.data().store.guild_settings(guild_id).await?;
+
+    let all_escalators = ctx.data().store.escalators().await?;
+
+    if let Some(floors) = scope
+        .iter()
+        .find(|floors| !all_escalators.iter().any(|e| e.floors == **floors))
+    {
+        ctx.say(catalog.not_an_escalator(*floors)).await?;
         return Ok(());
     }
 
+    let escalators = all_escalators
+        .into_iter()
+        .filter(|escalator| scope.is_empty() || scope.contains(&escalator.floors))
+        .collect_vec();
+
     let channel_id = ctx.channel_id();
 
-    let statuses = generate::menu_status(&ctx.data().pool).await?;
-    let menu_buttons = generate::menu_buttons();
+    let mut msg = CreateMessage::new()
+        .content(generate::menu_status(&escalators, &settings))
+        .components(vec![generate::menu_buttons(settings.locale)]);
 
-    let msg = CreateMessage::new()
-        .content(statuses)
-        .components(vec![menu_buttons]);
-    let menu = channel_id.send_message(ctx, msg).await?;
+    if let Some(diagram) = generate::menu_diagram(&escalators, &settings) {
+        msg = msg.add_file(diagram);
+    }
 
-    let message_id = menu.id;
+    let menu = channel_id.send_message(ctx, msg).await?;
 
-    sqlx::query(
-        "
-        INSERT INTO menu_messages (guild_id, channel_id, message_id)
-        VALUES ($1, $2, $3)
-        ",
-    )
-    .bind(guild_id.get() as i64)
-    .bind(channel_id.get() as i64)
-    .bind(message_id.get() as i64)
-    .execute(&mut *transaction)
-    .await?;
+    let menu = Menu {
+        guild_id: guild_id.get() as i64,
+        channel_id: channel_id.get() as i64,
+        message_id: menu.id.get() as i64,
+    };
 
-    transaction.commit().await?;
+    ctx.data().store.add_menu(menu, &scope).await?;
 
-    ctx.say("Initialized report menu.").await?;
+    ctx.say(catalog.menu_initialized()).await?;
 
     Ok(())
 }
 
-/// (dev-only) Remove the status menu.
+/// (moderator-only) List the status menus in this server, and whether they're able to sync.
 #[poise::command(slash_command, ephemeral = true)]
-async fn clear(ctx: Context<'_>) -> Result<(), Error> {
+async fn status(ctx: Context<'_>) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
+    let catalog = super::reply_locale(ctx).await.catalog();
+
     let Some(guild_id) = ctx.guild_id() else {
-        ctx.say("`/menu clear` must be used in a guild.").await?;
+        ctx.say(catalog.guild_only("/menu status")).await?;
         return Ok(());
     };
 
-    let Some((channel_id, message_id)) = sqlx::query_as::<_, (i64, i64)>(
-        "
-        DELETE FROM menu_messages
-        WHERE guild_id = $1
-        RETURNING channel_id, message_id
-        ",
-    )
-    .bind(guild_id.get() as i64)
-    .fetch_optional(&ctx.data().pool)
-    .await?
-    else {
-        ctx.say("No report menu exists in this server.").await?;
+    let menus = ctx.data().store.guild_menus(guild_id).await?;
+
+    if menus.is_empty() {
+        ctx.say(catalog.no_menus()).await?;
         return Ok(());
+    }
+
+    let body = menus
+        .into_iter()
+        .map(|menu| {
+            let link = MessageId::new(menu.message_id as u64)
+                .link(ChannelId::new(menu.channel_id as u64), Some(guild_id));
+
+            let escalators = if menu.escalators.is_empty() {
+                String::from(catalog.all_escalators())
+            } else {
+                menu.escalators.iter().join(", ")
+            };
+
+            let health = match menu.last_error {
+                _ if menu.missing_permissions => String::from(catalog.menu_missing_permissions()),
+                Some(err) => catalog.menu_sync_failed(&err),
+                None => String::from(catalog.menu_syncing()),
+            };
+
+            format!("`{}` {link} ({escalators})\n{health}", menu.message_id)
+        })
+        .join("\n");
+
+    ctx.say(format!("**{}:**\n{body}", catalog.menus_title()))
+        .await?;
+
+    Ok(())
+}
+
+/// (moderator-only) Remove a status menu, or every status menu in the current channel.
+#[poise::command(slash_command, ephemeral = true)]
+async fn clear(
+    ctx: Context<'_>,
+    #[description = "The message ID of the menu to remove (see `/menu status`)"] message_id: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(catalog.guild_only("/menu clear")).await?;
+        return Ok(());
+    };
+
+    let message_id = match message_id.map(|id| id.trim().parse::<u64>()) {
+        Some(Ok(id)) if id != 0 => Some(MessageId::new(id)),
+        Some(_) => {
+            ctx.say(catalog.invalid_message_id()).await?;
+            return Ok(());
+        }
+        None => None,
     };
 
-    let channel_id = ChannelId::new(channel_id as u64);
-    let message_id = MessageId::new(message_id as u64);
-    let res = ctx
-        .http()
-        .delete_message(channel_id, message_id, None)
-        .await;
+    let menus = ctx
+        .data()
+        .store
+        .remove_menus(guild_id, ctx.channel_id(), message_id)
+        .await?;
+
+    if menus.is_empty() {
+        ctx.say(catalog.no_matching_menu()).await?;
+        return Ok(());
+    }
 
-    if let Err(err) = res {
-        log::warn!("An error ocurred trying to delete report menu: {err}");
+    for menu in &menus {
+        let channel_id = ChannelId::new(menu.channel_id as u64);
+        let message_id = MessageId::new(menu.message_id as u64);
+        let res = ctx
+            .http()
+            .delete_message(channel_id, message_id, None)
+            .await;
+
+        if let Err(err) = res {
+            log::warn!("An error ocurred trying to delete report menu: {err}");
+        }
     }
 
-    ctx.say("Deleted report menu").await?;
+    ctx.say(catalog.menus_deleted(menus.len())).await?;
 
     Ok(())
 }
@@ -1,20 +1,136 @@
 mod alerts;
+mod config;
 mod history;
 mod menu;
+mod stats;
+mod webhooks;
 
+use itertools::Itertools;
 use poise::CreateReply;
 
-use crate::{generate, prelude::*};
+use crate::{
+    data::{
+        escalator_input::{EscalatorInput, InputError},
+        settings::Access,
+    },
+    generate,
+    locale::{self, Locale},
+    prelude::*,
+};
 
 /// Returns a vector containing all enabled bot commands.
 pub fn commands() -> Vec<poise::Command<crate::Data, Error>> {
-    vec![
+    let mut commands = vec![
         register(),
         menu::menu(),
         history::history(),
         alerts::alerts(),
+        stats::stats(),
+        config::config(),
+        webhooks::webhooks(),
         gist(),
-    ]
+    ];
+
+    for command in &mut commands {
+        localize(command, None);
+    }
+
+    commands
+}
+
+/// Adds the descriptions from every locale's catalog to a command and its subcommands.
+fn localize(command: &mut poise::Command<crate::Data, Error>, parent: Option<&str>) {
+    let path = match parent {
+        Some(parent) => format!("{parent} {}", command.name),
+        None => command.name.clone(),
+    };
+
+    for locale in Locale::ALL {
+        let catalog = locale.catalog();
+
+        for &discord_locale in locale.discord_locales() {
+            if let Some(description) = catalog.command_description(&path) {
+                command
+                    .description_localizations
+                    .insert(discord_locale.to_owned(), description.to_owned());
+            }
+
+            for parameter in &mut command.parameters {
+                if let Some(description) = catalog.parameter_description(&path, &parameter.name) {
+                    parameter
+                        .description_localizations
+                        .insert(discord_locale.to_owned(), description.to_owned());
+                }
+            }
+        }
+    }
+
+    for subcommand in &mut command.subcommands {
+        localize(subcommand, Some(&path));
+    }
+}
+
+/// The locale to reply to a command in,
+/// which is the user's own locale if it's supported, and otherwise the server's locale.
+async fn reply_locale(ctx: Context<'_>) -> Locale {
+    locale::for_interaction(&*ctx.data().store, ctx.locale(), ctx.guild_id()).await
+}
+
+/// Only lets admins use a command.
+async fn admin_check(ctx: Context<'_>) -> Result<bool, Error> {
+    check_access(ctx, Access::Admin).await
+}
+
+/// Only lets moderators and admins use a command.
+async fn moderator_check(ctx: Context<'_>) -> Result<bool, Error> {
+    check_access(ctx, Access::Moderator).await
+}
+
+/// Checks whether or not the author has the required access in the guild,
+/// letting them know why they can't use the command if they don't.
+/// The bot's owners have access to every guild.
+async fn check_access(ctx: Context<'_>, required: Access) -> Result<bool, Error> {
+    if ctx.framework().options().owners.contains(&ctx.author().id) {
+        return Ok(true);
+    }
+
+    let access = match (ctx.guild_id(), ctx.author_member().await) {
+        (Some(guild_id), Some(member)) => ctx
+            .data()
+            .store
+            .guild_settings(guild_id)
+            .await?
+            .access(member.permissions, &member.roles),
+        _ => None,
+    };
+
+    if access.is_some_and(|access| access >= required) {
+        return Ok(true);
+    }
+
+    let catalog = reply_locale(ctx).await.catalog();
+    let msg = CreateReply::default()
+        .content(catalog.missing_access(required))
+        .ephemeral(true);
+    ctx.send(msg).await?;
+
+    Ok(false)
+}
+
+/// Parses a list of escalators separated by spaces or commas,
+/// returning an empty list if every escalator is included.
+fn parse_scope(input: &str) -> Result<Vec<EscalatorFloors>, InputError> {
+    let mut scope = vec![];
+
+    for input in input.split([' ', ',']).filter(|s| !s.is_empty()) {
+        let Some(floors) = input.parse::<EscalatorInput>()?.floors() else {
+            return Ok(vec![]);
+        };
+
+        scope.extend(floors);
+    }
+
+    Ok(scope.into_iter().unique().collect())
 }
 
 /// (dev-only) Spawn a button panel to register application commands.
@@ -29,14 +145,16 @@ async fn register(ctx: Context<'_>) -> Result<(), Error> {
 async fn gist(ctx: Context<'_>) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
-    match generate::gist(&ctx.data().pool).await {
+    let locale = reply_locale(ctx).await;
+
+    match generate::gist(&*ctx.data().store, locale).await {
         Ok(gist) => {
             let msg = CreateReply::default().embed(gist);
             ctx.send(msg).await?;
         }
         Err(err) => {
             log::error!("An error ocurred trying to generate a gist: {err}");
-            ctx.say("A database error ocurred.").await?;
+            ctx.say(locale.catalog().database_error()).await?;
         }
     }
 
@@ -0,0 +1,77 @@
+use chrono::Utc;
+use chrono_tz::America::New_York as NYCTimeZone;
+use poise::CreateReply;
+
+use crate::{data::history, generate, prelude::*, render};
+
+const RELIABILITY_COUNT: usize = 5;
+
+/// Display how reliable the escalators have been recently.
+#[poise::command(slash_command, ephemeral = true)]
+pub async fn stats(
+    ctx: Context<'_>,
+    #[description = "How many days to look back on (defaults to 7)"]
+    #[min = 1]
+    #[max = 90]
+    days: Option<u16>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = super::reply_locale(ctx).await;
+    let catalog = locale.catalog();
+
+    let days = days.unwrap_or(7);
+
+    let end = Utc::now();
+    let start = end - chrono::Duration::days(days as i64);
+
+    let store = &*ctx.data().store;
+
+    let res = tokio::try_join!(
+        history::load_uptimes(store, start, end),
+        store.last_changes(start),
+        store.changes(start, end),
+    );
+
+    let (uptimes, before, changes) = match res {
+        Ok(stats) => stats,
+        Err(err) => {
+            log::error!("An error ocurred trying to load the reliability stats: {err}");
+            ctx.say(catalog.database_error()).await?;
+
+            return Ok(());
+        }
+    };
+
+    let outages = history::outages_by_hour(&before, &changes, &NYCTimeZone);
+    let outage_count = outages.iter().flatten().sum::<u32>();
+
+    let embed = serenity::CreateEmbed::default()
+        .title(catalog.reliability_title(days))
+        .field(
+            catalog.most_reliable(),
+            generate::uptimes(uptimes.iter().take(RELIABILITY_COUNT)),
+            true,
+        )
+        .field(
+            catalog.least_reliable(),
+            generate::uptimes(uptimes.iter().rev().take(RELIABILITY_COUNT)),
+            true,
+        )
+        .field(catalog.outages_reported(), outage_count.to_string(), true);
+
+    let mut reply = CreateReply::default().embed(embed);
+
+    match render::reliability_charts(&outages, &uptimes, locale) {
+        Ok(charts) => {
+            for chart in charts {
+                reply = reply.attachment(chart);
+            }
+        }
+        Err(err) => log::warn!("An error ocurred trying to render the reliability charts: {err}"),
+    }
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use itertools::Itertools;
+
+use crate::{bot_tasks::webhook, generate, locale::WebhookDelivery, prelude::*};
+
+/// How many webhooks a server can register.
+const MAX_WEBHOOKS: usize = 5;
+
+#[poise::command(
+    slash_command,
+    subcommands("add", "list", "remove"),
+    check = "super::admin_check"
+)]
+pub async fn webhooks(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// (admin-only) Register a URL to be sent every status report.
+#[poise::command(slash_command, ephemeral = true)]
+async fn add(
+    ctx: Context<'_>,
+    #[description = "The URL to POST reports to"] url: String,
+    #[description = "A secret to sign reports with, sent in the `X-Escalator-Signature` header"]
+    secret: Option<String>,
+    #[description = "Only send reports for these escalators, eg. `2-4 3/5` (defaults to all escalators)"]
+    escalators: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(catalog.guild_only("/webhooks add")).await?;
+        return Ok(());
+    };
+
+    let url = match reqwest::Url::parse(url.trim()) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+        _ => {
+            ctx.say(catalog.invalid_webhook_url()).await?;
+            return Ok(());
+        }
+    };
+
+    if !webhook::is_public_url(&url).await {
+        ctx.say(catalog.private_webhook_url()).await?;
+        return Ok(());
+    }
+
+    let scope = match super::parse_scope(escalators.as_deref().unwrap_or("")) {
+        Ok(scope) => scope,
+        Err(err) => {
+            ctx.say(format!("{}.", catalog.input_error(err))).await?;
+            return Ok(());
+        }
+    };
+
+    let all_escalators = ctx.data().store.escalators().await?;
+
+    if let Some(floors) = scope
+        .iter()
+        .find(|floors| !all_escalators.iter().any(|e| e.floors == **floors))
+    {
+        ctx.say(catalog.not_an_escalator(*floors)).await?;
+        return Ok(());
+    }
+
+    let secret = secret.filter(|secret| !secret.is_empty());
+
+    let res = ctx
+        .data()
+        .store
+        .add_webhook(
+            guild_id,
+            url.as_str(),
+            secret.as_deref(),
+            &scope,
+            MAX_WEBHOOKS,
+        )
+        .await?;
+
+    let Some(webhook_id) = res else {
+        ctx.say(catalog.too_many_webhooks(MAX_WEBHOOKS)).await?;
+        return Ok(());
+    };
+
+    ctx.say(catalog.webhook_added(webhook_id)).await?;
+
+    Ok(())
+}
+
+/// (admin-only) List the webhooks in this server, and how their last delivery went.
+#[poise::command(slash_command, ephemeral = true)]
+async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(catalog.guild_only("/webhooks list")).await?;
+        return Ok(());
+    };
+
+    let webhooks = ctx.data().store.guild_webhooks(guild_id).await?;
+
+    if webhooks.is_empty() {
+        ctx.say(catalog.no_webhooks()).await?;
+        return Ok(());
+    }
+
+    let body = webhooks
+        .into_iter()
+        .map(|webhook| {
+            let escalators = if webhook.escalators.is_empty() {
+                String::from(catalog.all_escalators())
+            } else {
+                webhook.escalators.iter().join(", ")
+            };
+            let signed = if webhook.signed {
+                format!(" {}", catalog.webhook_signed())
+            } else {
+                String::new()
+            };
+
+            let delivery = webhook.last_delivery.map(|delivery| WebhookDelivery {
+                delivered: delivery
+                    .attempt
+                    .status_code
+                    .is_some_and(|code| (200..300).contains(&code)),
+                timestamp: generate::relative_age(delivery.attempted_at),
+            });
+
+            format!(
+                "`{}` <{}> ({escalators}){signed}\n{}",
+                webhook.webhook_id,
+                webhook.url,
+                catalog.webhook_delivery(delivery.as_ref())
+            )
+        })
+        .join("\n");
+
+    ctx.say(format!("**{}:**\n{body}", catalog.webhooks_title()))
+        .await?;
+
+    Ok(())
+}
+
+/// (admin-only) Remove a webhook from this server.
+#[poise::command(slash_command, ephemeral = true)]
+async fn remove(
+    ctx: Context<'_>,
+    #[description = "The ID of the webhook to remove (see `/webhooks list`)"] webhook_id: i32,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let catalog = super::reply_locale(ctx).await.catalog();
+
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say(catalog.guild_only("/webhooks remove")).await?;
+        return Ok(());
+    };
+
+    let removed = ctx
+        .data()
+        .store
+        .remove_webhook(guild_id, webhook_id)
+        .await?;
+
+    if !removed {
+        ctx.say(catalog.no_matching_webhook()).await?;
+    } else {
+        ctx.say(catalog.webhook_removed()).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! The database everything is stored in, which is Postgres by default,
+//! or SQLite when built with the `sqlite` feature (for small deployments and local testing).
+//!
+//! Most queries are written so they work with both, and the few that can't be
+//! are written twice and picked between with [`sql!`].
+
+#[cfg(not(feature = "sqlite"))]
+pub type Db = sqlx::Postgres;
+#[cfg(feature = "sqlite")]
+pub type Db = sqlx::Sqlite;
+
+pub type DbPool = sqlx::Pool<Db>;
+pub type DbRow = <Db as sqlx::Database>::Row;
+pub type DbConnection = <Db as sqlx::Database>::Connection;
+
+/// The migrations for the database the bot was built for.
+/// SQLite has its own migrations with the same versions, creating an equivalent schema.
+#[cfg(not(feature = "sqlite"))]
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
+#[cfg(feature = "sqlite")]
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// Picks the version of a query written for the database the bot was built for,
+/// like `sql!(postgres: "...", sqlite: "...")`.
+#[cfg(not(feature = "sqlite"))]
+macro_rules! sql {
+    (postgres: $postgres:expr, sqlite: $sqlite:expr $(,)?) => {
+        $postgres
+    };
+}
+
+#[cfg(feature = "sqlite")]
+macro_rules! sql {
+    (postgres: $postgres:expr, sqlite: $sqlite:expr $(,)?) => {
+        $sqlite
+    };
+}
+
+pub(crate) use sql;
+
+/// Encodes a list to be bound as a single parameter:
+/// an array on Postgres, or a JSON array on SQLite (which is read with `json_each`).
+#[cfg(not(feature = "sqlite"))]
+pub fn list<T>(values: &[T]) -> &[T] {
+    values
+}
+
+#[cfg(feature = "sqlite")]
+pub fn list<T: serde::Serialize>(values: &[T]) -> String {
+    serde_json::to_string(values).expect("lists of numbers and strings can be serialized")
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+pub mod tests {
+    use super::*;
+
+    /// Connects to a new in-memory database with every migration applied.
+    /// It only lives as long as its one connection, so the pool never closes it.
+    pub async fn memory_pool() -> DbPool {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        MIGRATOR.run(&pool).await.unwrap();
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn migrations_create_the_escalators() {
+        let pool = memory_pool().await;
+
+        let (count,) = sqlx::query_as::<_, (i64,)>(
+            "
+            SELECT COUNT(*)
+            FROM escalators
+            WHERE current_status = 'open'
+            ",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(count, 14);
+    }
+
+    #[tokio::test]
+    async fn lists_are_bound_as_json() {
+        let pool = memory_pool().await;
+
+        let rows = sqlx::query_as::<_, (i16, Option<String>)>(
+            "
+            SELECT n.value, e.value
+            FROM json_each($1) n
+            INNER JOIN json_each($2) e
+                ON n.key = e.key
+            ",
+        )
+        .bind(list(&[4_i16, 6]))
+        .bind(list(&[None, Some("timed out")]))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(rows, [(4, None), (6, Some(String::from("timed out")))]);
+    }
+}
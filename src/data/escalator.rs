@@ -1,16 +1,22 @@
 use std::fmt::Display;
 
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
 use super::status::Status;
 
-#[derive(sqlx::FromRow, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(sqlx::FromRow, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Escalator {
     #[sqlx(flatten)]
     pub floors: EscalatorFloors,
     #[sqlx(rename = "current_status")]
     pub status: Status,
+    /// When the status last changed, if it's ever been reported.
+    #[sqlx(default)]
+    pub status_changed_at: Option<DateTime<Utc>>,
 }
 
-#[derive(sqlx::FromRow, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(sqlx::FromRow, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct EscalatorFloors {
     #[sqlx(rename = "floor_start", try_from = "i16")]
     pub start: u8,
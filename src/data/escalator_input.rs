@@ -1,3 +1,7 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use crate::prelude::*;
+
 #[derive(Debug, Clone, Copy)]
 pub enum EscalatorInput {
     All,            // "all"
@@ -20,31 +24,64 @@ pub fn parse_floor(ch: char) -> Result<u8, InputError> {
 }
 
 impl EscalatorInput {
-    pub fn message_noun(&self) -> String {
-        match self {
-            Self::All => String::from("`ALL` escalators"),
-            Self::Pair(a, b) => {
-                let lower = a.min(b);
-                let upper = a.max(b);
-                format!("the `{lower}-{upper}` and `{upper}-{lower}` escalators")
+    pub fn is_singular(&self) -> bool {
+        matches!(self, Self::Direct(..))
+    }
+
+    /// The escalators this input refers to, or `None` if it refers to all of them.
+    pub fn floors(&self) -> Option<smallvec::SmallVec<[EscalatorFloors; 2]>> {
+        match *self {
+            Self::All => None,
+            Self::Pair(a, b) => Some(smallvec::smallvec![
+                EscalatorFloors::new(a, b),
+                EscalatorFloors::new(b, a),
+            ]),
+            Self::Direct(start, end) => Some(smallvec::smallvec![EscalatorFloors::new(start, end)]),
+        }
+    }
+}
+
+impl FromStr for EscalatorInput {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (Some(start), Some('-'), Some(end), None) => {
+                Ok(Self::Direct(parse_floor(start)?, parse_floor(end)?))
             }
-            Self::Direct(lower, upper) => format!("the `{lower}-{upper}` escalator"),
+            (Some(a), Some('/'), Some(b), None) => Ok(Self::Pair(parse_floor(a)?, parse_floor(b)?)),
+            _ => Err(InputError::UnknownFormat),
         }
     }
+}
 
-    pub fn short_noun(&self) -> String {
+/// Writes the input the way it's parsed.
+impl Display for EscalatorInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::All => String::from("ALL escalators"),
-            Self::Pair(a, b) => {
-                let lower = a.min(b);
-                let upper = a.max(b);
-                format!("{lower}-{upper} and {upper}-{lower}")
-            }
-            Self::Direct(lower, upper) => format!("{lower}-{upper}"),
+            Self::All => write!(f, "all"),
+            Self::Pair(a, b) => write!(f, "{a}/{b}"),
+            Self::Direct(start, end) => write!(f, "{start}-{end}"),
         }
     }
+}
 
-    pub fn is_singular(&self) -> bool {
-        matches!(self, Self::Direct(..))
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "Escalators must be in the `#-#` or `#/#` format"),
+            Self::InvalidFloor(floor) => write!(f, "`{floor}` is not a valid floor"),
+            Self::InvalidEscalator(start, end) => {
+                write!(f, "`{start}-{end}` is not a valid escalator")
+            }
+        }
     }
 }
+
+impl Error for InputError {}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+
+use serde::Serialize;
+
+use crate::prelude::*;
+
+use super::{status::Status, store::Store};
+
+#[derive(sqlx::FromRow, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusChange {
+    #[sqlx(flatten)]
+    pub floors: EscalatorFloors,
+    pub status: Status,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A status change, as it's returned by `GET /reports`.
+#[derive(sqlx::FromRow, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    #[sqlx(flatten)]
+    pub floors: EscalatorFloors,
+    pub status: Status,
+    /// Whether the change was reported by a user, rather than made by the bot itself.
+    /// Who reported it is left out, since the API is public.
+    pub user_reported: bool,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Loads the uptime of every escalator between `start` and `end`, most reliable first.
+pub async fn load_uptimes(
+    store: &dyn Store,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<(EscalatorFloors, f64)>, sqlx::Error> {
+    let escalators = store.escalators().await?;
+    let before = store.last_changes(start).await?;
+    let changes = store.changes(start, end).await?;
+
+    let mut uptimes = uptimes(&escalators, &before, &changes, start, end);
+    uptimes.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    Ok(uptimes)
+}
+
+/// Counts how many times an escalator went out of service,
+/// grouped by the weekday (starting on Monday) and hour in the given time zone.
+///
+/// Only changes from `OPEN` count, so an outage that goes from `DOWN` to `BLOCKED` is counted once.
+/// `before` should contain the last change of each escalator prior to `changes`,
+/// and escalators without one are assumed to have started `OPEN`.
+pub fn outages_by_hour<Tz: TimeZone>(
+    before: &[StatusChange],
+    changes: &[StatusChange],
+    tz: &Tz,
+) -> [[u32; 24]; 7] {
+    let mut outages = [[0; 24]; 7];
+
+    let mut statuses = before
+        .iter()
+        .map(|change| (change.floors, change.status))
+        .collect::<HashMap<_, _>>();
+
+    for change in changes {
+        let previous = statuses.insert(change.floors, change.status);
+
+        if change.status == Status::Open || previous.is_some_and(|status| status != Status::Open) {
+            continue;
+        }
+
+        let local = change.changed_at.with_timezone(tz);
+        let weekday = local.weekday().num_days_from_monday() as usize;
+
+        outages[weekday][local.hour() as usize] += 1;
+    }
+
+    outages
+}
+
+/// Calculates the fraction of time each escalator was `OPEN` between `start` and `end`.
+///
+/// `before` should contain the last change of each escalator prior to `start`,
+/// and `changes` every change between `start` and `end` in chronological order.
+/// Escalators without any changes are assumed to have had their current status the whole time,
+/// and escalators without a change prior to `start` are assumed to have started `OPEN`.
+pub fn uptimes(
+    escalators: &[Escalator],
+    before: &[StatusChange],
+    changes: &[StatusChange],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<(EscalatorFloors, f64)> {
+    let total = (end - start).num_seconds().max(1) as f64;
+
+    let initial = before
+        .iter()
+        .map(|change| (change.floors, change.status))
+        .collect::<HashMap<_, _>>();
+
+    escalators
+        .iter()
+        .map(|escalator| {
+            let floors = escalator.floors;
+
+            let mut changes = changes
+                .iter()
+                .filter(|change| change.floors == floors)
+                .peekable();
+
+            let mut status = match initial.get(&floors) {
+                Some(&status) => status,
+                None if changes.peek().is_some() => Status::Open,
+                None => escalator.status,
+            };
+
+            let mut since = start;
+            let mut open = 0;
+
+            for change in changes {
+                if status == Status::Open {
+                    open += (change.changed_at - since).num_seconds();
+                }
+
+                status = change.status;
+                since = change.changed_at;
+            }
+
+            if status == Status::Open {
+                open += (end - since).num_seconds();
+            }
+
+            (floors, open as f64 / total)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escalator(start: u8, end: u8, status: Status) -> Escalator {
+        Escalator {
+            floors: EscalatorFloors::new(start, end),
+            status,
+            status_changed_at: None,
+        }
+    }
+
+    fn change(start: u8, end: u8, status: Status, hour: u32) -> StatusChange {
+        StatusChange {
+            floors: EscalatorFloors::new(start, end),
+            status,
+            changed_at: Utc.with_ymd_and_hms(2026, 1, 1, hour, 0, 0).unwrap(),
+        }
+    }
+
+    fn window() -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn unchanged_escalators_keep_current_status() {
+        let (start, end) = window();
+        let escalators = [escalator(2, 3, Status::Open), escalator(3, 2, Status::Down)];
+
+        let uptimes = uptimes(&escalators, &[], &[], start, end);
+
+        assert_eq!(uptimes[0].1, 1.0);
+        assert_eq!(uptimes[1].1, 0.0);
+    }
+
+    #[test]
+    fn changes_split_the_window() {
+        let (start, end) = window();
+        let escalators = [escalator(2, 3, Status::Open)];
+        let changes = [
+            change(2, 3, Status::Down, 2),
+            change(2, 3, Status::Open, 4),
+            change(2, 3, Status::Blocked, 9),
+        ];
+
+        let uptimes = uptimes(&escalators, &[], &changes, start, end);
+
+        assert_eq!(uptimes[0].1, 0.7);
+    }
+
+    #[test]
+    fn prior_change_sets_initial_status() {
+        let (start, end) = window();
+        let escalators = [escalator(2, 3, Status::Open)];
+        let before = [StatusChange {
+            changed_at: start - chrono::Duration::hours(1),
+            ..change(2, 3, Status::Down, 0)
+        }];
+        let changes = [change(2, 3, Status::Open, 5)];
+
+        let uptimes = uptimes(&escalators, &before, &changes, start, end);
+
+        assert_eq!(uptimes[0].1, 0.5);
+    }
+
+    #[test]
+    fn outages_are_bucketed_by_local_time() {
+        // 2026-01-01 is a Thursday, and New York is 5 hours behind UTC in January
+        let changes = [
+            change(2, 3, Status::Down, 2),
+            change(2, 3, Status::Open, 4),
+            change(3, 2, Status::Blocked, 15),
+        ];
+
+        let outages = outages_by_hour(&[], &changes, &chrono_tz::America::New_York);
+
+        assert_eq!(outages[2][21], 1);
+        assert_eq!(outages[3][10], 1);
+        assert_eq!(outages.iter().flatten().sum::<u32>(), 2);
+    }
+
+    #[test]
+    fn outages_are_only_counted_when_they_start() {
+        let before = [StatusChange {
+            changed_at: Utc.with_ymd_and_hms(2025, 12, 31, 0, 0, 0).unwrap(),
+            ..change(3, 2, Status::Down, 0)
+        }];
+        let changes = [
+            change(2, 3, Status::Down, 2),
+            change(2, 3, Status::Blocked, 3),
+            change(2, 3, Status::Down, 4),
+            // already out of service before the changes
+            change(3, 2, Status::Blocked, 5),
+            change(2, 3, Status::Open, 6),
+            change(2, 3, Status::Down, 7),
+        ];
+
+        let outages = outages_by_hour(&before, &changes, &Utc);
+
+        assert_eq!(outages[3][2], 1);
+        assert_eq!(outages[3][7], 1);
+        assert_eq!(outages.iter().flatten().sum::<u32>(), 2);
+    }
+}
@@ -1,22 +1,26 @@
 pub mod channels;
+pub mod db;
 pub mod escalator;
 pub mod escalator_input;
+pub mod history;
 pub mod report;
+pub mod settings;
 pub mod status;
+pub mod store;
 
 use std::sync::Arc;
 use tokio::sync::broadcast;
 
 #[derive(Clone)]
 pub struct Data {
-    pub pool: sqlx::PgPool,
+    pub store: Arc<dyn store::Store>,
     channels: Arc<parking_lot::RwLock<channels::AnyChannels>>,
 }
 
 impl Data {
-    pub fn new(pool: sqlx::PgPool) -> Self {
+    pub fn new(store: Arc<dyn store::Store>) -> Self {
         Self {
-            pool,
+            store,
             channels: Arc::new(parking_lot::RwLock::new(channels::AnyChannels::new())),
         }
     }
@@ -0,0 +1,415 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, TimeZone, Timelike};
+
+use crate::{data::db::DbRow, locale::Locale, prelude::*};
+
+/// The most reports an announcement can be set to display.
+pub const MAX_REPORTS_DISPLAYED: usize = 20;
+
+/// Everything a guild can configure with `/config`, with the defaults filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuildSettings {
+    /// How long to pool reports for after the first one is received.
+    pub delay: Duration,
+    pub max_reports_displayed: usize,
+    /// Whether or not to crosspost announcements sent in News channels.
+    pub crosspost: bool,
+    /// Whether or not to include the gist of the current statuses.
+    pub include_gist: bool,
+    pub mode: AnnouncementMode,
+    /// Whether or not to post a summary every weekday morning.
+    pub daily_summary: bool,
+    /// Whether or not to post the weekly reliability summary.
+    pub weekly_summary: bool,
+    /// The language announcements, summaries and menus are made in.
+    pub locale: Locale,
+    /// The hour (in New York) reports open at.
+    pub reports_open_hour: u32,
+    /// The hour (in New York) reports close at.
+    pub reports_close_hour: u32,
+    /// Whether or not reports are locked during weekends.
+    pub lock_weekends: bool,
+    /// Whether or not menus have a diagram of the building attached.
+    pub show_diagram: bool,
+    /// Whether or not menus show how long ago each status changed.
+    pub show_ages: bool,
+    /// The role allowed to use every staff command.
+    pub admin_role: Option<serenity::RoleId>,
+    /// The role allowed to manage the guild's menus.
+    pub moderator_role: Option<serenity::RoleId>,
+}
+
+/// What a member is allowed to manage in a guild, where admins can do everything moderators can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    Moderator,
+    Admin,
+}
+
+#[derive(sqlx::Type, poise::ChoiceParameter, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "announcement_mode", rename_all = "lowercase")]
+pub enum AnnouncementMode {
+    /// Post a new message for every announcement.
+    Post,
+    /// Keep editing a single pinned message with the latest statuses.
+    Live,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_secs(2 * 60),
+            max_reports_displayed: 8,
+            crosspost: true,
+            include_gist: true,
+            mode: AnnouncementMode::Post,
+            daily_summary: false,
+            weekly_summary: false,
+            locale: Locale::default(),
+            reports_open_hour: 7,
+            reports_close_hour: 19,
+            lock_weekends: true,
+            show_diagram: true,
+            show_ages: true,
+            admin_role: None,
+            moderator_role: None,
+        }
+    }
+}
+
+impl GuildSettings {
+    /// Checks whether or not reports can be made at the given time.
+    pub fn reports_open<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> bool {
+        let is_weekend = now.weekday().num_days_from_monday() >= 5;
+        let hour = now.hour();
+
+        !(self.lock_weekends && is_weekend)
+            && self.reports_open_hour <= hour
+            && hour < self.reports_close_hour
+    }
+
+    /// Finds the access a member has from their permissions and roles.
+    /// Anyone who can manage the guild is an admin, even if the guild has no admin role.
+    pub fn access(
+        &self,
+        permissions: Option<serenity::Permissions>,
+        roles: &[serenity::RoleId],
+    ) -> Option<Access> {
+        let has_role = |role: Option<serenity::RoleId>| role.is_some_and(|id| roles.contains(&id));
+
+        let manages_guild =
+            permissions.is_some_and(|perms| perms.administrator() || perms.manage_guild());
+
+        if manages_guild || has_role(self.admin_role) {
+            Some(Access::Admin)
+        } else if has_role(self.moderator_role) {
+            Some(Access::Moderator)
+        } else {
+            None
+        }
+    }
+}
+
+/// Missing (NULL) columns fall back to the default settings,
+/// so this can be used with a LEFT OUTER JOIN on `guild_settings`.
+impl<'r> sqlx::FromRow<'r, DbRow> for GuildSettings {
+    fn from_row(row: &'r DbRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+
+        let default = Self::default();
+
+        let delay = row
+            .try_get::<Option<i32>, _>("announce_delay_secs")?
+            .map(|secs| Duration::from_secs(secs.max(0) as u64))
+            .unwrap_or(default.delay);
+
+        // at least one report must be displayed
+        let max_reports_displayed = row
+            .try_get::<Option<i16>, _>("max_reports_displayed")?
+            .map(|max| max.max(1) as usize)
+            .unwrap_or(default.max_reports_displayed);
+
+        let crosspost = row
+            .try_get::<Option<bool>, _>("crosspost")?
+            .unwrap_or(default.crosspost);
+
+        let include_gist = row
+            .try_get::<Option<bool>, _>("include_gist")?
+            .unwrap_or(default.include_gist);
+
+        let mode = row
+            .try_get::<Option<AnnouncementMode>, _>("announcement_mode")?
+            .unwrap_or(default.mode);
+
+        let daily_summary = row
+            .try_get::<Option<bool>, _>("daily_summary")?
+            .unwrap_or(default.daily_summary);
+
+        let weekly_summary = row
+            .try_get::<Option<bool>, _>("weekly_summary")?
+            .unwrap_or(default.weekly_summary);
+
+        let locale = row
+            .try_get::<Option<Locale>, _>("locale")?
+            .unwrap_or(default.locale);
+
+        let reports_open_hour = row
+            .try_get::<Option<i16>, _>("reports_open_hour")?
+            .map(|hour| hour.clamp(0, 24) as u32)
+            .unwrap_or(default.reports_open_hour);
+
+        let reports_close_hour = row
+            .try_get::<Option<i16>, _>("reports_close_hour")?
+            .map(|hour| hour.clamp(0, 24) as u32)
+            .unwrap_or(default.reports_close_hour);
+
+        let lock_weekends = row
+            .try_get::<Option<bool>, _>("lock_weekends")?
+            .unwrap_or(default.lock_weekends);
+
+        let show_diagram = row
+            .try_get::<Option<bool>, _>("show_diagram")?
+            .unwrap_or(default.show_diagram);
+
+        let show_ages = row
+            .try_get::<Option<bool>, _>("show_ages")?
+            .unwrap_or(default.show_ages);
+
+        let admin_role = row
+            .try_get::<Option<i64>, _>("admin_role_id")?
+            .map(|id| serenity::RoleId::new(id as u64));
+
+        let moderator_role = row
+            .try_get::<Option<i64>, _>("moderator_role_id")?
+            .map(|id| serenity::RoleId::new(id as u64));
+
+        Ok(Self {
+            delay,
+            max_reports_displayed,
+            crosspost,
+            include_gist,
+            mode,
+            daily_summary,
+            weekly_summary,
+            locale,
+            reports_open_hour,
+            reports_close_hour,
+            lock_weekends,
+            show_diagram,
+            show_ages,
+            admin_role,
+            moderator_role,
+        })
+    }
+}
+
+/// A single setting that can be changed with `/config set`.
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    #[name = "Announcement delay"]
+    AnnounceDelay,
+    #[name = "Max reports"]
+    MaxReports,
+    #[name = "Crosspost"]
+    Crosspost,
+    #[name = "Include gist"]
+    IncludeGist,
+    #[name = "Announcement mode"]
+    Mode,
+    #[name = "Daily summary"]
+    DailySummary,
+    #[name = "Weekly summary"]
+    WeeklySummary,
+    #[name = "Language"]
+    Language,
+    #[name = "Reports open hour"]
+    ReportsOpen,
+    #[name = "Reports close hour"]
+    ReportsClose,
+    #[name = "Lock weekends"]
+    LockWeekends,
+    #[name = "Menu diagram"]
+    MenuDiagram,
+    #[name = "Menu ages"]
+    MenuAges,
+    #[name = "Admin role"]
+    AdminRole,
+    #[name = "Moderator role"]
+    ModeratorRole,
+}
+
+/// A parsed value for a setting, typed like its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingValue {
+    BigInt(i64),
+    Int(i32),
+    SmallInt(i16),
+    Bool(bool),
+    Mode(AnnouncementMode),
+    Locale(Locale),
+}
+
+/// Why a value couldn't be parsed for a setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidValue {
+    /// The value must be a whole number in the range.
+    Number { min: i32, max: i32 },
+    /// The value must be a yes/no answer.
+    Bool,
+    /// The value must be one of the choices.
+    Choice(&'static [&'static str]),
+    /// The value must be a role mention or ID.
+    Role,
+}
+
+impl Setting {
+    /// The `guild_settings` column the setting is stored in.
+    pub(super) const fn column(self) -> &'static str {
+        match self {
+            Self::AnnounceDelay => "announce_delay_secs",
+            Self::MaxReports => "max_reports_displayed",
+            Self::Crosspost => "crosspost",
+            Self::IncludeGist => "include_gist",
+            Self::Mode => "announcement_mode",
+            Self::DailySummary => "daily_summary",
+            Self::WeeklySummary => "weekly_summary",
+            Self::Language => "locale",
+            Self::ReportsOpen => "reports_open_hour",
+            Self::ReportsClose => "reports_close_hour",
+            Self::LockWeekends => "lock_weekends",
+            Self::MenuDiagram => "show_diagram",
+            Self::MenuAges => "show_ages",
+            Self::AdminRole => "admin_role_id",
+            Self::ModeratorRole => "moderator_role_id",
+        }
+    }
+
+    /// Parses a value typed by a user for the setting.
+    pub fn parse(self, value: &str) -> Result<SettingValue, InvalidValue> {
+        let value = value.trim().to_lowercase();
+
+        let number = |min: i32, max: i32| {
+            value
+                .parse::<i32>()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or(InvalidValue::Number { min, max })
+        };
+
+        match self {
+            Self::AnnounceDelay => number(0, 3600).map(SettingValue::Int),
+            Self::MaxReports => {
+                number(1, MAX_REPORTS_DISPLAYED as i32).map(|n| SettingValue::SmallInt(n as i16))
+            }
+            Self::ReportsOpen => number(0, 23).map(|n| SettingValue::SmallInt(n as i16)),
+            Self::ReportsClose => number(1, 24).map(|n| SettingValue::SmallInt(n as i16)),
+            Self::Crosspost
+            | Self::IncludeGist
+            | Self::DailySummary
+            | Self::WeeklySummary
+            | Self::LockWeekends
+            | Self::MenuDiagram
+            | Self::MenuAges => match value.as_str() {
+                "true" | "yes" | "on" | "sí" | "si" => Ok(SettingValue::Bool(true)),
+                "false" | "no" | "off" => Ok(SettingValue::Bool(false)),
+                _ => Err(InvalidValue::Bool),
+            },
+            Self::Mode => match value.as_str() {
+                "post" => Ok(SettingValue::Mode(AnnouncementMode::Post)),
+                "live" => Ok(SettingValue::Mode(AnnouncementMode::Live)),
+                _ => Err(InvalidValue::Choice(&["post", "live"])),
+            },
+            Self::Language => Locale::ALL
+                .into_iter()
+                .find(|locale| locale.code() == value)
+                .map(SettingValue::Locale)
+                .ok_or(InvalidValue::Choice(&["en", "es"])),
+            Self::AdminRole | Self::ModeratorRole => value
+                .trim_start_matches("<@&")
+                .trim_end_matches('>')
+                .parse::<u64>()
+                .ok()
+                .filter(|&id| id != 0 && id <= i64::MAX as u64)
+                .map(|id| SettingValue::BigInt(id as i64))
+                .ok_or(InvalidValue::Role),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+
+    #[test]
+    fn reports_follow_the_schedule() {
+        let settings = GuildSettings::default();
+
+        // 2026-10-19 is a Monday
+        let morning = New_York.with_ymd_and_hms(2026, 10, 19, 6, 59, 0).unwrap();
+        let noon = New_York.with_ymd_and_hms(2026, 10, 19, 12, 0, 0).unwrap();
+        let evening = New_York.with_ymd_and_hms(2026, 10, 19, 19, 0, 0).unwrap();
+        let saturday = New_York.with_ymd_and_hms(2026, 10, 24, 12, 0, 0).unwrap();
+
+        assert!(!settings.reports_open(&morning));
+        assert!(settings.reports_open(&noon));
+        assert!(!settings.reports_open(&evening));
+        assert!(!settings.reports_open(&saturday));
+
+        let weekends = GuildSettings {
+            lock_weekends: false,
+            ..settings
+        };
+        assert!(weekends.reports_open(&saturday));
+    }
+
+    #[test]
+    fn values_are_parsed_for_their_setting() {
+        assert_eq!(
+            Setting::AnnounceDelay.parse("90"),
+            Ok(SettingValue::Int(90))
+        );
+        assert_eq!(
+            Setting::MaxReports.parse("0"),
+            Err(InvalidValue::Number { min: 1, max: 20 })
+        );
+        assert_eq!(
+            Setting::LockWeekends.parse(" No "),
+            Ok(SettingValue::Bool(false))
+        );
+        assert_eq!(
+            Setting::Mode.parse("live"),
+            Ok(SettingValue::Mode(AnnouncementMode::Live))
+        );
+        assert_eq!(
+            Setting::Language.parse("es"),
+            Ok(SettingValue::Locale(Locale::Es))
+        );
+        assert!(Setting::Language.parse("fr").is_err());
+        assert_eq!(
+            Setting::AdminRole.parse("<@&1234>"),
+            Ok(SettingValue::BigInt(1234))
+        );
+    }
+
+    #[test]
+    fn access_comes_from_roles_or_permissions() {
+        let admin = serenity::RoleId::new(1);
+        let moderator = serenity::RoleId::new(2);
+        let settings = GuildSettings {
+            admin_role: Some(admin),
+            moderator_role: Some(moderator),
+            ..Default::default()
+        };
+
+        let manage_guild = Some(serenity::Permissions::MANAGE_GUILD);
+        let none = Some(serenity::Permissions::empty());
+
+        assert_eq!(settings.access(manage_guild, &[]), Some(Access::Admin));
+        assert_eq!(settings.access(none, &[admin]), Some(Access::Admin));
+        assert_eq!(settings.access(none, &[moderator]), Some(Access::Moderator));
+        assert_eq!(settings.access(none, &[]), None);
+        assert_eq!(GuildSettings::default().access(none, &[]), None);
+    }
+}
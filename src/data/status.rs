@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[sqlx(type_name = "escalator_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum Status {
     Open,
     Down,
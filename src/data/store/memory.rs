@@ -0,0 +1,985 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use parking_lot::Mutex;
+use smallvec::SmallVec;
+
+use crate::{
+    data::{
+        escalator_input::EscalatorInput,
+        history::{Report, StatusChange},
+        report::UserReport,
+        settings::{GuildSettings, Setting, SettingValue},
+        status::Status,
+    },
+    locale::Locale,
+    prelude::*,
+};
+
+use super::{
+    AlertStore, AnnouncementChannel, ChannelStore, Delivery, DeliveryAttempt, EscalatorStore,
+    HistoryStore, Menu, MenuHealth, MenuStore, OutboxEvent, OutboxStore, SettingsStore,
+    UptimeStore, Webhook, WebhookHealth, WebhookStore,
+};
+
+/// Stores everything in memory, starting with the same escalators as the database,
+/// so commands and tasks can be tested without one.
+pub struct MemoryStore {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    escalators: Vec<Escalator>,
+    /// Every status change, in the order they were made.
+    status_changes: Vec<Report>,
+    alerts: BTreeSet<(u64, u8, u8)>,
+    floor_alerts: BTreeSet<(u64, u8)>,
+    user_locales: HashMap<u64, Locale>,
+    announcement_channels: BTreeMap<i64, i64>,
+    guild_settings: HashMap<i64, GuildSettings>,
+    live_messages: HashMap<i64, (i64, serenity::MessageId)>,
+    posted_summaries: HashMap<i64, DateTime<Utc>>,
+    menus: Vec<MenuEntry>,
+    webhooks: Vec<WebhookEntry>,
+    next_webhook_id: i32,
+    last_seen: Option<DateTime<Utc>>,
+    outbox: Vec<OutboxEvent>,
+    next_event_id: i64,
+    outbox_cursors: HashMap<String, i64>,
+}
+
+struct MenuEntry {
+    menu: Menu,
+    missing_permissions: bool,
+    last_error: Option<String>,
+    scope: Vec<EscalatorFloors>,
+}
+
+struct WebhookEntry {
+    guild_id: i64,
+    webhook: Webhook,
+    scope: Vec<EscalatorFloors>,
+    deliveries: Vec<Delivery>,
+}
+
+/// The escalators the migrations create, in one direction.
+const ESCALATORS: [(u8, u8); 7] = [(2, 3), (2, 4), (3, 5), (4, 6), (5, 7), (6, 8), (7, 9)];
+
+/// Escalators are ordered by floor, with both directions next to each other.
+fn sort_key(floors: &EscalatorFloors) -> (u8, u8) {
+    (floors.start + floors.end, floors.start)
+}
+
+fn sorted(mut escalators: Vec<EscalatorFloors>) -> Vec<EscalatorFloors> {
+    escalators.sort_by_key(sort_key);
+    escalators
+}
+
+/// Changes a single setting, or resets it to its default if there's no value,
+/// the same way its column is read from the database.
+fn apply(settings: &mut GuildSettings, setting: Setting, value: Option<SettingValue>) {
+    let default = GuildSettings::default();
+
+    let number = match value {
+        Some(SettingValue::BigInt(n)) => Some(n),
+        Some(SettingValue::Int(n)) => Some(n as i64),
+        Some(SettingValue::SmallInt(n)) => Some(n as i64),
+        _ => None,
+    };
+    let flag = match value {
+        Some(SettingValue::Bool(flag)) => Some(flag),
+        _ => None,
+    };
+
+    match setting {
+        Setting::AnnounceDelay => {
+            settings.delay = number.map_or(default.delay, |secs| Duration::from_secs(secs as u64));
+        }
+        Setting::MaxReports => {
+            settings.max_reports_displayed =
+                number.map_or(default.max_reports_displayed, |max| max as usize);
+        }
+        Setting::Crosspost => settings.crosspost = flag.unwrap_or(default.crosspost),
+        Setting::IncludeGist => settings.include_gist = flag.unwrap_or(default.include_gist),
+        Setting::Mode => {
+            settings.mode = match value {
+                Some(SettingValue::Mode(mode)) => mode,
+                _ => default.mode,
+            };
+        }
+        Setting::DailySummary => settings.daily_summary = flag.unwrap_or(default.daily_summary),
+        Setting::WeeklySummary => settings.weekly_summary = flag.unwrap_or(default.weekly_summary),
+        Setting::Language => {
+            settings.locale = match value {
+                Some(SettingValue::Locale(locale)) => locale,
+                _ => default.locale,
+            };
+        }
+        Setting::ReportsOpen => {
+            settings.reports_open_hour =
+                number.map_or(default.reports_open_hour, |hour| hour as u32);
+        }
+        Setting::ReportsClose => {
+            settings.reports_close_hour =
+                number.map_or(default.reports_close_hour, |hour| hour as u32);
+        }
+        Setting::LockWeekends => settings.lock_weekends = flag.unwrap_or(default.lock_weekends),
+        Setting::MenuDiagram => settings.show_diagram = flag.unwrap_or(default.show_diagram),
+        Setting::MenuAges => settings.show_ages = flag.unwrap_or(default.show_ages),
+        Setting::AdminRole => {
+            settings.admin_role = number.map(|id| serenity::RoleId::new(id as u64));
+        }
+        Setting::ModeratorRole => {
+            settings.moderator_role = number.map(|id| serenity::RoleId::new(id as u64));
+        }
+    }
+}
+
+/// Whether or not something limited to the scope is about any of the escalators.
+fn in_scope(scope: &[EscalatorFloors], escalators: &[EscalatorFloors]) -> bool {
+    scope.is_empty() || scope.iter().any(|floors| escalators.contains(floors))
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        let escalators = ESCALATORS
+            .into_iter()
+            .flat_map(|(start, end)| [(start, end), (end, start)])
+            .map(|(start, end)| Escalator {
+                floors: EscalatorFloors::new(start, end),
+                status: Status::Open,
+                status_changed_at: None,
+            })
+            .sorted_by_key(|escalator| sort_key(&escalator.floors))
+            .collect();
+
+        Self {
+            state: Mutex::new(State {
+                escalators,
+                next_webhook_id: 1,
+                next_event_id: 1,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+impl MemoryStore {
+    /// Changes a guild's settings, which are otherwise the defaults.
+    pub fn set_guild_settings(&self, guild_id: serenity::GuildId, settings: GuildSettings) {
+        self.state
+            .lock()
+            .guild_settings
+            .insert(guild_id.get() as i64, settings);
+    }
+}
+
+#[async_trait]
+impl EscalatorStore for MemoryStore {
+    async fn escalators(&self) -> Result<Vec<Escalator>, sqlx::Error> {
+        Ok(self.state.lock().escalators.clone())
+    }
+
+    async fn commit_report(
+        &self,
+        reporter: Option<serenity::UserId>,
+        escalators: EscalatorInput,
+        status: Status,
+    ) -> Result<SmallVec<[EscalatorFloors; 2]>, sqlx::Error> {
+        let reported = |floors: &EscalatorFloors| match escalators {
+            EscalatorInput::All => true,
+            EscalatorInput::Direct(start, end) => *floors == EscalatorFloors::new(start, end),
+            EscalatorInput::Pair(start, end) => {
+                *floors == EscalatorFloors::new(start, end)
+                    || *floors == EscalatorFloors::new(end, start)
+            }
+        };
+
+        let changed_at = Utc::now();
+
+        let mut state = self.state.lock();
+
+        let affected: SmallVec<[EscalatorFloors; 2]> = state
+            .escalators
+            .iter_mut()
+            .filter(|escalator| escalator.status != status && reported(&escalator.floors))
+            .map(|escalator| {
+                escalator.status = status;
+                escalator.status_changed_at = Some(changed_at);
+                escalator.floors
+            })
+            .collect();
+
+        let user_reported = reporter.is_some();
+        state
+            .status_changes
+            .extend(affected.iter().map(|&floors| Report {
+                floors,
+                status,
+                user_reported,
+                changed_at,
+            }));
+
+        let id = state.next_event_id;
+        state.next_event_id += 1;
+        state.outbox.push(OutboxEvent {
+            id,
+            report: UserReport {
+                reporter,
+                escalators,
+                affected_escalators: affected.clone(),
+                new_status: status,
+            },
+            created_at: changed_at,
+        });
+
+        Ok(affected)
+    }
+}
+
+#[async_trait]
+impl HistoryStore for MemoryStore {
+    async fn changes(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<StatusChange>, sqlx::Error> {
+        let changes = self
+            .state
+            .lock()
+            .status_changes
+            .iter()
+            .filter(|report| (start..end).contains(&report.changed_at))
+            .map(|report| StatusChange {
+                floors: report.floors,
+                status: report.status,
+                changed_at: report.changed_at,
+            })
+            .collect();
+
+        Ok(changes)
+    }
+
+    async fn last_changes(&self, time: DateTime<Utc>) -> Result<Vec<StatusChange>, sqlx::Error> {
+        let mut last = HashMap::new();
+
+        for report in &self.state.lock().status_changes {
+            if report.changed_at < time {
+                last.insert(
+                    report.floors,
+                    StatusChange {
+                        floors: report.floors,
+                        status: report.status,
+                        changed_at: report.changed_at,
+                    },
+                );
+            }
+        }
+
+        Ok(last.into_values().collect())
+    }
+
+    async fn reports(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<Report>, sqlx::Error> {
+        let reports = self
+            .state
+            .lock()
+            .status_changes
+            .iter()
+            .rev()
+            .filter(|report| report.changed_at > since)
+            .take(limit.max(0) as usize)
+            .copied()
+            .collect();
+
+        Ok(reports)
+    }
+}
+
+#[async_trait]
+impl AlertStore for MemoryStore {
+    async fn watchlist(
+        &self,
+        user_id: serenity::UserId,
+    ) -> Result<Vec<(EscalatorFloors, bool)>, sqlx::Error> {
+        let state = self.state.lock();
+        let user_id = user_id.get();
+
+        let watchlist = state
+            .escalators
+            .iter()
+            .map(|escalator| {
+                let EscalatorFloors { start, end } = escalator.floors;
+                (
+                    escalator.floors,
+                    state.alerts.contains(&(user_id, start, end)),
+                )
+            })
+            .collect();
+
+        Ok(watchlist)
+    }
+
+    async fn watched_escalators(
+        &self,
+        user_id: serenity::UserId,
+    ) -> Result<Vec<Escalator>, sqlx::Error> {
+        let state = self.state.lock();
+        let user_id = user_id.get();
+
+        let watched = state
+            .escalators
+            .iter()
+            .filter(|escalator| {
+                let EscalatorFloors { start, end } = escalator.floors;
+                state.alerts.contains(&(user_id, start, end))
+            })
+            .map(|escalator| Escalator {
+                status_changed_at: None,
+                ..*escalator
+            })
+            .sorted_by_key(|escalator| (escalator.floors.start, escalator.floors.end))
+            .collect();
+
+        Ok(watched)
+    }
+
+    async fn set_watchlist(
+        &self,
+        user_id: serenity::UserId,
+        escalators: &[EscalatorFloors],
+    ) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock();
+        let user_id = user_id.get();
+
+        state.alerts.retain(|&(id, ..)| id != user_id);
+        state.alerts.extend(
+            escalators
+                .iter()
+                .map(|floors| (user_id, floors.start, floors.end)),
+        );
+
+        Ok(())
+    }
+
+    async fn watched_floors(&self, user_id: serenity::UserId) -> Result<Vec<u8>, sqlx::Error> {
+        let user_id = user_id.get();
+
+        let floors = self
+            .state
+            .lock()
+            .floor_alerts
+            .iter()
+            .filter(|&&(id, _)| id == user_id)
+            .map(|&(_, floor)| floor)
+            .collect();
+
+        Ok(floors)
+    }
+
+    async fn toggle_floor(
+        &self,
+        user_id: serenity::UserId,
+        floor: u8,
+    ) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock();
+        let key = (user_id.get(), floor);
+
+        let watching = !state.floor_alerts.remove(&key);
+        if watching {
+            state.floor_alerts.insert(key);
+        }
+
+        Ok(watching)
+    }
+
+    async fn set_user_locale(
+        &self,
+        user_id: serenity::UserId,
+        locale: Locale,
+    ) -> Result<(), sqlx::Error> {
+        self.state.lock().user_locales.insert(user_id.get(), locale);
+
+        Ok(())
+    }
+
+    async fn watchers(
+        &self,
+        escalators: &[EscalatorFloors],
+    ) -> Result<Vec<(serenity::UserId, Option<Locale>)>, sqlx::Error> {
+        let state = self.state.lock();
+
+        let escalator_watchers = state
+            .alerts
+            .iter()
+            .filter(|&&(_, start, end)| escalators.contains(&EscalatorFloors::new(start, end)))
+            .map(|&(user_id, ..)| user_id);
+
+        let floor_watchers = state
+            .floor_alerts
+            .iter()
+            .filter(|&&(_, floor)| {
+                escalators
+                    .iter()
+                    .any(|floors| floors.start == floor || floors.end == floor)
+            })
+            .map(|&(user_id, _)| user_id);
+
+        let watchers = escalator_watchers
+            .chain(floor_watchers)
+            .unique()
+            .map(|user_id| {
+                (
+                    serenity::UserId::new(user_id),
+                    state.user_locales.get(&user_id).copied(),
+                )
+            })
+            .collect();
+
+        Ok(watchers)
+    }
+}
+
+#[async_trait]
+impl ChannelStore for MemoryStore {
+    async fn set_announcement_channel(
+        &self,
+        guild_id: serenity::GuildId,
+        channel_id: serenity::ChannelId,
+    ) -> Result<(), sqlx::Error> {
+        self.state
+            .lock()
+            .announcement_channels
+            .insert(guild_id.get() as i64, channel_id.get() as i64);
+
+        Ok(())
+    }
+
+    async fn remove_announcement_channel(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<(), sqlx::Error> {
+        self.state
+            .lock()
+            .announcement_channels
+            .remove(&(guild_id.get() as i64));
+
+        Ok(())
+    }
+
+    async fn announcement_channels(
+        &self,
+        guild_ids: Option<&[i64]>,
+    ) -> Result<Vec<AnnouncementChannel>, sqlx::Error> {
+        let state = self.state.lock();
+
+        let channels = state
+            .announcement_channels
+            .iter()
+            .filter(|(guild_id, _)| guild_ids.is_none_or(|ids| ids.contains(guild_id)))
+            .map(|(&guild_id, &channel_id)| AnnouncementChannel {
+                guild_id,
+                channel_id,
+                settings: state
+                    .guild_settings
+                    .get(&guild_id)
+                    .copied()
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(channels)
+    }
+
+    async fn live_message(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+    ) -> Result<Option<serenity::MessageId>, sqlx::Error> {
+        let message_id = self
+            .state
+            .lock()
+            .live_messages
+            .get(&guild_id)
+            .filter(|(channel, _)| *channel == channel_id)
+            .map(|&(_, message_id)| message_id);
+
+        Ok(message_id)
+    }
+
+    async fn set_live_message(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+        message_id: serenity::MessageId,
+    ) -> Result<(), sqlx::Error> {
+        self.state
+            .lock()
+            .live_messages
+            .insert(guild_id, (channel_id, message_id));
+
+        Ok(())
+    }
+
+    async fn posted_summaries(&self) -> Result<HashMap<i64, DateTime<Utc>>, sqlx::Error> {
+        Ok(self.state.lock().posted_summaries.clone())
+    }
+
+    async fn set_posted_summary(
+        &self,
+        guild_id: i64,
+        summary_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        self.state
+            .lock()
+            .posted_summaries
+            .insert(guild_id, summary_time);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SettingsStore for MemoryStore {
+    async fn guild_settings(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<GuildSettings, sqlx::Error> {
+        let settings = self
+            .state
+            .lock()
+            .guild_settings
+            .get(&(guild_id.get() as i64))
+            .copied();
+
+        Ok(settings.unwrap_or_default())
+    }
+
+    async fn guild_locale(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Option<Locale>, sqlx::Error> {
+        let locale = self
+            .state
+            .lock()
+            .guild_settings
+            .get(&(guild_id.get() as i64))
+            .map(|settings| settings.locale);
+
+        Ok(locale)
+    }
+
+    async fn save_setting(
+        &self,
+        guild_id: serenity::GuildId,
+        setting: Setting,
+        value: SettingValue,
+    ) -> Result<GuildSettings, sqlx::Error> {
+        let mut state = self.state.lock();
+        let settings = state
+            .guild_settings
+            .entry(guild_id.get() as i64)
+            .or_default();
+
+        apply(settings, setting, Some(value));
+
+        Ok(*settings)
+    }
+
+    async fn reset_settings(
+        &self,
+        guild_id: serenity::GuildId,
+        setting: Option<Setting>,
+    ) -> Result<GuildSettings, sqlx::Error> {
+        let mut state = self.state.lock();
+
+        let Some(setting) = setting else {
+            state.guild_settings.remove(&(guild_id.get() as i64));
+            return Ok(GuildSettings::default());
+        };
+
+        let Some(settings) = state.guild_settings.get_mut(&(guild_id.get() as i64)) else {
+            return Ok(GuildSettings::default());
+        };
+
+        apply(settings, setting, None);
+
+        Ok(*settings)
+    }
+}
+
+#[async_trait]
+impl MenuStore for MemoryStore {
+    async fn menus(&self, affected: Option<&[EscalatorFloors]>) -> Result<Vec<Menu>, sqlx::Error> {
+        let menus = self
+            .state
+            .lock()
+            .menus
+            .iter()
+            .filter(|entry| affected.is_none_or(|affected| in_scope(&entry.scope, affected)))
+            .map(|entry| entry.menu)
+            .collect();
+
+        Ok(menus)
+    }
+
+    async fn guild_menus(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Vec<MenuHealth>, sqlx::Error> {
+        let menus = self
+            .state
+            .lock()
+            .menus
+            .iter()
+            .filter(|entry| entry.menu.guild_id == guild_id.get() as i64)
+            .map(|entry| MenuHealth {
+                channel_id: entry.menu.channel_id,
+                message_id: entry.menu.message_id,
+                missing_permissions: entry.missing_permissions,
+                last_error: entry.last_error.clone(),
+                escalators: sorted(entry.scope.clone()),
+            })
+            .sorted_by_key(|menu| menu.message_id)
+            .collect();
+
+        Ok(menus)
+    }
+
+    async fn menu_escalators(
+        &self,
+        message_id: serenity::MessageId,
+    ) -> Result<Vec<Escalator>, sqlx::Error> {
+        let state = self.state.lock();
+
+        let scope = state
+            .menus
+            .iter()
+            .find(|entry| entry.menu.message_id == message_id.get() as i64)
+            .map(|entry| entry.scope.as_slice())
+            .unwrap_or_default();
+
+        let escalators = state
+            .escalators
+            .iter()
+            .filter(|escalator| scope.is_empty() || scope.contains(&escalator.floors))
+            .copied()
+            .collect();
+
+        Ok(escalators)
+    }
+
+    async fn is_scoped(&self, message_id: serenity::MessageId) -> Result<bool, sqlx::Error> {
+        let scoped = self.state.lock().menus.iter().any(|entry| {
+            entry.menu.message_id == message_id.get() as i64 && !entry.scope.is_empty()
+        });
+
+        Ok(scoped)
+    }
+
+    async fn add_menu(&self, menu: Menu, scope: &[EscalatorFloors]) -> Result<(), sqlx::Error> {
+        self.state.lock().menus.push(MenuEntry {
+            menu,
+            missing_permissions: false,
+            last_error: None,
+            scope: scope.to_vec(),
+        });
+
+        Ok(())
+    }
+
+    async fn remove_menus(
+        &self,
+        guild_id: serenity::GuildId,
+        channel_id: serenity::ChannelId,
+        message_id: Option<serenity::MessageId>,
+    ) -> Result<Vec<Menu>, sqlx::Error> {
+        let removed = |menu: &Menu| {
+            menu.guild_id == guild_id.get() as i64
+                && match message_id {
+                    Some(message_id) => menu.message_id == message_id.get() as i64,
+                    None => menu.channel_id == channel_id.get() as i64,
+                }
+        };
+
+        let mut state = self.state.lock();
+        let (removed, kept) = std::mem::take(&mut state.menus)
+            .into_iter()
+            .partition::<Vec<_>, _>(|entry| removed(&entry.menu));
+        state.menus = kept;
+
+        Ok(removed.into_iter().map(|entry| entry.menu).collect())
+    }
+
+    async fn remove_menu(&self, message_id: serenity::MessageId) -> Result<(), sqlx::Error> {
+        self.state
+            .lock()
+            .menus
+            .retain(|entry| entry.menu.message_id != message_id.get() as i64);
+
+        Ok(())
+    }
+
+    async fn replace_menu(
+        &self,
+        message_id: serenity::MessageId,
+        new_message_id: serenity::MessageId,
+    ) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock();
+
+        if let Some(entry) = state
+            .menus
+            .iter_mut()
+            .find(|entry| entry.menu.message_id == message_id.get() as i64)
+        {
+            entry.menu.message_id = new_message_id.get() as i64;
+            entry.missing_permissions = false;
+            entry.last_error = None;
+        }
+
+        Ok(())
+    }
+
+    async fn menu_synced(&self, message_id: serenity::MessageId) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock();
+
+        if let Some(entry) = state
+            .menus
+            .iter_mut()
+            .find(|entry| entry.menu.message_id == message_id.get() as i64)
+        {
+            entry.missing_permissions = false;
+            entry.last_error = None;
+        }
+
+        Ok(())
+    }
+
+    async fn menu_sync_failed(
+        &self,
+        message_id: serenity::MessageId,
+        error: &str,
+        missing_permissions: bool,
+    ) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock();
+
+        if let Some(entry) = state
+            .menus
+            .iter_mut()
+            .find(|entry| entry.menu.message_id == message_id.get() as i64)
+        {
+            entry.missing_permissions |= missing_permissions;
+            entry.last_error = Some(error.to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WebhookStore for MemoryStore {
+    async fn add_webhook(
+        &self,
+        guild_id: serenity::GuildId,
+        url: &str,
+        secret: Option<&str>,
+        scope: &[EscalatorFloors],
+        max: usize,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let mut state = self.state.lock();
+        let guild_id = guild_id.get() as i64;
+
+        let count = state
+            .webhooks
+            .iter()
+            .filter(|entry| entry.guild_id == guild_id)
+            .count();
+
+        if count >= max {
+            return Ok(None);
+        }
+
+        let webhook_id = state.next_webhook_id;
+        state.next_webhook_id += 1;
+
+        state.webhooks.push(WebhookEntry {
+            guild_id,
+            webhook: Webhook {
+                webhook_id,
+                url: url.to_owned(),
+                secret: secret.map(str::to_owned),
+            },
+            scope: scope.to_vec(),
+            deliveries: vec![],
+        });
+
+        Ok(Some(webhook_id))
+    }
+
+    async fn guild_webhooks(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Vec<WebhookHealth>, sqlx::Error> {
+        let webhooks = self
+            .state
+            .lock()
+            .webhooks
+            .iter()
+            .filter(|entry| entry.guild_id == guild_id.get() as i64)
+            .map(|entry| WebhookHealth {
+                webhook_id: entry.webhook.webhook_id,
+                url: entry.webhook.url.clone(),
+                signed: entry.webhook.secret.is_some(),
+                escalators: sorted(entry.scope.clone()),
+                last_delivery: entry.deliveries.last().cloned(),
+            })
+            .collect();
+
+        Ok(webhooks)
+    }
+
+    async fn remove_webhook(
+        &self,
+        guild_id: serenity::GuildId,
+        webhook_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock();
+        let count = state.webhooks.len();
+
+        state.webhooks.retain(|entry| {
+            entry.guild_id != guild_id.get() as i64 || entry.webhook.webhook_id != webhook_id
+        });
+
+        Ok(state.webhooks.len() < count)
+    }
+
+    async fn webhooks(&self, affected: &[EscalatorFloors]) -> Result<Vec<Webhook>, sqlx::Error> {
+        let webhooks = self
+            .state
+            .lock()
+            .webhooks
+            .iter()
+            .filter(|entry| in_scope(&entry.scope, affected))
+            .map(|entry| entry.webhook.clone())
+            .collect();
+
+        Ok(webhooks)
+    }
+
+    async fn record_deliveries(
+        &self,
+        webhook_id: i32,
+        attempts: &[DeliveryAttempt],
+    ) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock();
+        let attempted_at = Utc::now();
+
+        if let Some(entry) = state
+            .webhooks
+            .iter_mut()
+            .find(|entry| entry.webhook.webhook_id == webhook_id)
+        {
+            entry
+                .deliveries
+                .extend(attempts.iter().map(|attempt| Delivery {
+                    attempt: attempt.clone(),
+                    attempted_at,
+                }));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UptimeStore for MemoryStore {
+    async fn last_seen(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        Ok(self.state.lock().last_seen)
+    }
+
+    async fn set_last_seen(&self, at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        self.state.lock().last_seen = Some(at);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutboxStore for MemoryStore {
+    async fn events_after(
+        &self,
+        event_id: i64,
+        limit: usize,
+    ) -> Result<Vec<OutboxEvent>, sqlx::Error> {
+        let events = self
+            .state
+            .lock()
+            .outbox
+            .iter()
+            .filter(|event| event.id > event_id)
+            .take(limit)
+            .cloned()
+            .collect();
+
+        Ok(events)
+    }
+
+    async fn events_until(
+        &self,
+        event_id: i64,
+        limit: usize,
+    ) -> Result<Vec<OutboxEvent>, sqlx::Error> {
+        let state = self.state.lock();
+        let until = state.outbox.partition_point(|event| event.id <= event_id);
+
+        Ok(state.outbox[until.saturating_sub(limit)..until].to_vec())
+    }
+
+    async fn cursor(&self, consumer: &str) -> Result<i64, sqlx::Error> {
+        let mut state = self.state.lock();
+        let latest = state.outbox.last().map_or(0, |event| event.id);
+
+        let cursor = *state
+            .outbox_cursors
+            .entry(consumer.to_owned())
+            .or_insert(latest);
+
+        Ok(cursor)
+    }
+
+    async fn set_cursor(&self, consumer: &str, event_id: i64) -> Result<(), sqlx::Error> {
+        self.state
+            .lock()
+            .outbox_cursors
+            .insert(consumer.to_owned(), event_id);
+        Ok(())
+    }
+
+    async fn prune_events(&self, consumers: &[&str], keep: usize) -> Result<u64, sqlx::Error> {
+        let mut state = self.state.lock();
+
+        let handled = state
+            .outbox_cursors
+            .iter()
+            .filter(|(consumer, _)| consumers.contains(&consumer.as_str()))
+            .map(|(_, &cursor)| cursor)
+            .min();
+
+        let kept = state.outbox.len().saturating_sub(keep.max(1));
+        let (Some(handled), Some(kept)) = (handled, state.outbox.get(kept).map(|event| event.id))
+        else {
+            return Ok(0);
+        };
+
+        let before = state.outbox.len();
+        state
+            .outbox
+            .retain(|event| event.id > handled || event.id >= kept);
+
+        Ok((before - state.outbox.len()) as u64)
+    }
+}
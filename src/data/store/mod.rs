@@ -0,0 +1,855 @@
+//! Everything the commands, tasks and API store, behind traits so they can be run
+//! against the database ([`SqlStore`]) or an in-memory store in tests.
+
+#[cfg(test)]
+mod memory;
+mod sql;
+
+#[cfg(test)]
+pub use memory::MemoryStore;
+pub use sql::SqlStore;
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use smallvec::SmallVec;
+
+use crate::{locale::Locale, prelude::*};
+
+use super::{
+    escalator_input::EscalatorInput,
+    history::{Report, StatusChange},
+    report::UserReport,
+    settings::{GuildSettings, Setting, SettingValue},
+    status::Status,
+};
+
+/// Every kind of storage, which is what [`Data`] holds.
+pub trait Store:
+    EscalatorStore
+    + HistoryStore
+    + AlertStore
+    + ChannelStore
+    + SettingsStore
+    + MenuStore
+    + WebhookStore
+    + UptimeStore
+    + OutboxStore
+{
+}
+
+impl<T> Store for T where
+    T: EscalatorStore
+        + HistoryStore
+        + AlertStore
+        + ChannelStore
+        + SettingsStore
+        + MenuStore
+        + WebhookStore
+        + UptimeStore
+        + OutboxStore
+{
+}
+
+#[derive(sqlx::FromRow, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Menu {
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub message_id: i64,
+}
+
+/// A menu, and whether or not it was able to sync the last time it was tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuHealth {
+    pub channel_id: i64,
+    pub message_id: i64,
+    pub missing_permissions: bool,
+    pub last_error: Option<String>,
+    /// The escalators the menu displays, which is every escalator if it's empty.
+    pub escalators: Vec<EscalatorFloors>,
+}
+
+/// A guild's announcement channel, along with the guild's settings.
+#[derive(sqlx::FromRow, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnnouncementChannel {
+    pub guild_id: i64,
+    pub channel_id: i64,
+    #[sqlx(flatten)]
+    pub settings: GuildSettings,
+}
+
+/// A webhook that's sent reports.
+#[derive(sqlx::FromRow, Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    pub webhook_id: i32,
+    pub url: String,
+    pub secret: Option<String>,
+}
+
+/// A webhook as it's listed to its guild, with how its last delivery went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookHealth {
+    pub webhook_id: i32,
+    pub url: String,
+    pub signed: bool,
+    /// The escalators the webhook is sent reports for, which is every escalator if it's empty.
+    pub escalators: Vec<EscalatorFloors>,
+    pub last_delivery: Option<Delivery>,
+}
+
+/// The outcome of a single attempt to deliver a report to a webhook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryAttempt {
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// A recorded attempt to deliver a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub attempt: DeliveryAttempt,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// A report in the outbox, which is handled by each of its consumers in order.
+#[derive(Debug, Clone)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub report: UserReport,
+    pub created_at: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait EscalatorStore: Send + Sync {
+    /// Loads every escalator, ordered by floor.
+    async fn escalators(&self) -> Result<Vec<Escalator>, sqlx::Error>;
+
+    /// Changes the status of the reported escalators and logs every change at once,
+    /// adding the report to the outbox and returning the escalators whose status changed.
+    async fn commit_report(
+        &self,
+        reporter: Option<serenity::UserId>,
+        escalators: EscalatorInput,
+        status: Status,
+    ) -> Result<SmallVec<[EscalatorFloors; 2]>, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait HistoryStore: Send + Sync {
+    /// Loads every status change between `start` and `end` in chronological order.
+    async fn changes(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<StatusChange>, sqlx::Error>;
+
+    /// Loads the latest change to each escalator before `time`.
+    async fn last_changes(&self, time: DateTime<Utc>) -> Result<Vec<StatusChange>, sqlx::Error>;
+
+    /// Loads up to `limit` of the most recent status changes made after `since`, newest first.
+    async fn reports(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<Report>, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait AlertStore: Send + Sync {
+    /// Loads every escalator, ordered by floor, and whether or not the user is watching it.
+    async fn watchlist(
+        &self,
+        user_id: serenity::UserId,
+    ) -> Result<Vec<(EscalatorFloors, bool)>, sqlx::Error>;
+
+    /// Loads the escalators the user is watching.
+    async fn watched_escalators(
+        &self,
+        user_id: serenity::UserId,
+    ) -> Result<Vec<Escalator>, sqlx::Error>;
+
+    /// Replaces the escalators the user is watching.
+    async fn set_watchlist(
+        &self,
+        user_id: serenity::UserId,
+        escalators: &[EscalatorFloors],
+    ) -> Result<(), sqlx::Error>;
+
+    /// Loads the floors the user is watching, in order.
+    async fn watched_floors(&self, user_id: serenity::UserId) -> Result<Vec<u8>, sqlx::Error>;
+
+    /// Subscribes or unsubscribes a user from a floor,
+    /// returning whether or not the user is now watching the floor.
+    async fn toggle_floor(&self, user_id: serenity::UserId, floor: u8)
+        -> Result<bool, sqlx::Error>;
+
+    /// Remembers a user's locale, so messages sent outside of interactions can use it.
+    async fn set_user_locale(
+        &self,
+        user_id: serenity::UserId,
+        locale: Locale,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Loads the users watching any of the escalators, or a floor they start or end at,
+    /// along with their locale.
+    async fn watchers(
+        &self,
+        escalators: &[EscalatorFloors],
+    ) -> Result<Vec<(serenity::UserId, Option<Locale>)>, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait ChannelStore: Send + Sync {
+    async fn set_announcement_channel(
+        &self,
+        guild_id: serenity::GuildId,
+        channel_id: serenity::ChannelId,
+    ) -> Result<(), sqlx::Error>;
+
+    async fn remove_announcement_channel(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Loads the announcement channels of the given guilds, or of every guild.
+    async fn announcement_channels(
+        &self,
+        guild_ids: Option<&[i64]>,
+    ) -> Result<Vec<AnnouncementChannel>, sqlx::Error>;
+
+    /// Loads the guild's live message, if it's been sent in the channel.
+    async fn live_message(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+    ) -> Result<Option<serenity::MessageId>, sqlx::Error>;
+
+    /// Replaces the guild's live message.
+    async fn set_live_message(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+        message_id: serenity::MessageId,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Loads the time of the last summary posted in each guild.
+    async fn posted_summaries(&self) -> Result<HashMap<i64, DateTime<Utc>>, sqlx::Error>;
+
+    /// Records that the summary for the given time was posted in the guild.
+    async fn set_posted_summary(
+        &self,
+        guild_id: i64,
+        summary_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait SettingsStore: Send + Sync {
+    /// Loads a guild's settings, which are the defaults if it hasn't changed any.
+    async fn guild_settings(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<GuildSettings, sqlx::Error>;
+
+    /// Loads the locale a guild has chosen, if any.
+    async fn guild_locale(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Option<Locale>, sqlx::Error>;
+
+    /// Changes a single setting of a guild, returning the updated settings.
+    async fn save_setting(
+        &self,
+        guild_id: serenity::GuildId,
+        setting: Setting,
+        value: SettingValue,
+    ) -> Result<GuildSettings, sqlx::Error>;
+
+    /// Resets a single setting of a guild, or every setting if none is given,
+    /// returning the updated settings.
+    async fn reset_settings(
+        &self,
+        guild_id: serenity::GuildId,
+        setting: Option<Setting>,
+    ) -> Result<GuildSettings, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait MenuStore: Send + Sync {
+    /// Loads every menu, or only the menus displaying any of the given escalators.
+    async fn menus(&self, affected: Option<&[EscalatorFloors]>) -> Result<Vec<Menu>, sqlx::Error>;
+
+    /// Loads the menus in a guild, and whether or not they're able to sync.
+    async fn guild_menus(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Vec<MenuHealth>, sqlx::Error>;
+
+    /// Loads the escalators displayed by a menu,
+    /// which is every escalator if the menu isn't limited to any.
+    async fn menu_escalators(
+        &self,
+        message_id: serenity::MessageId,
+    ) -> Result<Vec<Escalator>, sqlx::Error>;
+
+    /// Checks whether or not a menu only displays some of the escalators.
+    async fn is_scoped(&self, message_id: serenity::MessageId) -> Result<bool, sqlx::Error>;
+
+    /// Adds a menu displaying the given escalators, or every escalator if there aren't any.
+    async fn add_menu(&self, menu: Menu, scope: &[EscalatorFloors]) -> Result<(), sqlx::Error>;
+
+    /// Removes a guild's menu, or every menu in the channel if no message is given,
+    /// returning the removed menus.
+    async fn remove_menus(
+        &self,
+        guild_id: serenity::GuildId,
+        channel_id: serenity::ChannelId,
+        message_id: Option<serenity::MessageId>,
+    ) -> Result<Vec<Menu>, sqlx::Error>;
+
+    async fn remove_menu(&self, message_id: serenity::MessageId) -> Result<(), sqlx::Error>;
+
+    /// Moves a menu to a new message, which is healthy since it was just sent.
+    async fn replace_menu(
+        &self,
+        message_id: serenity::MessageId,
+        new_message_id: serenity::MessageId,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Clears any errors from the menu's last syncs.
+    async fn menu_synced(&self, message_id: serenity::MessageId) -> Result<(), sqlx::Error>;
+
+    /// Records why the menu couldn't be synced,
+    /// and whether or not it was because of missing permissions.
+    async fn menu_sync_failed(
+        &self,
+        message_id: serenity::MessageId,
+        error: &str,
+        missing_permissions: bool,
+    ) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait WebhookStore: Send + Sync {
+    /// Adds a webhook sent reports for the given escalators, or every escalator if there aren't any,
+    /// returning its ID. Nothing is added if the guild already has `max` webhooks.
+    async fn add_webhook(
+        &self,
+        guild_id: serenity::GuildId,
+        url: &str,
+        secret: Option<&str>,
+        scope: &[EscalatorFloors],
+        max: usize,
+    ) -> Result<Option<i32>, sqlx::Error>;
+
+    /// Loads the webhooks in a guild, and how their last delivery went.
+    async fn guild_webhooks(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Vec<WebhookHealth>, sqlx::Error>;
+
+    /// Removes a guild's webhook, returning whether or not it existed.
+    async fn remove_webhook(
+        &self,
+        guild_id: serenity::GuildId,
+        webhook_id: i32,
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Loads the webhooks sent reports for every escalator, or for any of the affected escalators.
+    async fn webhooks(&self, affected: &[EscalatorFloors]) -> Result<Vec<Webhook>, sqlx::Error>;
+
+    /// Records every attempt to deliver a report to a webhook,
+    /// unless it was removed while it was being delivered to.
+    async fn record_deliveries(
+        &self,
+        webhook_id: i32,
+        attempts: &[DeliveryAttempt],
+    ) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait UptimeStore: Send + Sync {
+    /// Loads when the bot was last known to be running, if it's ever run before.
+    async fn last_seen(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    /// Records that the bot was running at the given time.
+    async fn set_last_seen(&self, at: DateTime<Utc>) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    /// Loads up to `limit` of the reports after the given one, oldest first.
+    async fn events_after(
+        &self,
+        event_id: i64,
+        limit: usize,
+    ) -> Result<Vec<OutboxEvent>, sqlx::Error>;
+
+    /// Loads up to `limit` of the reports up to and including the given one, oldest first.
+    async fn events_until(
+        &self,
+        event_id: i64,
+        limit: usize,
+    ) -> Result<Vec<OutboxEvent>, sqlx::Error>;
+
+    /// Loads the last report the consumer handled.
+    /// A new consumer starts after the latest report, rather than handling every past report.
+    async fn cursor(&self, consumer: &str) -> Result<i64, sqlx::Error>;
+
+    /// Records that the consumer handled every report up to the given one.
+    async fn set_cursor(&self, consumer: &str, event_id: i64) -> Result<(), sqlx::Error>;
+
+    /// Removes the reports every one of the given consumers has handled, except the latest `keep`,
+    /// returning how many were removed. Cursors of any other consumer (eg. one that was removed)
+    /// are ignored. The latest report is always kept, so its ID is never given to another report.
+    async fn prune_events(&self, consumers: &[&str], keep: usize) -> Result<u64, sqlx::Error>;
+}
+
+/// The same checks are run against every store, so they can't drift apart.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: serenity::UserId = serenity::UserId::new(1);
+    const GUILD: serenity::GuildId = serenity::GuildId::new(2);
+    const CHANNEL: serenity::ChannelId = serenity::ChannelId::new(3);
+
+    fn floors(start: u8, end: u8) -> EscalatorFloors {
+        EscalatorFloors::new(start, end)
+    }
+
+    async fn reports_change_each_status_once(store: &dyn Store) {
+        let report = || store.commit_report(Some(USER), EscalatorInput::Pair(4, 6), Status::Down);
+
+        let affected = report().await.unwrap();
+        assert_eq!(&affected[..], [floors(4, 6), floors(6, 4)]);
+
+        // reporting the same status again doesn't change anything
+        assert!(report().await.unwrap().is_empty());
+
+        let escalators = store.escalators().await.unwrap();
+        assert_eq!(escalators.len(), 14);
+        assert_eq!(escalators[0].floors, floors(2, 3));
+
+        let down = escalators
+            .iter()
+            .filter(|escalator| escalator.status == Status::Down)
+            .collect::<Vec<_>>();
+        assert_eq!(down.len(), 2);
+        assert!(down
+            .iter()
+            .all(|escalator| escalator.status_changed_at.is_some()));
+    }
+
+    async fn history_keeps_every_change(store: &dyn Store) {
+        let start = Utc::now();
+
+        store
+            .commit_report(Some(USER), EscalatorInput::Pair(4, 6), Status::Down)
+            .await
+            .unwrap();
+        store
+            .commit_report(None, EscalatorInput::Direct(4, 6), Status::Open)
+            .await
+            .unwrap();
+
+        let end = Utc::now() + chrono::TimeDelta::seconds(1);
+
+        let changes = store.changes(start, end).await.unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[2].floors, floors(4, 6));
+        assert_eq!(changes[2].status, Status::Open);
+        assert!(store.changes(end, end).await.unwrap().is_empty());
+
+        let mut last = store.last_changes(end).await.unwrap();
+        last.sort_by_key(|change| change.floors.start);
+        assert_eq!(last.len(), 2);
+        assert_eq!(last[0].status, Status::Open);
+        assert_eq!(last[1].status, Status::Down);
+        assert!(store.last_changes(start).await.unwrap().is_empty());
+
+        let reports = store.reports(start, 2).await.unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].status, Status::Open);
+        assert!(!reports[0].user_reported);
+        assert!(reports[1].user_reported);
+    }
+
+    async fn settings_fall_back_to_the_defaults(store: &dyn Store) {
+        assert_eq!(
+            store.guild_settings(GUILD).await.unwrap(),
+            GuildSettings::default()
+        );
+        assert_eq!(store.guild_locale(GUILD).await.unwrap(), None);
+
+        let settings = store
+            .save_setting(GUILD, Setting::Language, SettingValue::Locale(Locale::Es))
+            .await
+            .unwrap();
+        assert_eq!(settings.locale, Locale::Es);
+
+        let settings = store
+            .save_setting(GUILD, Setting::MaxReports, SettingValue::SmallInt(3))
+            .await
+            .unwrap();
+        assert_eq!(settings.max_reports_displayed, 3);
+        assert_eq!(settings.locale, Locale::Es);
+        assert_eq!(store.guild_settings(GUILD).await.unwrap(), settings);
+        assert_eq!(store.guild_locale(GUILD).await.unwrap(), Some(Locale::Es));
+
+        let settings = store
+            .reset_settings(GUILD, Some(Setting::MaxReports))
+            .await
+            .unwrap();
+        assert_eq!(
+            settings.max_reports_displayed,
+            GuildSettings::default().max_reports_displayed
+        );
+        assert_eq!(settings.locale, Locale::Es);
+
+        store.reset_settings(GUILD, None).await.unwrap();
+        assert_eq!(
+            store.guild_settings(GUILD).await.unwrap(),
+            GuildSettings::default()
+        );
+    }
+
+    async fn watchers_include_floors(store: &dyn Store) {
+        let other = serenity::UserId::new(4);
+
+        store
+            .set_watchlist(USER, &[floors(2, 3), floors(4, 6)])
+            .await
+            .unwrap();
+        store.set_watchlist(USER, &[floors(4, 6)]).await.unwrap();
+        store.set_user_locale(USER, Locale::Es).await.unwrap();
+
+        assert!(store.toggle_floor(other, 4).await.unwrap());
+        assert!(store.toggle_floor(other, 7).await.unwrap());
+        assert!(!store.toggle_floor(other, 7).await.unwrap());
+        assert_eq!(store.watched_floors(other).await.unwrap(), [4]);
+
+        let watched = store.watched_escalators(USER).await.unwrap();
+        assert_eq!(watched.len(), 1);
+        assert_eq!(watched[0].floors, floors(4, 6));
+
+        let watchlist = store.watchlist(USER).await.unwrap();
+        assert_eq!(
+            watchlist.iter().filter(|(_, watching)| *watching).count(),
+            1
+        );
+
+        let mut watchers = store.watchers(&[floors(4, 6)]).await.unwrap();
+        watchers.sort_by_key(|(user_id, _)| *user_id);
+        assert_eq!(watchers, [(USER, Some(Locale::Es)), (other, None)]);
+
+        assert!(store.watchers(&[floors(2, 3)]).await.unwrap().is_empty());
+    }
+
+    async fn scoped_menus_only_show_their_escalators(store: &dyn Store) {
+        let menu = |message_id| Menu {
+            guild_id: GUILD.get() as i64,
+            channel_id: CHANNEL.get() as i64,
+            message_id,
+        };
+        let scoped = serenity::MessageId::new(11);
+
+        store.add_menu(menu(10), &[]).await.unwrap();
+        store
+            .add_menu(menu(11), &[floors(4, 6), floors(2, 3)])
+            .await
+            .unwrap();
+
+        assert_eq!(store.menus(Some(&[floors(4, 6)])).await.unwrap().len(), 2);
+        assert_eq!(
+            store.menus(Some(&[floors(7, 9)])).await.unwrap(),
+            [menu(10)]
+        );
+        assert!(store.is_scoped(scoped).await.unwrap());
+        assert_eq!(store.menu_escalators(scoped).await.unwrap().len(), 2);
+
+        store
+            .menu_sync_failed(scoped, "Missing Access", true)
+            .await
+            .unwrap();
+        store
+            .menu_sync_failed(scoped, "timed out", false)
+            .await
+            .unwrap();
+
+        let menus = store.guild_menus(GUILD).await.unwrap();
+        assert_eq!(menus[1].escalators, [floors(2, 3), floors(4, 6)]);
+        assert!(menus[1].missing_permissions);
+        assert_eq!(menus[1].last_error.as_deref(), Some("timed out"));
+
+        let replaced = serenity::MessageId::new(12);
+        store.replace_menu(scoped, replaced).await.unwrap();
+        assert!(store.is_scoped(replaced).await.unwrap());
+        assert!(!store.guild_menus(GUILD).await.unwrap()[1].missing_permissions);
+
+        let removed = store.remove_menus(GUILD, CHANNEL, None).await.unwrap();
+        assert_eq!(removed.len(), 2);
+        assert!(store.menus(None).await.unwrap().is_empty());
+    }
+
+    async fn guilds_have_limited_webhooks(store: &dyn Store) {
+        let add = |scope: Vec<EscalatorFloors>| async move {
+            store
+                .add_webhook(GUILD, "https://example.com", None, &scope, 2)
+                .await
+                .unwrap()
+        };
+
+        let everything = add(vec![]).await.unwrap();
+        let scoped = add(vec![floors(4, 6)]).await.unwrap();
+        assert_eq!(add(vec![]).await, None);
+
+        assert_eq!(store.webhooks(&[floors(4, 6)]).await.unwrap().len(), 2);
+        assert_eq!(store.webhooks(&[floors(2, 3)]).await.unwrap().len(), 1);
+
+        let attempts = [
+            DeliveryAttempt {
+                status_code: Some(503),
+                error: None,
+            },
+            DeliveryAttempt {
+                status_code: None,
+                error: Some(String::from("timed out")),
+            },
+        ];
+        store.record_deliveries(scoped, &attempts).await.unwrap();
+
+        let webhooks = store.guild_webhooks(GUILD).await.unwrap();
+        assert_eq!(webhooks[0].last_delivery, None);
+        assert_eq!(webhooks[1].escalators, [floors(4, 6)]);
+        assert_eq!(
+            webhooks[1]
+                .last_delivery
+                .as_ref()
+                .map(|delivery| &delivery.attempt),
+            Some(&attempts[1])
+        );
+
+        assert!(store.remove_webhook(GUILD, everything).await.unwrap());
+        assert!(!store.remove_webhook(GUILD, everything).await.unwrap());
+    }
+
+    async fn channels_have_one_live_message(store: &dyn Store) {
+        store
+            .set_announcement_channel(GUILD, CHANNEL)
+            .await
+            .unwrap();
+
+        let channels = store.announcement_channels(None).await.unwrap();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].settings, GuildSettings::default());
+        assert!(store
+            .announcement_channels(Some(&[5]))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let (guild_id, channel_id) = (GUILD.get() as i64, CHANNEL.get() as i64);
+        let message_id = serenity::MessageId::new(10);
+        store
+            .set_live_message(guild_id, channel_id, message_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.live_message(guild_id, channel_id).await.unwrap(),
+            Some(message_id)
+        );
+        assert_eq!(store.live_message(guild_id, 5).await.unwrap(), None);
+
+        store.remove_announcement_channel(GUILD).await.unwrap();
+        assert!(store.announcement_channels(None).await.unwrap().is_empty());
+    }
+
+    async fn posted_summaries_replace_each_other(store: &dyn Store) {
+        assert!(store.posted_summaries().await.unwrap().is_empty());
+
+        let first = DateTime::from_timestamp(1_800_000_000, 0).unwrap();
+        let second = first + chrono::TimeDelta::days(1);
+
+        store.set_posted_summary(1, first).await.unwrap();
+        store.set_posted_summary(1, second).await.unwrap();
+        store.set_posted_summary(2, first).await.unwrap();
+
+        let posted = store.posted_summaries().await.unwrap();
+        assert_eq!(posted, HashMap::from([(1, second), (2, first)]));
+    }
+
+    async fn heartbeats_replace_each_other(store: &dyn Store) {
+        assert_eq!(store.last_seen().await.unwrap(), None);
+
+        let first = DateTime::from_timestamp(1_800_000_000, 0).unwrap();
+        let second = first + chrono::TimeDelta::minutes(1);
+
+        store.set_last_seen(first).await.unwrap();
+        store.set_last_seen(second).await.unwrap();
+        assert_eq!(store.last_seen().await.unwrap(), Some(second));
+    }
+
+    async fn outbox_keeps_reports_for_each_consumer(store: &dyn Store) {
+        store
+            .commit_report(Some(USER), EscalatorInput::Pair(4, 6), Status::Down)
+            .await
+            .unwrap();
+        // reports which don't change anything are still kept
+        store
+            .commit_report(None, EscalatorInput::Direct(4, 6), Status::Down)
+            .await
+            .unwrap();
+
+        let events = store.events_after(0, 10).await.unwrap();
+        assert_eq!(events.len(), 2);
+
+        let report = &events[0].report;
+        assert_eq!(report.reporter, Some(USER));
+        assert!(matches!(report.escalators, EscalatorInput::Pair(4, 6)));
+        assert_eq!(
+            &report.affected_escalators[..],
+            [floors(4, 6), floors(6, 4)]
+        );
+        assert_eq!(report.new_status, Status::Down);
+        assert!(events[1].report.affected_escalators.is_empty());
+
+        assert_eq!(store.events_after(0, 1).await.unwrap().len(), 1);
+        let remaining = store.events_after(events[0].id, 10).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, events[1].id);
+
+        store.set_cursor("alert", events[0].id).await.unwrap();
+        assert_eq!(store.cursor("alert").await.unwrap(), events[0].id);
+        store.set_cursor("alert", events[1].id).await.unwrap();
+        assert_eq!(store.cursor("alert").await.unwrap(), events[1].id);
+    }
+
+    async fn outbox_is_pruned_once_every_consumer_handles_it(store: &dyn Store) {
+        let report = || store.commit_report(None, EscalatorInput::All, Status::Down);
+
+        // a consumer without any reports to handle yet
+        assert_eq!(store.cursor("alert").await.unwrap(), 0);
+
+        for _ in 0..3 {
+            report().await.unwrap();
+        }
+        let events = store.events_after(0, 10).await.unwrap();
+
+        // a new consumer doesn't handle the reports made before it
+        assert_eq!(store.cursor("sync").await.unwrap(), events[2].id);
+
+        let consumers = ["alert", "sync"];
+        assert_eq!(store.prune_events(&consumers, 1).await.unwrap(), 0);
+        store.set_cursor("alert", events[0].id).await.unwrap();
+        assert_eq!(store.prune_events(&consumers, 1).await.unwrap(), 1);
+
+        // the latest report is kept even once it's been handled
+        store.set_cursor("alert", events[2].id).await.unwrap();
+        assert_eq!(store.prune_events(&consumers, 1).await.unwrap(), 1);
+
+        let remaining = store.events_after(0, 10).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, events[2].id);
+
+        report().await.unwrap();
+        let latest = store.events_after(events[2].id, 10).await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert!(latest[0].id > events[2].id);
+    }
+
+    async fn stale_cursors_dont_block_pruning(store: &dyn Store) {
+        // a consumer that's since been removed, which never handled anything
+        assert_eq!(store.cursor("removed").await.unwrap(), 0);
+
+        for _ in 0..3 {
+            store
+                .commit_report(None, EscalatorInput::All, Status::Down)
+                .await
+                .unwrap();
+        }
+        let events = store.events_after(0, 10).await.unwrap();
+
+        store.set_cursor("alert", events[2].id).await.unwrap();
+        assert_eq!(store.prune_events(&["alert"], 1).await.unwrap(), 2);
+        assert_eq!(store.events_after(0, 10).await.unwrap().len(), 1);
+
+        // nothing is removed if none of the consumers have a cursor
+        assert_eq!(store.prune_events(&["announce"], 1).await.unwrap(), 0);
+    }
+
+    async fn recent_reports_are_kept(store: &dyn Store) {
+        for _ in 0..4 {
+            store
+                .commit_report(None, EscalatorInput::All, Status::Down)
+                .await
+                .unwrap();
+        }
+        let events = store.events_after(0, 10).await.unwrap();
+
+        store.set_cursor("alert", events[3].id).await.unwrap();
+        assert_eq!(store.prune_events(&["alert"], 3).await.unwrap(), 1);
+
+        let recent = store.events_until(events[3].id, 10).await.unwrap();
+        let ids = recent.iter().map(|event| event.id).collect::<Vec<_>>();
+        assert_eq!(ids, [events[1].id, events[2].id, events[3].id]);
+
+        let recent = store.events_until(events[2].id, 1).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].id, events[2].id);
+        assert!(matches!(recent[0].report.escalators, EscalatorInput::All));
+    }
+
+    macro_rules! store_tests {
+        ($store:expr, $($test:ident),* $(,)?) => {
+            $(
+                #[tokio::test]
+                async fn $test() {
+                    super::$test(&$store).await;
+                }
+            )*
+        };
+    }
+
+    mod memory {
+        use super::*;
+
+        store_tests!(
+            MemoryStore::default(),
+            reports_change_each_status_once,
+            history_keeps_every_change,
+            settings_fall_back_to_the_defaults,
+            watchers_include_floors,
+            scoped_menus_only_show_their_escalators,
+            guilds_have_limited_webhooks,
+            channels_have_one_live_message,
+            heartbeats_replace_each_other,
+            posted_summaries_replace_each_other,
+            outbox_keeps_reports_for_each_consumer,
+            outbox_is_pruned_once_every_consumer_handles_it,
+            stale_cursors_dont_block_pruning,
+            recent_reports_are_kept,
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    mod sql {
+        use super::*;
+        use crate::data::db::tests::memory_pool;
+
+        store_tests!(
+            SqlStore::new(memory_pool().await),
+            reports_change_each_status_once,
+            history_keeps_every_change,
+            settings_fall_back_to_the_defaults,
+            watchers_include_floors,
+            scoped_menus_only_show_their_escalators,
+            guilds_have_limited_webhooks,
+            channels_have_one_live_message,
+            heartbeats_replace_each_other,
+            posted_summaries_replace_each_other,
+            outbox_keeps_reports_for_each_consumer,
+            outbox_is_pruned_once_every_consumer_handles_it,
+            stale_cursors_dont_block_pruning,
+            recent_reports_are_kept,
+        );
+    }
+}
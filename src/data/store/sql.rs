@@ -0,0 +1,1872 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use itertools::Itertools;
+use smallvec::SmallVec;
+
+use crate::{
+    data::{
+        db::{self, sql, DbConnection},
+        escalator_input::EscalatorInput,
+        history::{Report, StatusChange},
+        report::UserReport,
+        settings::{GuildSettings, Setting, SettingValue},
+        status::Status,
+    },
+    locale::Locale,
+    metrics,
+    prelude::*,
+};
+
+use super::{
+    AlertStore, AnnouncementChannel, ChannelStore, Delivery, DeliveryAttempt, EscalatorStore,
+    HistoryStore, Menu, MenuHealth, MenuStore, OutboxEvent, OutboxStore, SettingsStore,
+    UptimeStore, Webhook, WebhookHealth, WebhookStore,
+};
+
+/// Stores everything in the database, timing every query.
+pub struct SqlStore {
+    pool: DbPool,
+}
+
+impl SqlStore {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Splits escalators into their start and end floors, to be bound as two lists.
+fn split_floors(escalators: &[EscalatorFloors]) -> (Vec<i16>, Vec<i16>) {
+    escalators
+        .iter()
+        .map(|floors| (floors.start as i16, floors.end as i16))
+        .unzip()
+}
+
+#[async_trait]
+impl EscalatorStore for SqlStore {
+    async fn escalators(&self) -> Result<Vec<Escalator>, sqlx::Error> {
+        let query = sqlx::query_as::<_, Escalator>(
+            "
+            SELECT floor_start, floor_end, current_status, status_changed_at
+            FROM escalators
+            ORDER BY floor_start + floor_end,
+                floor_start
+            ",
+        )
+        .fetch_all(&self.pool);
+
+        metrics::time_query("escalators", query).await
+    }
+
+    async fn commit_report(
+        &self,
+        reporter: Option<serenity::UserId>,
+        escalators: EscalatorInput,
+        status: Status,
+    ) -> Result<SmallVec<[EscalatorFloors; 2]>, sqlx::Error> {
+        let commit = async {
+            // every change made by a report is logged at the same time
+            let changed_at = Utc::now();
+
+            let mut transaction = self.pool.begin().await?;
+
+            let affected = match escalators {
+                EscalatorInput::All => {
+                    report_all(&mut transaction, reporter, status, changed_at).await?
+                }
+                EscalatorInput::Direct(start, end) => {
+                    let floors = EscalatorFloors::new(start, end);
+
+                    if report_escalator(&mut transaction, reporter, floors, status, changed_at)
+                        .await?
+                    {
+                        smallvec::smallvec![floors]
+                    } else {
+                        smallvec::smallvec![]
+                    }
+                }
+                EscalatorInput::Pair(start, end) => {
+                    let mut affected = smallvec::smallvec![];
+                    for (start, end) in [(start, end), (end, start)] {
+                        let floors = EscalatorFloors::new(start, end);
+
+                        if report_escalator(&mut transaction, reporter, floors, status, changed_at)
+                            .await?
+                        {
+                            affected.push(floors);
+                        }
+                    }
+
+                    affected
+                }
+            };
+
+            add_event(
+                &mut transaction,
+                reporter,
+                escalators,
+                status,
+                &affected,
+                changed_at,
+            )
+            .await?;
+
+            transaction.commit().await?;
+
+            Ok(affected)
+        };
+
+        metrics::time_query("commit_report", commit).await
+    }
+}
+
+/// Updates every escalator's status and logs the changes,
+/// returning all affected escalators.
+async fn report_all(
+    conn: &mut DbConnection,
+    reporter: Option<serenity::UserId>,
+    status: Status,
+    changed_at: DateTime<Utc>,
+) -> Result<SmallVec<[EscalatorFloors; 2]>, sqlx::Error> {
+    let updated = sqlx::query_as::<_, EscalatorFloors>(
+        "
+        UPDATE escalators
+        SET current_status = $1,
+            status_changed_at = $2
+        WHERE current_status <> $1
+        RETURNING floor_start, floor_end
+        ",
+    )
+    .bind(status)
+    .bind(changed_at)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for &floors in &updated {
+        log_change(&mut *conn, reporter, floors, status, changed_at).await?;
+    }
+
+    Ok(updated.into_iter().collect())
+}
+
+/// Attempts to update a specific escalator's status and log the change,
+/// returning whether or not the escalator exists and if it changed the status.
+async fn report_escalator(
+    conn: &mut DbConnection,
+    reporter: Option<serenity::UserId>,
+    floors: EscalatorFloors,
+    status: Status,
+    changed_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query(
+        "
+        UPDATE escalators
+        SET current_status = $1,
+            status_changed_at = $2
+        WHERE current_status <> $1
+        AND floor_start = $3
+        AND floor_end = $4
+        RETURNING 1
+        ",
+    )
+    .bind(status)
+    .bind(changed_at)
+    .bind(floors.start as i16)
+    .bind(floors.end as i16)
+    .fetch_optional(&mut *conn)
+    .await?
+    .is_some();
+
+    if updated {
+        log_change(conn, reporter, floors, status, changed_at).await?;
+    }
+
+    Ok(updated)
+}
+
+/// Logs a change to an escalator's status, for the history and the API.
+async fn log_change(
+    conn: &mut DbConnection,
+    reporter: Option<serenity::UserId>,
+    floors: EscalatorFloors,
+    status: Status,
+    changed_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "
+        INSERT INTO status_changes (floor_start, floor_end, status, reporter_id, changed_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ",
+    )
+    .bind(floors.start as i16)
+    .bind(floors.end as i16)
+    .bind(status)
+    .bind(reporter.map(|id| id.get() as i64))
+    .bind(changed_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Adds a report to the outbox, for each of its consumers to handle.
+async fn add_event(
+    conn: &mut DbConnection,
+    reporter: Option<serenity::UserId>,
+    escalators: EscalatorInput,
+    status: Status,
+    affected: &[EscalatorFloors],
+    created_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    // reports are added one at a time until their transaction commits, so their IDs are in the
+    // order they're committed in, and a consumer never moves past one that isn't visible yet.
+    // SQLite only allows one write transaction at a time, so its IDs are already in that order
+    #[cfg(not(feature = "sqlite"))]
+    sqlx::query("LOCK TABLE outbox IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *conn)
+        .await?;
+
+    let (event_id,) = sqlx::query_as::<_, (i64,)>(
+        "
+        INSERT INTO outbox (reporter_id, escalators, status, created_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        ",
+    )
+    .bind(reporter.map(|id| id.get() as i64))
+    .bind(escalators.to_string())
+    .bind(status)
+    .bind(created_at)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let (starts, ends) = split_floors(affected);
+
+    sqlx::query(sql!(
+        postgres: "
+        INSERT INTO outbox_escalators (event_id, position, floor_start, floor_end)
+        SELECT $1, position, floor_start, floor_end
+        FROM UNNEST($2::smallint[], $3::smallint[]) WITH ORDINALITY
+            AS s (floor_start, floor_end, position)
+        ",
+        sqlite: "
+        INSERT INTO outbox_escalators (event_id, position, floor_start, floor_end)
+        SELECT $1, starts.key, starts.value, ends.value
+        FROM json_each($2) starts
+        INNER JOIN json_each($3) ends
+            ON starts.key = ends.key
+        ",
+    ))
+    .bind(event_id)
+    .bind(db::list(&starts))
+    .bind(db::list(&ends))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl HistoryStore for SqlStore {
+    async fn changes(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<StatusChange>, sqlx::Error> {
+        let query = sqlx::query_as::<_, StatusChange>(
+            "
+            SELECT floor_start, floor_end, status, changed_at
+            FROM status_changes
+            WHERE changed_at >= $1
+            AND changed_at < $2
+            ORDER BY changed_at
+            ",
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool);
+
+        metrics::time_query("history_changes", query).await
+    }
+
+    async fn last_changes(&self, time: DateTime<Utc>) -> Result<Vec<StatusChange>, sqlx::Error> {
+        let query = sqlx::query_as::<_, StatusChange>(sql!(
+            postgres: "
+            SELECT DISTINCT ON (floor_start, floor_end)
+                floor_start, floor_end, status, changed_at
+            FROM status_changes
+            WHERE changed_at < $1
+            ORDER BY floor_start, floor_end, changed_at DESC
+            ",
+            // the other columns come from the row with the MAX
+            sqlite: "
+            SELECT floor_start, floor_end, status, MAX(changed_at) AS changed_at
+            FROM status_changes
+            WHERE changed_at < $1
+            GROUP BY floor_start, floor_end
+            ",
+        ))
+        .bind(time)
+        .fetch_all(&self.pool);
+
+        metrics::time_query("history_last_changes", query).await
+    }
+
+    async fn reports(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<Report>, sqlx::Error> {
+        let query = sqlx::query_as::<_, Report>(
+            "
+            SELECT floor_start,
+                floor_end,
+                status,
+                reporter_id IS NOT NULL AS user_reported,
+                changed_at
+            FROM status_changes
+            WHERE changed_at > $1
+            ORDER BY changed_at DESC
+            LIMIT $2
+            ",
+        )
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool);
+
+        metrics::time_query("history_reports", query).await
+    }
+}
+
+#[async_trait]
+impl AlertStore for SqlStore {
+    async fn watchlist(
+        &self,
+        user_id: serenity::UserId,
+    ) -> Result<Vec<(EscalatorFloors, bool)>, sqlx::Error> {
+        #[derive(sqlx::FromRow)]
+        struct WatchlistEntry {
+            #[sqlx(flatten)]
+            floors: EscalatorFloors,
+            watching: bool,
+        }
+
+        sqlx::query_as::<_, WatchlistEntry>(
+            "
+            SELECT e.floor_start, e.floor_end, (a.user_id IS NOT NULL) as watching
+            FROM escalators e
+            LEFT OUTER JOIN alerts a
+                ON e.floor_start = a.floor_start
+                AND e.floor_end = a.floor_end
+                AND a.user_id = $1
+            ORDER BY e.floor_start + e.floor_end, e.floor_start
+            ",
+        )
+        .bind(user_id.get() as i64)
+        .fetch(&self.pool)
+        .map_ok(|entry| (entry.floors, entry.watching))
+        .try_collect()
+        .await
+    }
+
+    async fn watched_escalators(
+        &self,
+        user_id: serenity::UserId,
+    ) -> Result<Vec<Escalator>, sqlx::Error> {
+        sqlx::query_as::<_, Escalator>(
+            "
+            SELECT e.floor_start, e.floor_end, e.current_status
+            FROM alerts a
+            INNER JOIN escalators e
+                ON a.floor_start = e.floor_start
+                AND a.floor_end = e.floor_end
+            WHERE a.user_id = $1
+            ORDER BY a.floor_start, a.floor_end
+            ",
+        )
+        .bind(user_id.get() as i64)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn set_watchlist(
+        &self,
+        user_id: serenity::UserId,
+        escalators: &[EscalatorFloors],
+    ) -> Result<(), sqlx::Error> {
+        let (starts, ends) = split_floors(escalators);
+
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query(sql!(
+            postgres: "
+            DELETE FROM alerts a
+            WHERE user_id = $1
+            AND NOT EXISTS (
+                SELECT FROM UNNEST($2::smallint[], $3::smallint[])
+                    AS w (floor_start, floor_end)
+                WHERE a.floor_start = w.floor_start
+                AND a.floor_end = w.floor_end
+            )
+            ",
+            sqlite: "
+            WITH w AS (
+                SELECT starts.value AS floor_start, ends.value AS floor_end
+                FROM json_each($2) starts
+                INNER JOIN json_each($3) ends
+                    ON starts.key = ends.key
+            )
+            DELETE FROM alerts
+            WHERE user_id = $1
+            AND NOT EXISTS (
+                SELECT 1
+                FROM w
+                WHERE alerts.floor_start = w.floor_start
+                AND alerts.floor_end = w.floor_end
+            )
+            ",
+        ))
+        .bind(user_id.get() as i64)
+        .bind(db::list(&starts))
+        .bind(db::list(&ends))
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query(sql!(
+            postgres: "
+            INSERT INTO alerts (user_id, floor_start, floor_end)
+            SELECT $1 as user_id, w.floor_start, w.floor_end
+            FROM UNNEST($2::smallint[], $3::smallint[])
+                AS w (floor_start, floor_end)
+            LEFT OUTER JOIN alerts a
+                ON $1 = a.user_id
+                AND w.floor_start = a.floor_start
+                AND w.floor_end = a.floor_end
+            WHERE a.user_id IS NULL
+            ",
+            sqlite: "
+            WITH w AS (
+                SELECT starts.value AS floor_start, ends.value AS floor_end
+                FROM json_each($2) starts
+                INNER JOIN json_each($3) ends
+                    ON starts.key = ends.key
+            )
+            INSERT INTO alerts (user_id, floor_start, floor_end)
+            SELECT $1 as user_id, w.floor_start, w.floor_end
+            FROM w
+            LEFT OUTER JOIN alerts a
+                ON $1 = a.user_id
+                AND w.floor_start = a.floor_start
+                AND w.floor_end = a.floor_end
+            WHERE a.user_id IS NULL
+            ",
+        ))
+        .bind(user_id.get() as i64)
+        .bind(db::list(&starts))
+        .bind(db::list(&ends))
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await
+    }
+
+    async fn watched_floors(&self, user_id: serenity::UserId) -> Result<Vec<u8>, sqlx::Error> {
+        sqlx::query_as::<_, (i16,)>(
+            "
+            SELECT floor
+            FROM floor_alerts
+            WHERE user_id = $1
+            ORDER BY floor
+            ",
+        )
+        .bind(user_id.get() as i64)
+        .fetch(&self.pool)
+        .map_ok(|(floor,)| floor as u8)
+        .try_collect()
+        .await
+    }
+
+    async fn toggle_floor(
+        &self,
+        user_id: serenity::UserId,
+        floor: u8,
+    ) -> Result<bool, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let removed = sqlx::query(
+            "
+            DELETE FROM floor_alerts
+            WHERE user_id = $1
+            AND floor = $2
+            RETURNING 1
+            ",
+        )
+        .bind(user_id.get() as i64)
+        .bind(floor as i16)
+        .fetch_optional(&mut *transaction)
+        .await?
+        .is_some();
+
+        if !removed {
+            sqlx::query(
+                "
+                INSERT INTO floor_alerts (user_id, floor)
+                VALUES ($1, $2)
+                ",
+            )
+            .bind(user_id.get() as i64)
+            .bind(floor as i16)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(!removed)
+    }
+
+    async fn set_user_locale(
+        &self,
+        user_id: serenity::UserId,
+        locale: Locale,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            INSERT INTO user_locales (user_id, locale)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id)
+                DO UPDATE SET locale = $2
+            ",
+        )
+        .bind(user_id.get() as i64)
+        .bind(locale)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn watchers(
+        &self,
+        escalators: &[EscalatorFloors],
+    ) -> Result<Vec<(serenity::UserId, Option<Locale>)>, sqlx::Error> {
+        let (starts, ends) = split_floors(escalators);
+
+        // users watching an affected escalator directly,
+        // or watching a floor that an affected escalator starts or ends at
+        let query = sqlx::query_as::<_, (i64, Option<Locale>)>(sql!(
+            postgres: "
+            SELECT w.user_id, l.locale
+            FROM (
+                SELECT user_id
+                FROM alerts a
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM UNNEST($1::smallint[], $2::smallint[])
+                        AS r (floor_start, floor_end)
+                    WHERE a.floor_start = r.floor_start
+                    AND a.floor_end = r.floor_end
+                )
+                UNION
+                SELECT user_id
+                FROM floor_alerts f
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM UNNEST($1::smallint[], $2::smallint[])
+                        AS r (floor_start, floor_end)
+                    WHERE f.floor IN (r.floor_start, r.floor_end)
+                )
+            ) w
+            LEFT OUTER JOIN user_locales l
+                ON w.user_id = l.user_id
+            ",
+            sqlite: "
+            WITH r AS (
+                SELECT starts.value AS floor_start, ends.value AS floor_end
+                FROM json_each($1) starts
+                INNER JOIN json_each($2) ends
+                    ON starts.key = ends.key
+            )
+            SELECT w.user_id, l.locale
+            FROM (
+                SELECT user_id
+                FROM alerts a
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM r
+                    WHERE a.floor_start = r.floor_start
+                    AND a.floor_end = r.floor_end
+                )
+                UNION
+                SELECT user_id
+                FROM floor_alerts f
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM r
+                    WHERE f.floor IN (r.floor_start, r.floor_end)
+                )
+            ) w
+            LEFT OUTER JOIN user_locales l
+                ON w.user_id = l.user_id
+            ",
+        ))
+        .bind(db::list(&starts))
+        .bind(db::list(&ends))
+        .fetch(&self.pool)
+        .map_ok(|(user_id, locale)| (serenity::UserId::new(user_id as u64), locale))
+        .try_collect();
+
+        metrics::time_query("alert_watchers", query).await
+    }
+}
+
+#[async_trait]
+impl ChannelStore for SqlStore {
+    async fn set_announcement_channel(
+        &self,
+        guild_id: serenity::GuildId,
+        channel_id: serenity::ChannelId,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            INSERT INTO announcement_channels (guild_id, channel_id)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id)
+                DO UPDATE SET channel_id = $2
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .bind(channel_id.get() as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove_announcement_channel(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            DELETE FROM announcement_channels
+            WHERE guild_id = $1
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn announcement_channels(
+        &self,
+        guild_ids: Option<&[i64]>,
+    ) -> Result<Vec<AnnouncementChannel>, sqlx::Error> {
+        let Some(guild_ids) = guild_ids else {
+            let query = sqlx::query_as::<_, AnnouncementChannel>(
+                "
+                SELECT c.guild_id,
+                    c.channel_id,
+                    s.announce_delay_secs,
+                    s.max_reports_displayed,
+                    s.crosspost,
+                    s.include_gist,
+                    s.announcement_mode,
+                    s.daily_summary,
+                    s.weekly_summary,
+                    s.locale,
+                    s.reports_open_hour,
+                    s.reports_close_hour,
+                    s.lock_weekends,
+                    s.show_diagram,
+                    s.show_ages,
+                    s.admin_role_id,
+                    s.moderator_role_id
+                FROM announcement_channels c
+                LEFT OUTER JOIN guild_settings s
+                    ON c.guild_id = s.guild_id
+                ",
+            )
+            .fetch_all(&self.pool);
+
+            return metrics::time_query("announcement_channels", query).await;
+        };
+
+        let query = sqlx::query_as::<_, AnnouncementChannel>(sql!(
+            postgres: "
+            SELECT c.guild_id,
+                c.channel_id,
+                s.announce_delay_secs,
+                s.max_reports_displayed,
+                s.crosspost,
+                s.include_gist,
+                s.announcement_mode,
+                s.daily_summary,
+                s.weekly_summary,
+                s.locale,
+                s.reports_open_hour,
+                s.reports_close_hour,
+                s.lock_weekends,
+                s.show_diagram,
+                s.show_ages,
+                s.admin_role_id,
+                s.moderator_role_id
+            FROM announcement_channels c
+            LEFT OUTER JOIN guild_settings s
+                ON c.guild_id = s.guild_id
+            WHERE c.guild_id = ANY($1)
+            ",
+            sqlite: "
+            SELECT c.guild_id,
+                c.channel_id,
+                s.announce_delay_secs,
+                s.max_reports_displayed,
+                s.crosspost,
+                s.include_gist,
+                s.announcement_mode,
+                s.daily_summary,
+                s.weekly_summary,
+                s.locale,
+                s.reports_open_hour,
+                s.reports_close_hour,
+                s.lock_weekends,
+                s.show_diagram,
+                s.show_ages,
+                s.admin_role_id,
+                s.moderator_role_id
+            FROM announcement_channels c
+            LEFT OUTER JOIN guild_settings s
+                ON c.guild_id = s.guild_id
+            WHERE c.guild_id IN (SELECT value FROM json_each($1))
+            ",
+        ))
+        .bind(db::list(guild_ids))
+        .fetch_all(&self.pool);
+
+        metrics::time_query("announcement_channels", query).await
+    }
+
+    async fn live_message(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+    ) -> Result<Option<serenity::MessageId>, sqlx::Error> {
+        sqlx::query_as::<_, (i64,)>(
+            "
+            SELECT message_id
+            FROM live_messages
+            WHERE guild_id = $1
+            AND channel_id = $2
+            ",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map(|row| row.map(|(message_id,)| serenity::MessageId::new(message_id as u64)))
+    }
+
+    async fn set_live_message(
+        &self,
+        guild_id: i64,
+        channel_id: i64,
+        message_id: serenity::MessageId,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            INSERT INTO live_messages (guild_id, channel_id, message_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id)
+                DO UPDATE SET channel_id = $2, message_id = $3
+            ",
+        )
+        .bind(guild_id)
+        .bind(channel_id)
+        .bind(message_id.get() as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn posted_summaries(&self) -> Result<HashMap<i64, DateTime<Utc>>, sqlx::Error> {
+        let query = sqlx::query_as::<_, (i64, DateTime<Utc>)>(
+            "
+            SELECT guild_id, summary_time
+            FROM posted_summaries
+            ",
+        )
+        .fetch_all(&self.pool);
+
+        let posted = metrics::time_query("posted_summaries", query).await?;
+
+        Ok(posted.into_iter().collect())
+    }
+
+    async fn set_posted_summary(
+        &self,
+        guild_id: i64,
+        summary_time: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            INSERT INTO posted_summaries (guild_id, summary_time)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id)
+                DO UPDATE SET summary_time = $2
+            ",
+        )
+        .bind(guild_id)
+        .bind(summary_time)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SettingsStore for SqlStore {
+    async fn guild_settings(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<GuildSettings, sqlx::Error> {
+        let query = sqlx::query_as::<_, GuildSettings>(
+            "
+            SELECT *
+            FROM guild_settings
+            WHERE guild_id = $1
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .fetch_optional(&self.pool);
+
+        metrics::time_query("load_settings", query)
+            .await
+            .map(Option::unwrap_or_default)
+    }
+
+    async fn guild_locale(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Option<Locale>, sqlx::Error> {
+        sqlx::query_as::<_, (Option<Locale>,)>(
+            "
+            SELECT locale
+            FROM guild_settings
+            WHERE guild_id = $1
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map(|row| row.and_then(|(locale,)| locale))
+    }
+
+    async fn save_setting(
+        &self,
+        guild_id: serenity::GuildId,
+        setting: Setting,
+        value: SettingValue,
+    ) -> Result<GuildSettings, sqlx::Error> {
+        // the column comes from a fixed list, so it's safe to format into the query
+        let query = format!(
+            "
+            INSERT INTO guild_settings (guild_id, {column})
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id)
+                DO UPDATE SET {column} = $2
+            RETURNING *
+            ",
+            column = setting.column(),
+        );
+
+        let query = sqlx::query_as::<_, GuildSettings>(&query).bind(guild_id.get() as i64);
+
+        let query = match value {
+            SettingValue::BigInt(value) => query.bind(value),
+            SettingValue::Int(value) => query.bind(value),
+            SettingValue::SmallInt(value) => query.bind(value),
+            SettingValue::Bool(value) => query.bind(value),
+            SettingValue::Mode(value) => query.bind(value),
+            SettingValue::Locale(value) => query.bind(value),
+        };
+
+        query.fetch_one(&self.pool).await
+    }
+
+    async fn reset_settings(
+        &self,
+        guild_id: serenity::GuildId,
+        setting: Option<Setting>,
+    ) -> Result<GuildSettings, sqlx::Error> {
+        let Some(setting) = setting else {
+            sqlx::query(
+                "
+                DELETE FROM guild_settings
+                WHERE guild_id = $1
+                ",
+            )
+            .bind(guild_id.get() as i64)
+            .execute(&self.pool)
+            .await?;
+
+            return Ok(GuildSettings::default());
+        };
+
+        let query = format!(
+            "
+            UPDATE guild_settings
+            SET {column} = NULL
+            WHERE guild_id = $1
+            RETURNING *
+            ",
+            column = setting.column(),
+        );
+
+        sqlx::query_as::<_, GuildSettings>(&query)
+            .bind(guild_id.get() as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map(Option::unwrap_or_default)
+    }
+}
+
+#[async_trait]
+impl MenuStore for SqlStore {
+    async fn menus(&self, affected: Option<&[EscalatorFloors]>) -> Result<Vec<Menu>, sqlx::Error> {
+        let Some(affected) = affected else {
+            let query = sqlx::query_as::<_, Menu>(
+                "
+                SELECT guild_id, channel_id, message_id
+                FROM menu_messages
+                ",
+            )
+            .fetch_all(&self.pool);
+
+            return metrics::time_query("load_menus", query).await;
+        };
+
+        let (starts, ends) = split_floors(affected);
+
+        let query = sqlx::query_as::<_, Menu>(sql!(
+            postgres: "
+            SELECT m.guild_id, m.channel_id, m.message_id
+            FROM menu_messages m
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM menu_escalators s
+                WHERE s.message_id = m.message_id
+            )
+            OR EXISTS (
+                SELECT 1
+                FROM menu_escalators s,
+                    UNNEST($1::smallint[], $2::smallint[])
+                        AS r (floor_start, floor_end)
+                WHERE s.message_id = m.message_id
+                AND s.floor_start = r.floor_start
+                AND s.floor_end = r.floor_end
+            )
+            ",
+            sqlite: "
+            WITH r AS (
+                SELECT starts.value AS floor_start, ends.value AS floor_end
+                FROM json_each($1) starts
+                INNER JOIN json_each($2) ends
+                    ON starts.key = ends.key
+            )
+            SELECT m.guild_id, m.channel_id, m.message_id
+            FROM menu_messages m
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM menu_escalators s
+                WHERE s.message_id = m.message_id
+            )
+            OR EXISTS (
+                SELECT 1
+                FROM menu_escalators s, r
+                WHERE s.message_id = m.message_id
+                AND s.floor_start = r.floor_start
+                AND s.floor_end = r.floor_end
+            )
+            ",
+        ))
+        .bind(db::list(&starts))
+        .bind(db::list(&ends))
+        .fetch_all(&self.pool);
+
+        metrics::time_query("load_menus", query).await
+    }
+
+    async fn guild_menus(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Vec<MenuHealth>, sqlx::Error> {
+        let menus = sqlx::query_as::<_, (i64, i64, bool, Option<String>)>(
+            "
+            SELECT channel_id,
+                message_id,
+                missing_permissions,
+                last_error
+            FROM menu_messages
+            WHERE guild_id = $1
+            ORDER BY message_id
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let scopes = sqlx::query_as::<_, (i64, i16, i16)>(
+            "
+            SELECT s.message_id, s.floor_start, s.floor_end
+            FROM menu_escalators s
+            INNER JOIN menu_messages m
+                ON s.message_id = m.message_id
+            WHERE m.guild_id = $1
+            ORDER BY s.floor_start + s.floor_end,
+                s.floor_start
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut scopes = scopes
+            .into_iter()
+            .map(|(message_id, start, end)| {
+                (message_id, EscalatorFloors::new(start as u8, end as u8))
+            })
+            .into_group_map();
+
+        let menus = menus
+            .into_iter()
+            .map(
+                |(channel_id, message_id, missing_permissions, last_error)| MenuHealth {
+                    channel_id,
+                    message_id,
+                    missing_permissions,
+                    last_error,
+                    escalators: scopes.remove(&message_id).unwrap_or_default(),
+                },
+            )
+            .collect();
+
+        Ok(menus)
+    }
+
+    async fn menu_escalators(
+        &self,
+        message_id: serenity::MessageId,
+    ) -> Result<Vec<Escalator>, sqlx::Error> {
+        let query = sqlx::query_as::<_, Escalator>(
+            "
+            SELECT e.floor_start,
+                e.floor_end,
+                e.current_status,
+                e.status_changed_at
+            FROM escalators e
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM menu_escalators s
+                WHERE s.message_id = $1
+            )
+            OR EXISTS (
+                SELECT 1
+                FROM menu_escalators s
+                WHERE s.message_id = $1
+                AND s.floor_start = e.floor_start
+                AND s.floor_end = e.floor_end
+            )
+            ORDER BY e.floor_start + e.floor_end,
+                e.floor_start
+            ",
+        )
+        .bind(message_id.get() as i64)
+        .fetch_all(&self.pool);
+
+        metrics::time_query("load_menu_escalators", query).await
+    }
+
+    async fn is_scoped(&self, message_id: serenity::MessageId) -> Result<bool, sqlx::Error> {
+        sqlx::query_as::<_, (bool,)>(
+            "
+            SELECT EXISTS (
+                SELECT 1
+                FROM menu_escalators
+                WHERE message_id = $1
+            )
+            ",
+        )
+        .bind(message_id.get() as i64)
+        .fetch_one(&self.pool)
+        .await
+        .map(|(scoped,)| scoped)
+    }
+
+    async fn add_menu(&self, menu: Menu, scope: &[EscalatorFloors]) -> Result<(), sqlx::Error> {
+        let (starts, ends) = split_floors(scope);
+
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query(
+            "
+            INSERT INTO menu_messages (guild_id, channel_id, message_id)
+            VALUES ($1, $2, $3)
+            ",
+        )
+        .bind(menu.guild_id)
+        .bind(menu.channel_id)
+        .bind(menu.message_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query(sql!(
+            postgres: "
+            INSERT INTO menu_escalators (message_id, floor_start, floor_end)
+            SELECT $1, floor_start, floor_end
+            FROM UNNEST($2::smallint[], $3::smallint[])
+                AS s (floor_start, floor_end)
+            ",
+            sqlite: "
+            INSERT INTO menu_escalators (message_id, floor_start, floor_end)
+            SELECT $1, starts.value, ends.value
+            FROM json_each($2) starts
+            INNER JOIN json_each($3) ends
+                ON starts.key = ends.key
+            ",
+        ))
+        .bind(menu.message_id)
+        .bind(db::list(&starts))
+        .bind(db::list(&ends))
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await
+    }
+
+    async fn remove_menus(
+        &self,
+        guild_id: serenity::GuildId,
+        channel_id: serenity::ChannelId,
+        message_id: Option<serenity::MessageId>,
+    ) -> Result<Vec<Menu>, sqlx::Error> {
+        sqlx::query_as::<_, Menu>(
+            "
+            DELETE FROM menu_messages
+            WHERE guild_id = $1
+            AND (
+                message_id = $2
+                OR ($2 IS NULL AND channel_id = $3)
+            )
+            RETURNING guild_id, channel_id, message_id
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .bind(message_id.map(|id| id.get() as i64))
+        .bind(channel_id.get() as i64)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn remove_menu(&self, message_id: serenity::MessageId) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            DELETE FROM menu_messages
+            WHERE message_id = $1
+            ",
+        )
+        .bind(message_id.get() as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn replace_menu(
+        &self,
+        message_id: serenity::MessageId,
+        new_message_id: serenity::MessageId,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            UPDATE menu_messages
+            SET message_id = $2,
+                missing_permissions = false,
+                last_error = NULL
+            WHERE message_id = $1
+            ",
+        )
+        .bind(message_id.get() as i64)
+        .bind(new_message_id.get() as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn menu_synced(&self, message_id: serenity::MessageId) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            UPDATE menu_messages
+            SET missing_permissions = false,
+                last_error = NULL
+            WHERE message_id = $1
+            AND (missing_permissions OR last_error IS NOT NULL)
+            ",
+        )
+        .bind(message_id.get() as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn menu_sync_failed(
+        &self,
+        message_id: serenity::MessageId,
+        error: &str,
+        missing_permissions: bool,
+    ) -> Result<(), sqlx::Error> {
+        // other errors don't say anything about the permissions, so they're left as they were
+        sqlx::query(
+            "
+            UPDATE menu_messages
+            SET missing_permissions = missing_permissions OR $3,
+                last_error = $2
+            WHERE message_id = $1
+            ",
+        )
+        .bind(message_id.get() as i64)
+        .bind(error)
+        .bind(missing_permissions)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WebhookStore for SqlStore {
+    async fn add_webhook(
+        &self,
+        guild_id: serenity::GuildId,
+        url: &str,
+        secret: Option<&str>,
+        scope: &[EscalatorFloors],
+        max: usize,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let (count,) = sqlx::query_as::<_, (i64,)>(
+            "
+            SELECT COUNT(*)
+            FROM webhooks
+            WHERE guild_id = $1
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        if count as usize >= max {
+            return Ok(None);
+        }
+
+        let (webhook_id,) = sqlx::query_as::<_, (i32,)>(
+            "
+            INSERT INTO webhooks (guild_id, url, secret)
+            VALUES ($1, $2, $3)
+            RETURNING webhook_id
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .bind(url)
+        .bind(secret)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        let (starts, ends) = split_floors(scope);
+
+        sqlx::query(sql!(
+            postgres: "
+            INSERT INTO webhook_escalators (webhook_id, floor_start, floor_end)
+            SELECT $1, floor_start, floor_end
+            FROM UNNEST($2::smallint[], $3::smallint[])
+                AS s (floor_start, floor_end)
+            ",
+            sqlite: "
+            INSERT INTO webhook_escalators (webhook_id, floor_start, floor_end)
+            SELECT $1, starts.value, ends.value
+            FROM json_each($2) starts
+            INNER JOIN json_each($3) ends
+                ON starts.key = ends.key
+            ",
+        ))
+        .bind(webhook_id)
+        .bind(db::list(&starts))
+        .bind(db::list(&ends))
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(Some(webhook_id))
+    }
+
+    async fn guild_webhooks(
+        &self,
+        guild_id: serenity::GuildId,
+    ) -> Result<Vec<WebhookHealth>, sqlx::Error> {
+        #[derive(sqlx::FromRow)]
+        struct WebhookRow {
+            webhook_id: i32,
+            url: String,
+            signed: bool,
+            status_code: Option<i16>,
+            error: Option<String>,
+            attempted_at: Option<DateTime<Utc>>,
+        }
+
+        let webhooks = sqlx::query_as::<_, WebhookRow>(sql!(
+            postgres: "
+            SELECT w.webhook_id,
+                w.url,
+                (w.secret IS NOT NULL) AS signed,
+                d.status_code,
+                d.error,
+                d.attempted_at
+            FROM webhooks w
+            LEFT JOIN LATERAL (
+                SELECT status_code, error, attempted_at
+                FROM webhook_deliveries d
+                WHERE w.webhook_id = d.webhook_id
+                ORDER BY attempted_at DESC, id DESC
+                LIMIT 1
+            ) d ON true
+            WHERE w.guild_id = $1
+            ORDER BY w.webhook_id
+            ",
+            // the latest delivery is the one with the highest id, since they're never updated
+            sqlite: "
+            SELECT w.webhook_id,
+                w.url,
+                (w.secret IS NOT NULL) AS signed,
+                d.status_code,
+                d.error,
+                d.attempted_at
+            FROM webhooks w
+            LEFT JOIN webhook_deliveries d
+                ON d.id = (
+                    SELECT MAX(id)
+                    FROM webhook_deliveries
+                    WHERE webhook_id = w.webhook_id
+                )
+            WHERE w.guild_id = $1
+            ORDER BY w.webhook_id
+            ",
+        ))
+        .bind(guild_id.get() as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let scopes = sqlx::query_as::<_, (i32, i16, i16)>(
+            "
+            SELECT s.webhook_id, s.floor_start, s.floor_end
+            FROM webhook_escalators s
+            INNER JOIN webhooks w
+                ON s.webhook_id = w.webhook_id
+            WHERE w.guild_id = $1
+            ORDER BY s.floor_start + s.floor_end,
+                s.floor_start
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut scopes = scopes
+            .into_iter()
+            .map(|(webhook_id, start, end)| {
+                (webhook_id, EscalatorFloors::new(start as u8, end as u8))
+            })
+            .into_group_map();
+
+        let webhooks = webhooks
+            .into_iter()
+            .map(|webhook| WebhookHealth {
+                webhook_id: webhook.webhook_id,
+                url: webhook.url,
+                signed: webhook.signed,
+                escalators: scopes.remove(&webhook.webhook_id).unwrap_or_default(),
+                last_delivery: webhook.attempted_at.map(|attempted_at| Delivery {
+                    attempt: DeliveryAttempt {
+                        status_code: webhook.status_code.map(|code| code as u16),
+                        error: webhook.error,
+                    },
+                    attempted_at,
+                }),
+            })
+            .collect();
+
+        Ok(webhooks)
+    }
+
+    async fn remove_webhook(
+        &self,
+        guild_id: serenity::GuildId,
+        webhook_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let removed = sqlx::query(
+            "
+            DELETE FROM webhooks
+            WHERE guild_id = $1
+            AND webhook_id = $2
+            ",
+        )
+        .bind(guild_id.get() as i64)
+        .bind(webhook_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(removed > 0)
+    }
+
+    async fn webhooks(&self, affected: &[EscalatorFloors]) -> Result<Vec<Webhook>, sqlx::Error> {
+        let (starts, ends) = split_floors(affected);
+
+        let query = sqlx::query_as::<_, Webhook>(sql!(
+            postgres: "
+            SELECT webhook_id, url, secret
+            FROM webhooks w
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM webhook_escalators s
+                WHERE w.webhook_id = s.webhook_id
+            )
+            OR EXISTS (
+                SELECT 1
+                FROM webhook_escalators s
+                INNER JOIN UNNEST($1::smallint[], $2::smallint[])
+                    AS r (floor_start, floor_end)
+                    ON s.floor_start = r.floor_start
+                    AND s.floor_end = r.floor_end
+                WHERE w.webhook_id = s.webhook_id
+            )
+            ",
+            sqlite: "
+            WITH r AS (
+                SELECT starts.value AS floor_start, ends.value AS floor_end
+                FROM json_each($1) starts
+                INNER JOIN json_each($2) ends
+                    ON starts.key = ends.key
+            )
+            SELECT webhook_id, url, secret
+            FROM webhooks w
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM webhook_escalators s
+                WHERE w.webhook_id = s.webhook_id
+            )
+            OR EXISTS (
+                SELECT 1
+                FROM webhook_escalators s
+                INNER JOIN r
+                    ON s.floor_start = r.floor_start
+                    AND s.floor_end = r.floor_end
+                WHERE w.webhook_id = s.webhook_id
+            )
+            ",
+        ))
+        .bind(db::list(&starts))
+        .bind(db::list(&ends))
+        .fetch_all(&self.pool);
+
+        metrics::time_query("webhooks", query).await
+    }
+
+    async fn record_deliveries(
+        &self,
+        webhook_id: i32,
+        attempts: &[DeliveryAttempt],
+    ) -> Result<(), sqlx::Error> {
+        let numbers = (1..=attempts.len() as i16).collect::<Vec<_>>();
+        let status_codes = attempts
+            .iter()
+            .map(|attempt| attempt.status_code.map(|code| code as i16))
+            .collect::<Vec<_>>();
+        let errors = attempts
+            .iter()
+            .map(|attempt| attempt.error.clone())
+            .collect::<Vec<_>>();
+
+        // the webhook may have been removed while it was being delivered to
+        sqlx::query(sql!(
+            postgres: "
+            INSERT INTO webhook_deliveries (webhook_id, attempt, status_code, error)
+            SELECT w.webhook_id, a.attempt, a.status_code, a.error
+            FROM webhooks w
+            CROSS JOIN UNNEST($2::smallint[], $3::smallint[], $4::text[])
+                AS a (attempt, status_code, error)
+            WHERE w.webhook_id = $1
+            ",
+            sqlite: "
+            INSERT INTO webhook_deliveries (webhook_id, attempt, status_code, error)
+            SELECT w.webhook_id, attempts.value, status_codes.value, errors.value
+            FROM webhooks w
+            CROSS JOIN json_each($2) attempts
+            INNER JOIN json_each($3) status_codes
+                ON attempts.key = status_codes.key
+            INNER JOIN json_each($4) errors
+                ON attempts.key = errors.key
+            WHERE w.webhook_id = $1
+            ",
+        ))
+        .bind(webhook_id)
+        .bind(db::list(&numbers))
+        .bind(db::list(&status_codes))
+        .bind(db::list(&errors))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UptimeStore for SqlStore {
+    async fn last_seen(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let last_seen = sqlx::query_as::<_, (DateTime<Utc>,)>(
+            "
+            SELECT last_seen
+            FROM heartbeat
+            ",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(last_seen.map(|(last_seen,)| last_seen))
+    }
+
+    async fn set_last_seen(&self, at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            INSERT INTO heartbeat (last_seen)
+            VALUES ($1)
+            ON CONFLICT (id) DO UPDATE
+            SET last_seen = excluded.last_seen
+            ",
+        )
+        .bind(at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// A report as it's saved in the outbox, without the escalators it changed.
+#[derive(sqlx::FromRow)]
+struct EventRow {
+    id: i64,
+    reporter_id: Option<i64>,
+    escalators: String,
+    status: Status,
+    created_at: DateTime<Utc>,
+}
+
+impl SqlStore {
+    /// Loads the escalators changed by each of the reports, which have to be in order.
+    async fn outbox_events(&self, rows: Vec<EventRow>) -> Result<Vec<OutboxEvent>, sqlx::Error> {
+        let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+            return Ok(vec![]);
+        };
+
+        let affected = sqlx::query_as::<_, (i64, i16, i16)>(
+            "
+            SELECT event_id, floor_start, floor_end
+            FROM outbox_escalators
+            WHERE event_id BETWEEN $1 AND $2
+            ORDER BY event_id,
+                position
+            ",
+        )
+        .bind(first.id)
+        .bind(last.id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .into_group_map_by(|&(event_id, ..)| event_id);
+
+        rows.into_iter()
+            .map(|row| {
+                let escalators = row
+                    .escalators
+                    .parse::<EscalatorInput>()
+                    .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+
+                let affected_escalators = affected
+                    .get(&row.id)
+                    .into_iter()
+                    .flatten()
+                    .map(|&(_, start, end)| EscalatorFloors::new(start as u8, end as u8))
+                    .collect();
+
+                Ok(OutboxEvent {
+                    id: row.id,
+                    report: UserReport {
+                        reporter: row.reporter_id.map(|id| serenity::UserId::new(id as u64)),
+                        escalators,
+                        affected_escalators,
+                        new_status: row.status,
+                    },
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OutboxStore for SqlStore {
+    async fn events_after(
+        &self,
+        event_id: i64,
+        limit: usize,
+    ) -> Result<Vec<OutboxEvent>, sqlx::Error> {
+        let load = async {
+            let rows = sqlx::query_as::<_, EventRow>(
+                "
+                SELECT id, reporter_id, escalators, status, created_at
+                FROM outbox
+                WHERE id > $1
+                ORDER BY id
+                LIMIT $2
+                ",
+            )
+            .bind(event_id)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+            self.outbox_events(rows).await
+        };
+
+        metrics::time_query("outbox_events", load).await
+    }
+
+    async fn events_until(
+        &self,
+        event_id: i64,
+        limit: usize,
+    ) -> Result<Vec<OutboxEvent>, sqlx::Error> {
+        let load = async {
+            let mut rows = sqlx::query_as::<_, EventRow>(
+                "
+                SELECT id, reporter_id, escalators, status, created_at
+                FROM outbox
+                WHERE id <= $1
+                ORDER BY id DESC
+                LIMIT $2
+                ",
+            )
+            .bind(event_id)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+            rows.reverse();
+            self.outbox_events(rows).await
+        };
+
+        metrics::time_query("outbox_events_until", load).await
+    }
+
+    async fn cursor(&self, consumer: &str) -> Result<i64, sqlx::Error> {
+        // a new consumer's cursor is saved right away, so the reports made before it handles one
+        // aren't skipped if it restarts. SQLite needs the WHERE to parse the ON CONFLICT,
+        // and the update does nothing but let an existing cursor be returned
+        let (last_event_id,) = sqlx::query_as::<_, (i64,)>(
+            "
+            INSERT INTO outbox_cursors (consumer, last_event_id)
+            SELECT $1, COALESCE(MAX(id), 0)
+            FROM outbox
+            WHERE true
+            ON CONFLICT (consumer) DO UPDATE
+            SET last_event_id = outbox_cursors.last_event_id
+            RETURNING last_event_id
+            ",
+        )
+        .bind(consumer)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(last_event_id)
+    }
+
+    async fn set_cursor(&self, consumer: &str, event_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            INSERT INTO outbox_cursors (consumer, last_event_id)
+            VALUES ($1, $2)
+            ON CONFLICT (consumer) DO UPDATE
+            SET last_event_id = excluded.last_event_id
+            ",
+        )
+        .bind(consumer)
+        .bind(event_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn prune_events(&self, consumers: &[&str], keep: usize) -> Result<u64, sqlx::Error> {
+        // the report's escalators are removed along with it
+        let query = sqlx::query(sql!(
+            postgres: "
+            DELETE FROM outbox
+            WHERE id <= (
+                SELECT MIN(last_event_id)
+                FROM outbox_cursors
+                WHERE consumer = ANY($1)
+            )
+            AND id NOT IN (
+                SELECT id
+                FROM outbox
+                ORDER BY id DESC
+                LIMIT $2
+            )
+            ",
+            sqlite: "
+            DELETE FROM outbox
+            WHERE id <= (
+                SELECT MIN(last_event_id)
+                FROM outbox_cursors
+                WHERE consumer IN (SELECT value FROM json_each($1))
+            )
+            AND id NOT IN (
+                SELECT id
+                FROM outbox
+                ORDER BY id DESC
+                LIMIT $2
+            )
+            ",
+        ))
+        .bind(db::list(consumers))
+        .bind(keep.max(1) as i64)
+        .execute(&self.pool);
+
+        let res = metrics::time_query("outbox_prune", query).await?;
+
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::data::db::tests::memory_pool;
+
+    #[tokio::test]
+    async fn reports_log_every_change_at_once() {
+        let store = SqlStore::new(memory_pool().await);
+        let reporter = Some(serenity::UserId::new(1));
+
+        let affected = store
+            .commit_report(reporter, EscalatorInput::Pair(4, 6), Status::Down)
+            .await
+            .unwrap();
+        assert_eq!(
+            &affected[..],
+            [EscalatorFloors::new(4, 6), EscalatorFloors::new(6, 4)]
+        );
+
+        let changes = sqlx::query_as::<_, (i64, DateTime<Utc>)>(
+            "
+            SELECT reporter_id, changed_at
+            FROM status_changes
+            ",
+        )
+        .fetch_all(&store.pool)
+        .await
+        .unwrap();
+
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|&(reporter_id, _)| reporter_id == 1));
+        assert_eq!(changes[0].1, changes[1].1);
+    }
+}
+
+/// Run against the Postgres database at `DATABASE_URL`, and skipped if it isn't set.
+#[cfg(all(test, not(feature = "sqlite")))]
+mod postgres_tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::*;
+
+    async fn pool() -> Option<DbPool> {
+        let url = std::env::var("DATABASE_URL").ok()?;
+        let pool = DbPool::connect(&url).await.unwrap();
+        db::MIGRATOR.run(&pool).await.unwrap();
+
+        Some(pool)
+    }
+
+    #[tokio::test]
+    async fn outbox_ids_follow_commit_order() {
+        let Some(pool) = pool().await else {
+            return;
+        };
+        let store = Arc::new(SqlStore::new(pool.clone()));
+
+        let (last_id,) = sqlx::query_as::<_, (i64,)>("SELECT COALESCE(MAX(id), 0) FROM outbox")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // the first report is added, but isn't committed yet
+        let mut first = pool.begin().await.unwrap();
+        add_event(
+            &mut first,
+            None,
+            EscalatorInput::Direct(4, 6),
+            Status::Open,
+            &[],
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+
+        let second = tokio::spawn({
+            let store = Arc::clone(&store);
+            async move {
+                store
+                    .commit_report(None, EscalatorInput::Direct(6, 4), Status::Open)
+                    .await
+            }
+        });
+
+        // the second report waits for the first, rather than committing a later ID before it
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!second.is_finished());
+        assert!(store.events_after(last_id, 10).await.unwrap().is_empty());
+
+        first.commit().await.unwrap();
+        second.await.unwrap().unwrap();
+
+        let events = store.events_after(last_id, 10).await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0].report.escalators,
+            EscalatorInput::Direct(4, 6)
+        ));
+        assert!(matches!(
+            events[1].report.escalators,
+            EscalatorInput::Direct(6, 4)
+        ));
+    }
+}
@@ -1,42 +1,33 @@
 use std::time::{Duration, SystemTime, SystemTimeError};
 
 use crate::{
-    data::{report::UserReport, status::Status},
+    data::{
+        history::StatusChange, report::UserReport, settings::GuildSettings, status::Status,
+        store::EscalatorStore,
+    },
+    locale::{Escalators, Locale},
     prelude::*,
+    render,
 };
 
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
 use itertools::Itertools;
-use poise::serenity_prelude::{ButtonStyle, CreateActionRow, CreateButton};
+use poise::serenity_prelude::{ButtonStyle, CreateActionRow, CreateAttachment, CreateButton};
 
-pub async fn gist(pool: &sqlx::PgPool) -> Result<serenity::CreateEmbed, sqlx::Error> {
+pub async fn gist(
+    store: &dyn EscalatorStore,
+    locale: Locale,
+) -> Result<serenity::CreateEmbed, sqlx::Error> {
     // -- Setup
 
-    let embed = serenity::CreateEmbed::default().title("Here's the gist...");
-
-    let escalator_count = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM escalators")
-        .fetch_one(pool)
-        .await?
-        .0 as usize;
-
-    let mut summaries = vec![];
-
-    // add summaries for down and blocked status escalators (only if there are any of either)
-    for status in [Status::Down, Status::Blocked] {
-        let escalators = sqlx::query_as::<_, EscalatorFloors>(
-            "
-            SELECT floor_start, floor_end
-            FROM escalators
-            WHERE current_status = $1
-            ",
-        )
-        .bind(status)
-        .fetch_all(pool)
-        .await?;
-
-        if !escalators.is_empty() {
-            summaries.push(summarize_status(status, &escalators, escalator_count));
-        }
-    }
+    let catalog = locale.catalog();
+
+    let embed = serenity::CreateEmbed::default().title(catalog.gist_title());
+
+    let escalators = store.escalators().await?;
+
+    let summaries = gist_summaries(&escalators, locale, relative_age);
 
     // -- Handle Variations
 
@@ -50,89 +41,94 @@ pub async fn gist(pool: &sqlx::PgPool) -> Result<serenity::CreateEmbed, sqlx::Er
 
     // -- All are Open
 
-    let emoji = Status::Open.emoji();
-
-    let embed = embed
-        .description(format!("`{emoji}` `ALL` escalators are `OPEN`! 🥳 🎉"))
-        .color((55, 220, 70));
+    let embed = embed.description(all_open(locale)).color((55, 220, 70));
 
     Ok(embed)
 }
 
-/// Generates a summary for a specific status.
+/// Generates a summary for each status that any of the escalators are down or blocked with,
+/// formatting how long ago they changed with `age`. It's empty if every escalator is open.
+pub fn gist_summaries(
+    escalators: &[Escalator],
+    locale: Locale,
+    age: impl Fn(DateTime<Utc>) -> String,
+) -> Vec<String> {
+    [Status::Down, Status::Blocked]
+        .into_iter()
+        .filter_map(|status| {
+            let matching = escalators
+                .iter()
+                .filter(|escalator| escalator.status == status)
+                .copied()
+                .collect_vec();
+
+            (!matching.is_empty())
+                .then(|| summarize_status(status, &matching, escalators.len(), locale, &age))
+        })
+        .collect()
+}
+
+/// Generates the gist for when every escalator is open.
+pub fn all_open(locale: Locale) -> String {
+    let emoji = Status::Open.emoji();
+    let all_open = locale
+        .catalog()
+        .escalators_status(Escalators::All, Status::Open);
+
+    format!("`{emoji}` {all_open}! 🥳 🎉")
+}
+
+/// Generates a summary for a specific status,
+/// including how long ago the most recent of the escalators changed.
 fn summarize_status(
     status: Status,
-    escalators: &[EscalatorFloors],
+    escalators: &[Escalator],
     escalator_count: usize,
+    locale: Locale,
+    age: &impl Fn(DateTime<Utc>) -> String,
 ) -> String {
-    let emoji = status.emoji();
-
-    let mut message = format!("`{}` ", emoji);
+    let catalog = locale.catalog();
+    let floors = escalators.iter().map(|e| e.floors).collect_vec();
 
-    if escalators.len() == escalator_count {
+    let noun = if escalators.len() == escalator_count {
         // all
-        message.push_str("`ALL` escalators");
+        Escalators::All
     } else if escalators.len() >= escalator_count / 2 {
         // more than half
-        message.push_str("`MANY` escalators");
+        Escalators::Many
     } else {
         // less than half
-        message.push_str(&nounify_escalators(escalators));
-    }
-
-    if escalators.len() == 1 {
-        message.push_str(" is ");
-    } else {
-        message.push_str(" are ");
-    }
-
-    // TODO: make this less verbose
-    let status = match status {
-        Status::Open => "`OPEN`",
-        Status::Down => "`DOWN`",
-        Status::Blocked => "`BLOCKED`",
+        Escalators::Listed(&floors)
     };
 
-    message.push_str(status);
-    message.push('.');
-
-    message
-}
-
-/// Turn a collection of Escalators into a format that could be put into a message.
-pub fn nounify_escalators(escalators: &[EscalatorFloors]) -> String {
-    if escalators.is_empty() {
-        return String::from("`NO` escalators");
-    }
-
-    if escalators.len() == 1 {
-        return format!("The `{}` escalator", escalators[0]);
-    }
-
-    // how many escalators there are not including the first and last
-    let mid_count = escalators.len() - 2;
-
-    let mut escalators = escalators
-        .iter()
-        .copied()
-        .map(|escalator| format!("`{escalator}`"));
-    let mut noun = format!("The {}", escalators.next().unwrap());
+    let mut message = format!(
+        "`{}` {}",
+        status.emoji(),
+        catalog.escalators_status(noun, status)
+    );
 
-    for escalator in escalators.by_ref().take(mid_count) {
-        noun.push_str(&format!(", {escalator}"));
+    if let Some(changed_at) = escalators.iter().filter_map(|e| e.status_changed_at).max() {
+        message.push_str(&catalog.updated(&age(changed_at)));
     }
 
-    noun.push_str(&format!(", and {} escalators", escalators.next().unwrap()));
+    message.push('.');
 
-    noun
+    message
 }
 
 /// Generates a message of a list of recent reports.
-pub fn announcement<'a, I>(max_reports_displayed: usize, reports: I) -> String
+pub fn announcement<'a, I>(max_reports_displayed: usize, reports: I, locale: Locale) -> String
 where
     I: Iterator<Item = &'a UserReport> + ExactSizeIterator + 'a,
 {
-    let mut reports = reports.map(UserReport::to_string);
+    let catalog = locale.catalog();
+
+    let mut reports = reports.map(|report| {
+        let emoji = report.new_status.emoji();
+        let report = catalog.user_report(report.reporter, &report.escalators);
+
+        format!("`{emoji}` {report}")
+    });
 
     if reports.len() <= max_reports_displayed {
         return reports.join("\n");
@@ -144,47 +140,147 @@ where
         message.push('\n');
     }
 
-    message.push_str(&format!("\n*(...and {} more)*", reports.len()));
+    message.push('\n');
+    message.push_str(&catalog.more_reports(reports.len()));
 
     message
 }
 
 /// Generates an alert message from a user report.
-pub fn alert(report: &UserReport) -> String {
-    let emoji = report.new_status.emoji();
-    let noun = nounify_escalators(&report.affected_escalators);
-    let is_are = if report.affected_escalators.len() == 1 {
-        "is"
-    } else {
-        "are"
-    };
-    let status = report.new_status.as_id_str();
+pub fn alert(report: &UserReport, locale: Locale) -> String {
+    status_message(&report.affected_escalators, report.new_status, locale)
+}
+
+/// Generates a message saying the given escalators changed to a status.
+pub fn status_message(escalators: &[EscalatorFloors], status: Status, locale: Locale) -> String {
+    let emoji = status.emoji();
+    let message = locale
+        .catalog()
+        .escalators_status(Escalators::Listed(escalators), status);
+
+    format!("`{emoji}` {message}")
+}
+
+/// Generates a message listing status changes, showing only the most recent ones.
+pub fn status_changes(
+    max_changes_displayed: usize,
+    changes: &[StatusChange],
+    locale: Locale,
+) -> String {
+    let catalog = locale.catalog();
+
+    if changes.is_empty() {
+        return String::from(catalog.no_changes());
+    }
 
-    format!("`{emoji}` {noun} {is_are} `{status}`")
+    let skipped = changes.len().saturating_sub(max_changes_displayed);
+
+    let mut message = changes[skipped..]
+        .iter()
+        .map(|change| {
+            let timestamp = Timestamp::Short
+                .generate_at(change.changed_at.into())
+                .expect("Time went backwards");
+
+            format!(
+                "`{}` `{}` {timestamp}",
+                change.status.emoji(),
+                change.floors
+            )
+        })
+        .join("\n");
+
+    if skipped > 0 {
+        message.push('\n');
+        message.push_str(&catalog.earlier_changes(skipped));
+    }
+
+    message
+}
+
+/// Generates a list of escalators and the percentage of time they were open.
+pub fn uptimes<'a, I>(uptimes: I) -> String
+where
+    I: Iterator<Item = &'a (EscalatorFloors, f64)>,
+{
+    uptimes
+        .map(|(floors, uptime)| format!("`{floors}` {:.1}%", uptime * 100.0))
+        .join("\n")
 }
 
-/// Generates a message containing the status of every escalator.
-pub async fn menu_status(pool: &sqlx::PgPool) -> Result<String, sqlx::Error> {
-    let statuses = sqlx::query_as::<_, Escalator>(
-        "
-        SELECT floor_start,
-            floor_end,
-            current_status
-        FROM escalators
-        ORDER BY floor_start + floor_end,
-            floor_start
-        ",
+/// Generates a message containing the status of the given escalators,
+/// and how long ago each of them changed if the guild shows it.
+pub fn menu_status(escalators: &[Escalator], settings: &GuildSettings) -> String {
+    let statuses = escalator_pairs(escalators)
+        .map(|pair| {
+            pair.iter()
+                .map(|escalator| match escalator.status_changed_at {
+                    Some(changed_at) if settings.show_ages => {
+                        format!("`{escalator}` {}", relative_age(changed_at))
+                    }
+                    _ => format!("`{escalator}`"),
+                })
+                .join(" · ")
+        })
+        .join("\n");
+
+    format!(
+        "**{}:**\n{statuses}",
+        settings.locale.catalog().statuses_title()
     )
-    .fetch_all(pool)
-    .await?
-    .iter()
-    .map(Escalator::to_string)
-    .chunks(2)
-    .into_iter()
-    .map(|mut pair| pair.join(" "))
-    .join("\n");
-
-    Ok(format!("**Escalator Statuses:**```\n{statuses}```"))
+}
+
+/// Groups escalators into the pairs they're displayed in, which are the escalators
+/// going up and down between the same floors, in the order they first appear.
+/// Escalators whose other direction isn't given are left on their own.
+pub fn escalator_pairs(escalators: &[Escalator]) -> impl Iterator<Item = Vec<&Escalator>> {
+    let mut pairs = IndexMap::<(u8, u8), Vec<&Escalator>>::new();
+
+    for escalator in escalators {
+        let EscalatorFloors { start, end } = escalator.floors;
+        let floors = (start.min(end), start.max(end));
+
+        pairs.entry(floors).or_default().push(escalator);
+    }
+
+    pairs.into_values()
+}
+
+/// Generates a Discord timestamp which displays how long ago the given time was.
+pub fn relative_age(time: DateTime<Utc>) -> String {
+    Timestamp::Relative
+        .generate_at(time.into())
+        .expect("Time went backwards")
+}
+
+/// Lets a channel know the bot was offline between the two times, and could've missed reports.
+pub fn offline_notice(from: DateTime<Utc>, to: DateTime<Utc>, locale: Locale) -> String {
+    let [from, to] = [from, to].map(|time| {
+        Timestamp::Short
+            .generate_at(time.into())
+            .expect("Time went backwards")
+    });
+
+    locale.catalog().offline_notice(&from, &to)
+}
+
+/// Generates a diagram of the given escalators' statuses as an image attachment,
+/// or `None` if the guild hides it or the diagram failed to render.
+pub fn menu_diagram(
+    escalators: &[Escalator],
+    settings: &GuildSettings,
+) -> Option<CreateAttachment> {
+    if !settings.show_diagram {
+        return None;
+    }
+
+    match render::building_diagram(escalators, settings.locale).to_png() {
+        Ok(diagram) => Some(CreateAttachment::bytes(diagram, render::DIAGRAM_FILENAME)),
+        Err(err) => {
+            log::warn!("An error ocurred trying to render the menu diagram: {err}");
+            None
+        }
+    }
 }
 
 pub const REPORT_EMOJI: char = '📢';
@@ -193,10 +289,10 @@ pub const REPORT_BUTTON_ID: &str = "REPORT";
 pub const INFO_EMOJI: char = '❔';
 pub const INFO_BUTTON_ID: &str = "INFO";
 
-pub fn menu_buttons() -> CreateActionRow {
+pub fn menu_buttons(locale: Locale) -> CreateActionRow {
     CreateActionRow::Buttons(vec![
         CreateButton::new(REPORT_BUTTON_ID)
-            .label("Report")
+            .label(locale.catalog().report_button())
             .emoji(REPORT_EMOJI)
             .style(ButtonStyle::Primary),
         CreateButton::new(INFO_BUTTON_ID)
@@ -248,10 +344,10 @@ impl Timestamp {
     }
 }
 
-pub fn timeout_message(duration: Duration) -> String {
+pub fn timeout_message(duration: Duration, locale: Locale) -> String {
     let timestamp = Timestamp::Relative
         .generate_at(SystemTime::now() + duration)
         .expect("Time went backwards");
 
-    format!("This menu will timeout {timestamp}")
+    locale.catalog().timeout_message(&timestamp)
 }
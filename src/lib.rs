@@ -0,0 +1,282 @@
+pub mod data;
+pub mod generate;
+pub mod locale;
+pub mod render;
+
+mod api;
+mod bot_tasks;
+mod commands;
+mod metrics;
+mod prelude;
+
+use bot_tasks::{
+    alert::AlertTask,
+    announce::AnnounceTask,
+    menus::{info::InfoTask, report::ReportTask, sync::SyncTask},
+    outbox::PruneTask,
+    summary::SummaryTask,
+    supervisor::{self, Backoff},
+    uptime::UptimeTask,
+    webhook::WebhookTask,
+    BotTask, Shutdown,
+};
+use futures::future::BoxFuture;
+use poise::serenity_prelude::{
+    Cache, CacheHttp, ComponentInteraction, FullEvent, Http, ShardMessenger,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::task;
+
+use prelude::*;
+
+/// The bot along with its tasks and HTTP API, which is run on Shuttle by the main binary,
+/// or anywhere else by the `standalone` binary.
+pub struct EscalatorBot {
+    framework: poise::Framework<Data, Error>,
+    data: Data,
+    token: String,
+}
+
+struct BotTasks<T> {
+    tasks: task::JoinSet<()>,
+    data: Data,
+    cache_http: Arc<T>,
+}
+
+struct CacheAndHttp(Arc<Cache>, Arc<Http>);
+
+/// How long tasks get to finish what they're doing when the bot shuts down.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl EscalatorBot {
+    /// Runs any pending migrations and sets up the bot framework.
+    pub async fn new(pool: DbPool, token: String) -> anyhow::Result<Self> {
+        data::db::MIGRATOR.run(&pool).await?;
+
+        let data = Data::new(Arc::new(data::store::SqlStore::new(pool)));
+
+        // create bot framework
+        let framework = poise::Framework::builder()
+            .options(poise::FrameworkOptions {
+                // add bot commands
+                commands: commands::commands(),
+                event_handler,
+                ..Default::default()
+            })
+            .setup({
+                let data = data.clone();
+                move |_ctx, _ready, _framework| {
+                    Box::pin(async move {
+                        // set up bot data
+                        log::info!("Bot is ready");
+                        Ok(data)
+                    })
+                }
+            })
+            .build();
+
+        Ok(Self {
+            framework,
+            data,
+            token,
+        })
+    }
+
+    /// Starts the bot, its tasks and the HTTP API, running until the client stops
+    /// (which it does once the process is asked to shut down).
+    pub async fn run(self, addr: std::net::SocketAddr) -> anyhow::Result<()> {
+        let intents = serenity::GatewayIntents::non_privileged();
+
+        let mut client = serenity::ClientBuilder::new(self.token, intents)
+            .framework(self.framework)
+            .await?;
+
+        let cache = Arc::clone(&client.cache);
+        let http = Arc::clone(&client.http);
+        let cache_http = Arc::new(CacheAndHttp(cache, http));
+        let api_data = self.data.clone();
+        let api = tokio::spawn(async move {
+            if let Err(err) = api::serve(addr, api_data).await {
+                log::error!("The HTTP API stopped: {err}");
+            }
+        });
+
+        let bot_tasks = BotTasks::new(self.data, cache_http)
+            .start_task(AnnounceTask)
+            .await?
+            .start_task(AlertTask)
+            .await?
+            .start_task(InfoTask)
+            .await?
+            .start_task(ReportTask)
+            .await?
+            .start_task(SyncTask)
+            .await?
+            .start_task(SummaryTask)
+            .await?
+            .start_task(WebhookTask)
+            .await?
+            .start_task(UptimeTask::default())
+            .await?
+            .start_task(PruneTask::new(outbox_consumers()))
+            .await?;
+
+        let shard_manager = Arc::clone(&client.shard_manager);
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            log::info!("Shutting down...");
+            shard_manager.shutdown_all().await;
+        });
+
+        let res = client.start().await;
+
+        // let the bot tasks finish up and stop the API once client stops
+        bot_tasks.shutdown().await;
+        api.abort();
+
+        Ok(res?)
+    }
+}
+
+#[cfg(feature = "shuttle")]
+#[shuttle_runtime::async_trait]
+impl shuttle_runtime::Service for EscalatorBot {
+    async fn bind(mut self, addr: std::net::SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        self.run(addr).await?;
+        Ok(())
+    }
+}
+
+impl CacheHttp for CacheAndHttp {
+    fn cache(&self) -> Option<&Arc<Cache>> {
+        Some(&self.0)
+    }
+
+    fn http(&self) -> &Http {
+        &self.1
+    }
+}
+
+impl<T: CacheHttp + 'static> BotTasks<T> {
+    fn new(data: Data, cache_http: Arc<T>) -> Self {
+        Self {
+            tasks: task::JoinSet::new(),
+            data,
+            cache_http,
+        }
+    }
+
+    /// Sets up a task and runs it in the background, restarting it if it fails.
+    async fn start_task<B: BotTask<T> + Clone + 'static>(
+        mut self,
+        task: B,
+    ) -> anyhow::Result<Self> {
+        let Some(data) = task.setup(&self.data, Arc::clone(&self.cache_http)).await else {
+            anyhow::bail!("Failed to run setup for bot task: {}", B::NAME);
+        };
+
+        self.tasks.spawn(supervisor::supervise(
+            task,
+            self.data.clone(),
+            Arc::clone(&self.cache_http),
+            data,
+            self.data.receiver(),
+            Backoff::default(),
+        ));
+
+        Ok(self)
+    }
+
+    /// Lets the tasks know the bot is shutting down, waiting for them to stop
+    /// and aborting them if they take too long.
+    async fn shutdown(mut self) {
+        self.data.send_message(Shutdown);
+
+        let stopped = async { while self.tasks.join_next().await.is_some() {} };
+
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, stopped)
+            .await
+            .is_err()
+        {
+            log::warn!("Bot tasks took too long to shut down, aborting them.");
+            self.tasks.abort_all();
+        }
+    }
+}
+
+/// The tasks reading reports from the outbox, which it's only pruned up to.
+fn outbox_consumers() -> Vec<&'static str> {
+    vec![
+        <AnnounceTask as BotTask<CacheAndHttp>>::NAME,
+        <AlertTask as BotTask<CacheAndHttp>>::NAME,
+        <SyncTask as BotTask<CacheAndHttp>>::NAME,
+        <WebhookTask as BotTask<CacheAndHttp>>::NAME,
+    ]
+}
+
+/// Waits for Ctrl+C, or for the process to be terminated.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                log::warn!("An error ocurred trying to listen for SIGTERM: {err}");
+                std::future::pending().await
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        () = terminate => {}
+    }
+}
+
+pub struct ComponentMessage {
+    interaction: ComponentInteraction,
+    shard: ShardMessenger,
+}
+
+/// TODO: rework this system and reduce cloning
+fn event_handler<'a>(
+    serenity_ctx: &'a serenity::Context,
+    event: &'a FullEvent,
+    ctx: poise::FrameworkContext<'a, Data, Error>,
+    _data: &'a Data,
+) -> BoxFuture<'a, Result<(), Error>> {
+    use serenity::Interaction;
+
+    log::debug!("Event received: {event:?}");
+
+    let FullEvent::InteractionCreate { interaction } = event else {
+        return Box::pin(async { Ok(()) });
+    };
+
+    if let Interaction::Component(interaction) = interaction {
+        if interaction.message.author.id == ctx.bot_id {
+            log::debug!("Interaction received: {interaction:?}");
+
+            let create_value = || {
+                let message = ComponentMessage {
+                    interaction: interaction.clone(),
+                    shard: serenity_ctx.shard.clone(),
+                };
+
+                Arc::new(message)
+            };
+
+            ctx.user_data
+                .send_message_with::<Arc<ComponentMessage>, _>(create_value);
+        }
+    }
+
+    Box::pin(async move { Ok(()) })
+}
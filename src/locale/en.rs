@@ -0,0 +1,570 @@
+use crate::{
+    data::{
+        escalator_input::{EscalatorInput, InputError},
+        settings::{Access, AnnouncementMode, GuildSettings, InvalidValue, Setting},
+        status::Status,
+    },
+    generate::REPORT_EMOJI,
+    prelude::*,
+};
+
+use itertools::Itertools;
+use poise::ChoiceParameter;
+
+use super::{Catalog, Escalators, WebhookDelivery};
+
+pub struct English;
+
+impl Catalog for English {
+    fn database_error(&self) -> &'static str {
+        "A database error ocurred."
+    }
+
+    fn guild_only(&self, command: &str) -> String {
+        format!("`{command}` must be used in a guild.")
+    }
+
+    fn missing_access(&self, required: Access) -> &'static str {
+        match required {
+            Access::Admin => {
+                "Only the admin role, or members with the Manage Server permission, can use this command."
+            }
+            Access::Moderator => {
+                "Only the moderator and admin roles, or members with the Manage Server permission, \
+                can use this command."
+            }
+        }
+    }
+
+    fn processing(&self) -> &'static str {
+        "Processing..."
+    }
+
+    fn timed_out(&self) -> &'static str {
+        "Interaction timed out, try again..."
+    }
+
+    fn timeout_message(&self, timestamp: &str) -> String {
+        format!("This menu will timeout {timestamp}")
+    }
+
+    // the doc comments on the commands are already in English
+    fn command_description(&self, _command: &str) -> Option<&'static str> {
+        None
+    }
+
+    fn parameter_description(&self, _command: &str, _parameter: &str) -> Option<&'static str> {
+        None
+    }
+
+    fn status_name(&self, status: Status, _count: usize) -> &'static str {
+        status.as_id_str()
+    }
+
+    fn status_label(&self, status: Status) -> &'static str {
+        match status {
+            Status::Open => "Open",
+            Status::Down => "Down",
+            Status::Blocked => "Blocked",
+        }
+    }
+
+    fn escalators_status(&self, escalators: Escalators, status: Status) -> String {
+        let (noun, count) = match escalators {
+            Escalators::All => (String::from("`ALL` escalators"), 2),
+            Escalators::Many => (String::from("`MANY` escalators"), 2),
+            Escalators::Listed(escalators) => (nounify_escalators(escalators), escalators.len()),
+        };
+
+        let is_are = if count == 1 { "is" } else { "are" };
+        let status = self.status_name(status, count);
+
+        format!("{noun} {is_are} `{status}`")
+    }
+
+    fn input_noun(&self, input: &EscalatorInput) -> String {
+        match *input {
+            EscalatorInput::All => String::from("`ALL` escalators"),
+            EscalatorInput::Pair(a, b) => {
+                let lower = a.min(b);
+                let upper = a.max(b);
+                format!("the `{lower}-{upper}` and `{upper}-{lower}` escalators")
+            }
+            EscalatorInput::Direct(lower, upper) => format!("the `{lower}-{upper}` escalator"),
+        }
+    }
+
+    fn input_short_noun(&self, input: &EscalatorInput) -> String {
+        match *input {
+            EscalatorInput::All => String::from("ALL escalators"),
+            EscalatorInput::Pair(a, b) => {
+                let lower = a.min(b);
+                let upper = a.max(b);
+                format!("{lower}-{upper} and {upper}-{lower}")
+            }
+            EscalatorInput::Direct(lower, upper) => format!("{lower}-{upper}"),
+        }
+    }
+
+    fn input_error(&self, err: InputError) -> String {
+        err.to_string()
+    }
+
+    fn not_an_escalator(&self, floors: EscalatorFloors) -> String {
+        format!("`{floors}` is not an escalator.")
+    }
+
+    fn gist_title(&self) -> &'static str {
+        "Here's the gist..."
+    }
+
+    fn updated(&self, timestamp: &str) -> String {
+        format!(" *(updated {timestamp})*")
+    }
+
+    fn user_report(&self, reporter: Option<serenity::UserId>, input: &EscalatorInput) -> String {
+        let reporter = reporter
+            .map(|id| format!("<@{id}>"))
+            .unwrap_or_else(|| String::from("an unknown user"));
+
+        format!("{reporter} reported {}.", self.input_noun(input))
+    }
+
+    fn more_reports(&self, count: usize) -> String {
+        format!("*(...and {count} more)*")
+    }
+
+    fn recent_reports(&self) -> &'static str {
+        "Recent reports (newest first)"
+    }
+
+    fn offline_notice(&self, from: &str, to: &str) -> String {
+        format!(
+            "⚠️ The bot was offline from {from} to {to}, \
+            so any reports made in the meantime were missed and the statuses may be stale."
+        )
+    }
+
+    fn summary_title(&self) -> &'static str {
+        "Good morning! Here's the gist..."
+    }
+
+    fn changes_since_summary(&self) -> &'static str {
+        "Changes since the last summary"
+    }
+
+    fn no_changes(&self) -> &'static str {
+        "*No changes.*"
+    }
+
+    fn earlier_changes(&self, count: usize) -> String {
+        format!("*(...and {count} earlier)*")
+    }
+
+    fn most_reliable_this_week(&self) -> &'static str {
+        "Most reliable this week"
+    }
+
+    fn least_reliable_this_week(&self) -> &'static str {
+        "Least reliable this week"
+    }
+
+    fn reliability_title(&self, days: u16) -> String {
+        if days == 1 {
+            String::from("Reliability over the past day")
+        } else {
+            format!("Reliability over the past {days} days")
+        }
+    }
+
+    fn most_reliable(&self) -> &'static str {
+        "Most reliable"
+    }
+
+    fn least_reliable(&self) -> &'static str {
+        "Least reliable"
+    }
+
+    fn outages_reported(&self) -> &'static str {
+        "Outages reported"
+    }
+
+    fn weekdays(&self) -> [&'static str; 7] {
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+    }
+
+    fn outages_chart_title(&self) -> &'static str {
+        "Outages by weekday and hour"
+    }
+
+    fn uptime_chart_title(&self) -> &'static str {
+        "Uptime by escalator"
+    }
+
+    fn floor_label(&self, floor: u8) -> String {
+        format!("Floor {floor}")
+    }
+
+    fn statuses_title(&self) -> &'static str {
+        "Escalator Statuses"
+    }
+
+    fn report_button(&self) -> &'static str {
+        "Report"
+    }
+
+    fn info_title(&self) -> &'static str {
+        "What The Heck Does All Of This Mean?"
+    }
+
+    fn info_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "Escalators",
+                indoc::indoc! {"
+                    Every escalator can be identified by their starting and ending floors in the `#-#` format.
+                    For example, the escalator for going from the 4th floor to the 2nd floor has the label `4-2`.
+                "}
+                .to_owned(),
+            ),
+            (
+                "Statuses",
+                indoc::formatdoc! {"
+                    Next to each escalator is an emoji representing their current status.
+                    There are three different states it could be in:
+                    `{open} OPEN` - the escalator is in working condition.
+                    `{down} DOWN` - the escalator isn't moving, but can be walked on.
+                    `{blocked} BLOCKED` - the escalator is under maintenance and can't be walked on.
+                    ",
+                    open = Status::Open.emoji(),
+                    down = Status::Down.emoji(),
+                    blocked = Status::Blocked.emoji(),
+                },
+            ),
+            (
+                "Reports",
+                indoc::formatdoc! {"
+                    You can report a status of an escalator by clicking the `{report} Report` button.
+                    Once in the report menu, you can specify the escalator by selecting \
+                    the starting and ending floors, and then you can select the status \
+                    and submit the report.
+                    You can also select `Pair` to report both the up and down escalators \
+                    or `All` if every escalator goes down due to an emergency (eg. a fire).
+                    ",
+                    report = REPORT_EMOJI,
+                },
+            ),
+        ]
+    }
+
+    fn reports_locked(&self, settings: &GuildSettings) -> String {
+        let days = if settings.lock_weekends {
+            " on weekdays"
+        } else {
+            ""
+        };
+
+        format!(
+            "Reports are only open from {}:00 to {}:00{days}.",
+            settings.reports_open_hour, settings.reports_close_hour,
+        )
+    }
+
+    fn pair_button(&self) -> &'static str {
+        "Pair"
+    }
+
+    fn all_button(&self) -> &'static str {
+        "All"
+    }
+
+    fn select_escalators(&self) -> &'static str {
+        "Select Escalator(s)"
+    }
+
+    fn select_status(&self) -> &'static str {
+        "Select Status"
+    }
+
+    fn submit_report(&self, input: &EscalatorInput) -> String {
+        format!("Report {}!", self.input_short_noun(input))
+    }
+
+    fn reported(&self, input: &EscalatorInput) -> String {
+        format!("Successfully reported {}.", self.input_noun(input))
+    }
+
+    fn menu_removed(&self, channel_id: serenity::ChannelId, guild_name: &str) -> String {
+        format!(
+            "The report menu in <#{channel_id}> (**{guild_name}**) was deleted and couldn't be recreated, \
+            so it has been removed. Use `/menu init` to create a new one."
+        )
+    }
+
+    fn save_list_button(&self) -> &'static str {
+        "Save List"
+    }
+
+    fn watchlist_updated(&self) -> &'static str {
+        "Watchlist updated."
+    }
+
+    fn floor_watched(&self, floor: u8) -> String {
+        format!("You will now be alerted when any escalator on floor `{floor}` gets reported.")
+    }
+
+    fn floor_unwatched(&self, floor: u8) -> String {
+        format!("You will no longer be alerted about floor `{floor}`.")
+    }
+
+    fn watch_list_title(&self) -> &'static str {
+        "Your Watch List"
+    }
+
+    fn watched_floors_title(&self) -> &'static str {
+        "Your Watched Floors"
+    }
+
+    fn menu_initialized(&self) -> &'static str {
+        "Initialized report menu."
+    }
+
+    fn no_menus(&self) -> &'static str {
+        "No report menus exist in this server."
+    }
+
+    fn menus_title(&self) -> &'static str {
+        "Report Menus"
+    }
+
+    fn all_escalators(&self) -> &'static str {
+        "all escalators"
+    }
+
+    fn menu_missing_permissions(&self) -> &'static str {
+        "⚠️ Missing permissions, the menu can't be updated."
+    }
+
+    fn menu_sync_failed(&self, err: &str) -> String {
+        format!("⚠️ Last sync failed: {err}")
+    }
+
+    fn menu_syncing(&self) -> &'static str {
+        "✅ Syncing normally."
+    }
+
+    fn invalid_message_id(&self) -> &'static str {
+        "Invalid message ID."
+    }
+
+    fn no_matching_menu(&self) -> &'static str {
+        "No matching report menu exists in this server."
+    }
+
+    fn menus_deleted(&self, count: usize) -> String {
+        if count == 1 {
+            String::from("Deleted 1 report menu.")
+        } else {
+            format!("Deleted {count} report menus.")
+        }
+    }
+
+    fn webhook_added(&self, webhook_id: i32) -> String {
+        format!("Added webhook `{webhook_id}`, it'll be sent every matching report.")
+    }
+
+    fn invalid_webhook_url(&self) -> &'static str {
+        "Invalid URL, it has to start with `http://` or `https://`."
+    }
+
+    fn private_webhook_url(&self) -> &'static str {
+        "That URL isn't on the public internet, so reports can't be sent to it."
+    }
+
+    fn too_many_webhooks(&self, max: usize) -> String {
+        format!("This server already has the maximum of {max} webhooks.")
+    }
+
+    fn no_webhooks(&self) -> &'static str {
+        "No webhooks exist in this server."
+    }
+
+    fn webhooks_title(&self) -> &'static str {
+        "Webhooks"
+    }
+
+    fn webhook_signed(&self) -> &'static str {
+        "🔒 signed"
+    }
+
+    fn webhook_delivery(&self, delivery: Option<&WebhookDelivery>) -> String {
+        let Some(delivery) = delivery else {
+            return String::from("No reports have been sent yet.");
+        };
+
+        let timestamp = &delivery.timestamp;
+
+        if delivery.delivered {
+            format!("✅ Last delivered {timestamp}.")
+        } else {
+            format!("⚠️ Last delivery failed {timestamp}.")
+        }
+    }
+
+    fn no_matching_webhook(&self) -> &'static str {
+        "No matching webhook exists in this server."
+    }
+
+    fn webhook_removed(&self) -> &'static str {
+        "Removed webhook."
+    }
+
+    fn time_ago(&self, elapsed: chrono::TimeDelta) -> String {
+        let (count, unit) = match elapsed {
+            _ if elapsed.num_minutes() < 1 => return String::from("just now"),
+            _ if elapsed.num_hours() < 1 => (elapsed.num_minutes(), "minute"),
+            _ if elapsed.num_days() < 1 => (elapsed.num_hours(), "hour"),
+            _ => (elapsed.num_days(), "day"),
+        };
+
+        let plural = if count == 1 { "" } else { "s" };
+
+        format!("{count} {unit}{plural} ago")
+    }
+
+    fn page_refresh_note(&self) -> &'static str {
+        "This page refreshes every minute."
+    }
+
+    fn feed_title(&self) -> &'static str {
+        "Escalator Reports"
+    }
+
+    fn history_channel_set(&self, channel_id: serenity::ChannelId) -> String {
+        format!("Set history channel to <#{channel_id}>.")
+    }
+
+    fn history_channel_removed(&self) -> &'static str {
+        "Removed history channel"
+    }
+
+    fn announcement_mode(&self, mode: AnnouncementMode) -> &'static str {
+        match mode {
+            AnnouncementMode::Post => "Post",
+            AnnouncementMode::Live => "Live",
+        }
+    }
+
+    fn setting_name(&self, setting: Setting) -> &'static str {
+        setting.name()
+    }
+
+    fn config_view(&self, settings: &GuildSettings) -> String {
+        indoc::formatdoc! {"
+            **Announcements:**
+            Announcement delay: `{delay}s`
+            Max reports: `{max_reports}`
+            Crosspost: `{crosspost}`
+            Include gist: `{include_gist}`
+            Announcement mode: `{mode}`
+
+            **Summaries:**
+            Daily summary: `{daily}`
+            Weekly summary: `{weekly}`
+
+            **Reports:**
+            Reports open hour: `{open}:00`
+            Reports close hour: `{close}:00`
+            Lock weekends: `{lock_weekends}`
+
+            **Menus:**
+            Menu diagram: `{diagram}`
+            Menu ages: `{ages}`
+
+            **Permissions:**
+            Admin role: {admin_role}
+            Moderator role: {moderator_role}
+
+            **Language:** `{language} ({code})`",
+            delay = settings.delay.as_secs(),
+            max_reports = settings.max_reports_displayed,
+            crosspost = settings.crosspost,
+            include_gist = settings.include_gist,
+            mode = self.announcement_mode(settings.mode),
+            daily = settings.daily_summary,
+            weekly = settings.weekly_summary,
+            open = settings.reports_open_hour,
+            close = settings.reports_close_hour,
+            lock_weekends = settings.lock_weekends,
+            diagram = settings.show_diagram,
+            ages = settings.show_ages,
+            admin_role = role_mention(settings.admin_role),
+            moderator_role = role_mention(settings.moderator_role),
+            language = settings.locale.name(),
+            code = settings.locale.code(),
+        }
+    }
+
+    fn setting_updated(&self, setting: Setting) -> String {
+        format!("Updated `{}`.", self.setting_name(setting))
+    }
+
+    fn setting_reset(&self, setting: Option<Setting>) -> String {
+        match setting {
+            Some(setting) => format!("Reset `{}` to its default.", self.setting_name(setting)),
+            None => String::from("Reset every setting to its default."),
+        }
+    }
+
+    fn invalid_value(&self, setting: Setting, err: InvalidValue) -> String {
+        let name = self.setting_name(setting);
+
+        match err {
+            InvalidValue::Number { min, max } => {
+                format!("`{name}` must be a whole number from {min} to {max}.")
+            }
+            InvalidValue::Bool => format!("`{name}` must be `yes` or `no`."),
+            InvalidValue::Role => format!("`{name}` must be a role mention or ID."),
+            InvalidValue::Choice(choices) => {
+                let choices = choices
+                    .iter()
+                    .map(|choice| format!("`{choice}`"))
+                    .join(", ");
+                format!("`{name}` must be one of {choices}.")
+            }
+        }
+    }
+}
+
+fn role_mention(role: Option<serenity::RoleId>) -> String {
+    role.map(|id| format!("<@&{id}>"))
+        .unwrap_or_else(|| String::from("`none`"))
+}
+
+/// Turn a collection of Escalators into a format that could be put into a message.
+fn nounify_escalators(escalators: &[EscalatorFloors]) -> String {
+    if escalators.is_empty() {
+        return String::from("`NO` escalators");
+    }
+
+    if escalators.len() == 1 {
+        return format!("The `{}` escalator", escalators[0]);
+    }
+
+    // how many escalators there are not including the first and last
+    let mid_count = escalators.len() - 2;
+
+    let mut escalators = escalators
+        .iter()
+        .copied()
+        .map(|escalator| format!("`{escalator}`"));
+    let mut noun = format!("The {}", escalators.next().unwrap());
+
+    for escalator in escalators.by_ref().take(mid_count) {
+        noun.push_str(&format!(", {escalator}"));
+    }
+
+    noun.push_str(&format!(", and {} escalators", escalators.next().unwrap()));
+
+    noun
+}
@@ -0,0 +1,669 @@
+use crate::{
+    data::{
+        escalator_input::{EscalatorInput, InputError},
+        settings::{Access, AnnouncementMode, GuildSettings, InvalidValue, Setting},
+        status::Status,
+    },
+    generate::REPORT_EMOJI,
+    prelude::*,
+};
+
+use itertools::Itertools;
+use poise::ChoiceParameter;
+
+use super::{Catalog, Escalators, WebhookDelivery};
+
+pub struct Spanish;
+
+impl Catalog for Spanish {
+    fn database_error(&self) -> &'static str {
+        "Ocurrió un error en la base de datos."
+    }
+
+    fn guild_only(&self, command: &str) -> String {
+        format!("`{command}` debe usarse en un servidor.")
+    }
+
+    fn missing_access(&self, required: Access) -> &'static str {
+        match required {
+            Access::Admin => {
+                "Solo el rol de administrador, o los miembros con el permiso Gestionar servidor, \
+                pueden usar este comando."
+            }
+            Access::Moderator => {
+                "Solo los roles de moderador y administrador, o los miembros con el permiso \
+                Gestionar servidor, pueden usar este comando."
+            }
+        }
+    }
+
+    fn processing(&self) -> &'static str {
+        "Procesando..."
+    }
+
+    fn timed_out(&self) -> &'static str {
+        "Se agotó el tiempo de espera, inténtalo de nuevo..."
+    }
+
+    fn timeout_message(&self, timestamp: &str) -> String {
+        format!("Este menú expirará {timestamp}")
+    }
+
+    fn command_description(&self, command: &str) -> Option<&'static str> {
+        let description = match command {
+            "menu init" => "(solo moderadores) Crea un menú de estados en el canal actual.",
+            "menu status" => {
+                "(solo moderadores) Muestra los menús de estados del servidor y si se pueden sincronizar."
+            }
+            "menu clear" => {
+                "(solo moderadores) Elimina un menú de estados, o todos los del canal actual."
+            }
+            "history set" => "(solo administradores) Establece el canal de anuncios del servidor.",
+            "history remove" => "(solo administradores) Quita el canal de anuncios del servidor.",
+            "alerts edit" => {
+                "Edita tu lista y recibe alertas cuando se reporte cualquier escalera en ella."
+            }
+            "alerts floor" => {
+                "Activa o desactiva las alertas de las escaleras que empiezan o terminan en un piso."
+            }
+            "alerts list" => "Revisa tu lista de alertas.",
+            "stats" => "Muestra qué tan confiables han sido las escaleras recientemente.",
+            "gist" => "Muestra un resumen del estado de las escaleras.",
+            "config view" => "(solo administradores) Muestra la configuración del servidor.",
+            "config set" => "(solo administradores) Cambia un ajuste del servidor.",
+            "config reset" => {
+                "(solo administradores) Restablece un ajuste del servidor, o todos, a su valor por defecto."
+            }
+            "webhooks add" => {
+                "(solo administradores) Registra una URL a la que se enviará cada reporte de estado."
+            }
+            "webhooks list" => {
+                "(solo administradores) Muestra los webhooks del servidor y cómo fue su último envío."
+            }
+            "webhooks remove" => "(solo administradores) Elimina un webhook del servidor.",
+            _ => return None,
+        };
+
+        Some(description)
+    }
+
+    fn parameter_description(&self, command: &str, parameter: &str) -> Option<&'static str> {
+        let description = match (command, parameter) {
+            ("menu init", "escalators") => {
+                "Solo mostrar estas escaleras, ej. `2-4 3/5` (por defecto todas)"
+            }
+            ("menu clear", "message_id") => {
+                "El ID del mensaje del menú a eliminar (ver `/menu status`)"
+            }
+            ("alerts floor", "floor") => "El piso a vigilar (o dejar de vigilar)",
+            ("stats", "days") => "Cuántos días revisar (por defecto 7)",
+            ("config set", "setting") => "El ajuste a cambiar",
+            ("config set", "value") => {
+                "El nuevo valor, ej. `120`, `sí`, `live` o `es` (ver `/config view`)"
+            }
+            ("config reset", "setting") => "El ajuste a restablecer (por defecto todos)",
+            ("webhooks add", "url") => "La URL a la que enviar los reportes con POST",
+            ("webhooks add", "secret") => {
+                "Un secreto para firmar los reportes, enviado en el encabezado `X-Escalator-Signature`"
+            }
+            ("webhooks add", "escalators") => {
+                "Solo enviar reportes de estas escaleras, ej. `2-4 3/5` (por defecto todas)"
+            }
+            ("webhooks remove", "webhook_id") => {
+                "El ID del webhook a eliminar (ver `/webhooks list`)"
+            }
+            _ => return None,
+        };
+
+        Some(description)
+    }
+
+    fn status_name(&self, status: Status, count: usize) -> &'static str {
+        match (status, count == 1) {
+            (Status::Open, true) => "ABIERTA",
+            (Status::Open, false) => "ABIERTAS",
+            (Status::Down, true) => "AVERIADA",
+            (Status::Down, false) => "AVERIADAS",
+            (Status::Blocked, true) => "BLOQUEADA",
+            (Status::Blocked, false) => "BLOQUEADAS",
+        }
+    }
+
+    fn status_label(&self, status: Status) -> &'static str {
+        match status {
+            Status::Open => "Abierta",
+            Status::Down => "Averiada",
+            Status::Blocked => "Bloqueada",
+        }
+    }
+
+    fn escalators_status(&self, escalators: Escalators, status: Status) -> String {
+        let (noun, count) = match escalators {
+            Escalators::All => (String::from("`TODAS` las escaleras"), 2),
+            Escalators::Many => (String::from("`MUCHAS` escaleras"), 2),
+            Escalators::Listed(escalators) => (nounify_escalators(escalators), escalators.len()),
+        };
+
+        // "ninguna" is singular, even though it refers to no escalators
+        let count = count.max(1);
+
+        let esta = if count == 1 { "está" } else { "están" };
+        let status = self.status_name(status, count);
+
+        format!("{noun} {esta} `{status}`")
+    }
+
+    fn input_noun(&self, input: &EscalatorInput) -> String {
+        match *input {
+            EscalatorInput::All => String::from("`TODAS` las escaleras"),
+            EscalatorInput::Pair(a, b) => {
+                let lower = a.min(b);
+                let upper = a.max(b);
+                format!("las escaleras `{lower}-{upper}` y `{upper}-{lower}`")
+            }
+            EscalatorInput::Direct(lower, upper) => format!("la escalera `{lower}-{upper}`"),
+        }
+    }
+
+    fn input_short_noun(&self, input: &EscalatorInput) -> String {
+        match *input {
+            EscalatorInput::All => String::from("TODAS las escaleras"),
+            EscalatorInput::Pair(a, b) => {
+                let lower = a.min(b);
+                let upper = a.max(b);
+                format!("{lower}-{upper} y {upper}-{lower}")
+            }
+            EscalatorInput::Direct(lower, upper) => format!("{lower}-{upper}"),
+        }
+    }
+
+    fn input_error(&self, err: InputError) -> String {
+        match err {
+            InputError::UnknownFormat => {
+                String::from("Las escaleras deben tener el formato `#-#` o `#/#`")
+            }
+            InputError::InvalidFloor(floor) => format!("`{floor}` no es un piso válido"),
+            InputError::InvalidEscalator(start, end) => {
+                format!("`{start}-{end}` no es una escalera válida")
+            }
+        }
+    }
+
+    fn not_an_escalator(&self, floors: EscalatorFloors) -> String {
+        format!("`{floors}` no es una escalera.")
+    }
+
+    fn gist_title(&self) -> &'static str {
+        "Aquí está el resumen..."
+    }
+
+    fn updated(&self, timestamp: &str) -> String {
+        format!(" *(actualizado {timestamp})*")
+    }
+
+    fn user_report(&self, reporter: Option<serenity::UserId>, input: &EscalatorInput) -> String {
+        let reporter = reporter
+            .map(|id| format!("<@{id}>"))
+            .unwrap_or_else(|| String::from("un usuario desconocido"));
+
+        format!("{reporter} reportó {}.", self.input_noun(input))
+    }
+
+    fn more_reports(&self, count: usize) -> String {
+        format!("*(...y {count} más)*")
+    }
+
+    fn recent_reports(&self) -> &'static str {
+        "Reportes recientes (los más nuevos primero)"
+    }
+
+    fn offline_notice(&self, from: &str, to: &str) -> String {
+        format!(
+            "⚠️ El bot estuvo desconectado desde {from} hasta {to}, \
+            así que los reportes hechos mientras tanto se perdieron y los estados podrían estar desactualizados."
+        )
+    }
+
+    fn summary_title(&self) -> &'static str {
+        "¡Buenos días! Aquí está el resumen..."
+    }
+
+    fn changes_since_summary(&self) -> &'static str {
+        "Cambios desde el último resumen"
+    }
+
+    fn no_changes(&self) -> &'static str {
+        "*Sin cambios.*"
+    }
+
+    fn earlier_changes(&self, count: usize) -> String {
+        if count == 1 {
+            String::from("*(...y 1 anterior)*")
+        } else {
+            format!("*(...y {count} anteriores)*")
+        }
+    }
+
+    fn most_reliable_this_week(&self) -> &'static str {
+        "Más confiables esta semana"
+    }
+
+    fn least_reliable_this_week(&self) -> &'static str {
+        "Menos confiables esta semana"
+    }
+
+    fn reliability_title(&self, days: u16) -> String {
+        if days == 1 {
+            String::from("Confiabilidad del último día")
+        } else {
+            format!("Confiabilidad de los últimos {days} días")
+        }
+    }
+
+    fn most_reliable(&self) -> &'static str {
+        "Más confiables"
+    }
+
+    fn least_reliable(&self) -> &'static str {
+        "Menos confiables"
+    }
+
+    fn outages_reported(&self) -> &'static str {
+        "Fallas reportadas"
+    }
+
+    fn weekdays(&self) -> [&'static str; 7] {
+        ["Lun", "Mar", "Mié", "Jue", "Vie", "Sáb", "Dom"]
+    }
+
+    fn outages_chart_title(&self) -> &'static str {
+        "Fallas por día y hora"
+    }
+
+    fn uptime_chart_title(&self) -> &'static str {
+        "Disponibilidad por escalera"
+    }
+
+    fn floor_label(&self, floor: u8) -> String {
+        format!("Piso {floor}")
+    }
+
+    fn statuses_title(&self) -> &'static str {
+        "Estado de las Escaleras"
+    }
+
+    fn report_button(&self) -> &'static str {
+        "Reportar"
+    }
+
+    fn info_title(&self) -> &'static str {
+        "¿Qué Rayos Significa Todo Esto?"
+    }
+
+    fn info_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "Escaleras",
+                indoc::indoc! {"
+                    Cada escalera se identifica por su piso de inicio y su piso final en el formato `#-#`.
+                    Por ejemplo, la escalera que va del 4º piso al 2º piso tiene la etiqueta `4-2`.
+                "}
+                .to_owned(),
+            ),
+            (
+                "Estados",
+                indoc::formatdoc! {"
+                    Junto a cada escalera hay un emoji que representa su estado actual.
+                    Hay tres estados posibles:
+                    `{open} ABIERTA` - la escalera funciona correctamente.
+                    `{down} AVERIADA` - la escalera no se mueve, pero se puede caminar por ella.
+                    `{blocked} BLOQUEADA` - la escalera está en mantenimiento y no se puede usar.
+                    ",
+                    open = Status::Open.emoji(),
+                    down = Status::Down.emoji(),
+                    blocked = Status::Blocked.emoji(),
+                },
+            ),
+            (
+                "Reportes",
+                indoc::formatdoc! {"
+                    Puedes reportar el estado de una escalera con el botón `{report} Reportar`.
+                    En el menú de reporte, elige la escalera seleccionando \
+                    el piso de inicio y el piso final, y luego selecciona el estado \
+                    y envía el reporte.
+                    También puedes seleccionar `Par` para reportar las escaleras de subida y bajada \
+                    o `Todas` si todas las escaleras fallan por una emergencia (ej. un incendio).
+                    ",
+                    report = REPORT_EMOJI,
+                },
+            ),
+        ]
+    }
+
+    fn reports_locked(&self, settings: &GuildSettings) -> String {
+        let days = if settings.lock_weekends {
+            " entre semana"
+        } else {
+            ""
+        };
+
+        format!(
+            "Los reportes solo están abiertos de {}:00 a {}:00{days}.",
+            settings.reports_open_hour, settings.reports_close_hour,
+        )
+    }
+
+    fn pair_button(&self) -> &'static str {
+        "Par"
+    }
+
+    fn all_button(&self) -> &'static str {
+        "Todas"
+    }
+
+    fn select_escalators(&self) -> &'static str {
+        "Selecciona Escalera(s)"
+    }
+
+    fn select_status(&self) -> &'static str {
+        "Selecciona Estado"
+    }
+
+    fn submit_report(&self, input: &EscalatorInput) -> String {
+        format!("¡Reportar {}!", self.input_short_noun(input))
+    }
+
+    fn reported(&self, input: &EscalatorInput) -> String {
+        format!("Se reportó {} con éxito.", self.input_noun(input))
+    }
+
+    fn menu_removed(&self, channel_id: serenity::ChannelId, guild_name: &str) -> String {
+        format!(
+            "El menú de reportes en <#{channel_id}> (**{guild_name}**) fue borrado y no se pudo recrear, \
+            así que se eliminó. Usa `/menu init` para crear uno nuevo."
+        )
+    }
+
+    fn save_list_button(&self) -> &'static str {
+        "Guardar Lista"
+    }
+
+    fn watchlist_updated(&self) -> &'static str {
+        "Lista de alertas actualizada."
+    }
+
+    fn floor_watched(&self, floor: u8) -> String {
+        format!("Ahora recibirás alertas cuando se reporte cualquier escalera del piso `{floor}`.")
+    }
+
+    fn floor_unwatched(&self, floor: u8) -> String {
+        format!("Ya no recibirás alertas sobre el piso `{floor}`.")
+    }
+
+    fn watch_list_title(&self) -> &'static str {
+        "Tu Lista de Alertas"
+    }
+
+    fn watched_floors_title(&self) -> &'static str {
+        "Tus Pisos Vigilados"
+    }
+
+    fn menu_initialized(&self) -> &'static str {
+        "Menú de reportes creado."
+    }
+
+    fn no_menus(&self) -> &'static str {
+        "No hay menús de reportes en este servidor."
+    }
+
+    fn menus_title(&self) -> &'static str {
+        "Menús de Reportes"
+    }
+
+    fn all_escalators(&self) -> &'static str {
+        "todas las escaleras"
+    }
+
+    fn menu_missing_permissions(&self) -> &'static str {
+        "⚠️ Faltan permisos, el menú no se puede actualizar."
+    }
+
+    fn menu_sync_failed(&self, err: &str) -> String {
+        format!("⚠️ La última sincronización falló: {err}")
+    }
+
+    fn menu_syncing(&self) -> &'static str {
+        "✅ Se sincroniza normalmente."
+    }
+
+    fn invalid_message_id(&self) -> &'static str {
+        "ID de mensaje inválido."
+    }
+
+    fn no_matching_menu(&self) -> &'static str {
+        "No existe ningún menú de reportes que coincida en este servidor."
+    }
+
+    fn menus_deleted(&self, count: usize) -> String {
+        if count == 1 {
+            String::from("Se eliminó 1 menú de reportes.")
+        } else {
+            format!("Se eliminaron {count} menús de reportes.")
+        }
+    }
+
+    fn webhook_added(&self, webhook_id: i32) -> String {
+        format!("Se agregó el webhook `{webhook_id}`, se le enviará cada reporte que coincida.")
+    }
+
+    fn invalid_webhook_url(&self) -> &'static str {
+        "URL inválida, tiene que empezar con `http://` o `https://`."
+    }
+
+    fn private_webhook_url(&self) -> &'static str {
+        "Esa URL no está en internet pública, así que no se le pueden enviar reportes."
+    }
+
+    fn too_many_webhooks(&self, max: usize) -> String {
+        format!("Este servidor ya tiene el máximo de {max} webhooks.")
+    }
+
+    fn no_webhooks(&self) -> &'static str {
+        "No hay webhooks en este servidor."
+    }
+
+    fn webhooks_title(&self) -> &'static str {
+        "Webhooks"
+    }
+
+    fn webhook_signed(&self) -> &'static str {
+        "🔒 firmado"
+    }
+
+    fn webhook_delivery(&self, delivery: Option<&WebhookDelivery>) -> String {
+        let Some(delivery) = delivery else {
+            return String::from("Todavía no se ha enviado ningún reporte.");
+        };
+
+        let timestamp = &delivery.timestamp;
+
+        if delivery.delivered {
+            format!("✅ Último envío {timestamp}.")
+        } else {
+            format!("⚠️ El último envío falló {timestamp}.")
+        }
+    }
+
+    fn no_matching_webhook(&self) -> &'static str {
+        "No existe ningún webhook que coincida en este servidor."
+    }
+
+    fn webhook_removed(&self) -> &'static str {
+        "Se eliminó el webhook."
+    }
+
+    fn time_ago(&self, elapsed: chrono::TimeDelta) -> String {
+        let (count, unit) = match elapsed {
+            _ if elapsed.num_minutes() < 1 => return String::from("justo ahora"),
+            _ if elapsed.num_hours() < 1 => (elapsed.num_minutes(), "minuto"),
+            _ if elapsed.num_days() < 1 => (elapsed.num_hours(), "hora"),
+            _ => (elapsed.num_days(), "día"),
+        };
+
+        let plural = if count == 1 { "" } else { "s" };
+
+        format!("hace {count} {unit}{plural}")
+    }
+
+    fn page_refresh_note(&self) -> &'static str {
+        "Esta página se actualiza cada minuto."
+    }
+
+    fn feed_title(&self) -> &'static str {
+        "Reportes de las Escaleras"
+    }
+
+    fn history_channel_set(&self, channel_id: serenity::ChannelId) -> String {
+        format!("El canal de anuncios ahora es <#{channel_id}>.")
+    }
+
+    fn history_channel_removed(&self) -> &'static str {
+        "Se quitó el canal de anuncios"
+    }
+
+    fn announcement_mode(&self, mode: AnnouncementMode) -> &'static str {
+        match mode {
+            AnnouncementMode::Post => "Publicar",
+            AnnouncementMode::Live => "En vivo",
+        }
+    }
+
+    fn setting_name(&self, setting: Setting) -> &'static str {
+        match setting {
+            Setting::AnnounceDelay => "Espera de anuncios",
+            Setting::MaxReports => "Máximo de reportes",
+            Setting::Crosspost => "Publicar en seguidores",
+            Setting::IncludeGist => "Incluir resumen",
+            Setting::Mode => "Modo de anuncios",
+            Setting::DailySummary => "Resumen diario",
+            Setting::WeeklySummary => "Resumen semanal",
+            Setting::Language => "Idioma",
+            Setting::ReportsOpen => "Apertura de reportes",
+            Setting::ReportsClose => "Cierre de reportes",
+            Setting::LockWeekends => "Bloquear fines de semana",
+            Setting::MenuDiagram => "Diagrama del menú",
+            Setting::MenuAges => "Antigüedad en el menú",
+            Setting::AdminRole => "Rol de administrador",
+            Setting::ModeratorRole => "Rol de moderador",
+        }
+    }
+
+    fn config_view(&self, settings: &GuildSettings) -> String {
+        indoc::formatdoc! {"
+            **Anuncios:**
+            Espera de anuncios: `{delay}s`
+            Máximo de reportes: `{max_reports}`
+            Publicar en seguidores: `{crosspost}`
+            Incluir resumen: `{include_gist}`
+            Modo de anuncios: `{mode}`
+
+            **Resúmenes:**
+            Resumen diario: `{daily}`
+            Resumen semanal: `{weekly}`
+
+            **Reportes:**
+            Apertura de reportes: `{open}:00`
+            Cierre de reportes: `{close}:00`
+            Bloquear fines de semana: `{lock_weekends}`
+
+            **Menús:**
+            Diagrama del menú: `{diagram}`
+            Antigüedad en el menú: `{ages}`
+
+            **Permisos:**
+            Rol de administrador: {admin_role}
+            Rol de moderador: {moderator_role}
+
+            **Idioma:** `{language} ({code})`",
+            delay = settings.delay.as_secs(),
+            max_reports = settings.max_reports_displayed,
+            crosspost = yes_no(settings.crosspost),
+            include_gist = yes_no(settings.include_gist),
+            mode = self.announcement_mode(settings.mode),
+            daily = yes_no(settings.daily_summary),
+            weekly = yes_no(settings.weekly_summary),
+            open = settings.reports_open_hour,
+            close = settings.reports_close_hour,
+            lock_weekends = yes_no(settings.lock_weekends),
+            diagram = yes_no(settings.show_diagram),
+            ages = yes_no(settings.show_ages),
+            admin_role = role_mention(settings.admin_role),
+            moderator_role = role_mention(settings.moderator_role),
+            language = settings.locale.name(),
+            code = settings.locale.code(),
+        }
+    }
+
+    fn setting_updated(&self, setting: Setting) -> String {
+        format!("Se actualizó `{}`.", self.setting_name(setting))
+    }
+
+    fn setting_reset(&self, setting: Option<Setting>) -> String {
+        match setting {
+            Some(setting) => format!(
+                "Se restableció `{}` a su valor por defecto.",
+                self.setting_name(setting)
+            ),
+            None => String::from("Se restablecieron todos los ajustes a su valor por defecto."),
+        }
+    }
+
+    fn invalid_value(&self, setting: Setting, err: InvalidValue) -> String {
+        let name = self.setting_name(setting);
+
+        match err {
+            InvalidValue::Number { min, max } => {
+                format!("`{name}` debe ser un número entero de {min} a {max}.")
+            }
+            InvalidValue::Bool => format!("`{name}` debe ser `sí` o `no`."),
+            InvalidValue::Role => format!("`{name}` debe ser una mención o ID de un rol."),
+            InvalidValue::Choice(choices) => {
+                let choices = choices
+                    .iter()
+                    .map(|choice| format!("`{choice}`"))
+                    .join(", ");
+                format!("`{name}` debe ser uno de {choices}.")
+            }
+        }
+    }
+}
+
+fn role_mention(role: Option<serenity::RoleId>) -> String {
+    role.map(|id| format!("<@&{id}>"))
+        .unwrap_or_else(|| String::from("`ninguno`"))
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "sí"
+    } else {
+        "no"
+    }
+}
+
+/// Turn a collection of Escalators into a format that could be put into a message.
+fn nounify_escalators(escalators: &[EscalatorFloors]) -> String {
+    match escalators {
+        [] => String::from("`NINGUNA` escalera"),
+        [escalator] => format!("La escalera `{escalator}`"),
+        [rest @ .., last] => {
+            let rest = rest
+                .iter()
+                .map(|escalator| format!("`{escalator}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            format!("Las escaleras {rest} y `{last}`")
+        }
+    }
+}
@@ -0,0 +1,281 @@
+mod en;
+mod es;
+
+use crate::{
+    data::{
+        escalator_input::{EscalatorInput, InputError},
+        settings::{Access, AnnouncementMode, GuildSettings, InvalidValue, Setting},
+        status::Status,
+        store::SettingsStore,
+    },
+    prelude::*,
+};
+
+/// A language the bot's messages are available in.
+#[derive(sqlx::Type, poise::ChoiceParameter, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "locale", rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    #[name = "English"]
+    En,
+    #[name = "Español"]
+    Es,
+}
+
+/// Which escalators a message is about, so the catalog can pick the right plural forms.
+#[derive(Debug, Clone, Copy)]
+pub enum Escalators<'a> {
+    All,
+    Many,
+    Listed(&'a [EscalatorFloors]),
+}
+
+/// The most recent attempt to deliver a report to a webhook.
+/// Only whether it worked is shown, since the responses could leak what's behind the URL.
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub delivered: bool,
+    /// A Discord timestamp of when the attempt was made.
+    pub timestamp: String,
+}
+
+impl Locale {
+    pub const ALL: [Self; 2] = [Self::En, Self::Es];
+
+    pub fn catalog(self) -> &'static dyn Catalog {
+        match self {
+            Self::En => &en::English,
+            Self::Es => &es::Spanish,
+        }
+    }
+
+    /// The ISO 639-1 code of the language.
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Es => "es",
+        }
+    }
+
+    /// The Discord locales which use this language.
+    pub const fn discord_locales(self) -> &'static [&'static str] {
+        match self {
+            Self::En => &["en-US", "en-GB"],
+            Self::Es => &["es-ES", "es-419"],
+        }
+    }
+
+    /// Finds the language of a Discord locale (eg. `es-419`), if it's supported.
+    pub fn from_discord(locale: &str) -> Option<Self> {
+        let language = locale.split('-').next()?;
+
+        Self::ALL
+            .into_iter()
+            .find(|supported| supported.code() == language)
+    }
+
+    /// Picks the user's own locale if it's supported, and otherwise the guild's locale.
+    pub fn resolve(user_locale: Option<&str>, guild_locale: Option<Self>) -> Self {
+        user_locale
+            .and_then(Self::from_discord)
+            .or(guild_locale)
+            .unwrap_or_default()
+    }
+}
+
+/// Resolves the locale to respond to an interaction in,
+/// only loading the guild's locale if the user's locale isn't supported.
+pub async fn for_interaction(
+    store: &dyn SettingsStore,
+    user_locale: Option<&str>,
+    guild_id: Option<serenity::GuildId>,
+) -> Locale {
+    if let Some(locale) = user_locale.and_then(Locale::from_discord) {
+        return locale;
+    }
+
+    let Some(guild_id) = guild_id else {
+        return Locale::default();
+    };
+
+    match store.guild_locale(guild_id).await {
+        Ok(locale) => locale.unwrap_or_default(),
+        Err(err) => {
+            log::warn!("An error ocurred trying to load a guild's locale: {err}");
+            Locale::default()
+        }
+    }
+}
+
+/// Every user-facing message, implemented once per language.
+pub trait Catalog: Sync {
+    // -- General
+
+    fn database_error(&self) -> &'static str;
+    fn guild_only(&self, command: &str) -> String;
+    /// Explains who can use a command that requires the access.
+    fn missing_access(&self, required: Access) -> &'static str;
+    fn processing(&self) -> &'static str;
+    fn timed_out(&self) -> &'static str;
+    fn timeout_message(&self, timestamp: &str) -> String;
+
+    /// The description of a slash command, or `None` to use the doc comment.
+    fn command_description(&self, command: &str) -> Option<&'static str>;
+    /// The description of a slash command's parameter, or `None` to use the doc comment.
+    fn parameter_description(&self, command: &str, parameter: &str) -> Option<&'static str>;
+
+    // -- Escalators
+
+    /// The status as it's written after a number of escalators (eg. `OPEN`).
+    fn status_name(&self, status: Status, count: usize) -> &'static str;
+    /// The status as it's written on its own (eg. `Open`).
+    fn status_label(&self, status: Status) -> &'static str;
+    /// A sentence (without punctuation) stating the escalators have the status.
+    fn escalators_status(&self, escalators: Escalators, status: Status) -> String;
+    fn input_noun(&self, input: &EscalatorInput) -> String;
+    fn input_short_noun(&self, input: &EscalatorInput) -> String;
+    fn input_error(&self, err: InputError) -> String;
+    fn not_an_escalator(&self, floors: EscalatorFloors) -> String;
+
+    // -- Gist & Announcements
+
+    fn gist_title(&self) -> &'static str;
+    fn updated(&self, timestamp: &str) -> String;
+    fn user_report(&self, reporter: Option<serenity::UserId>, input: &EscalatorInput) -> String;
+    fn more_reports(&self, count: usize) -> String;
+    fn recent_reports(&self) -> &'static str;
+    /// Sent to announcement channels after the bot was offline, between the two timestamps.
+    fn offline_notice(&self, from: &str, to: &str) -> String;
+
+    // -- Summaries & Stats
+
+    fn summary_title(&self) -> &'static str;
+    fn changes_since_summary(&self) -> &'static str;
+    fn no_changes(&self) -> &'static str;
+    fn earlier_changes(&self, count: usize) -> String;
+    fn most_reliable_this_week(&self) -> &'static str;
+    fn least_reliable_this_week(&self) -> &'static str;
+    fn reliability_title(&self, days: u16) -> String;
+    fn most_reliable(&self) -> &'static str;
+    fn least_reliable(&self) -> &'static str;
+    fn outages_reported(&self) -> &'static str;
+
+    // -- Charts
+
+    fn weekdays(&self) -> [&'static str; 7];
+    fn outages_chart_title(&self) -> &'static str;
+    fn uptime_chart_title(&self) -> &'static str;
+    fn floor_label(&self, floor: u8) -> String;
+
+    // -- Menus
+
+    fn statuses_title(&self) -> &'static str;
+    fn report_button(&self) -> &'static str;
+    fn info_title(&self) -> &'static str;
+    fn info_fields(&self) -> Vec<(&'static str, String)>;
+    fn reports_locked(&self, settings: &GuildSettings) -> String;
+    fn pair_button(&self) -> &'static str;
+    fn all_button(&self) -> &'static str;
+    fn select_escalators(&self) -> &'static str;
+    fn select_status(&self) -> &'static str;
+    fn submit_report(&self, input: &EscalatorInput) -> String;
+    fn reported(&self, input: &EscalatorInput) -> String;
+    fn menu_removed(&self, channel_id: serenity::ChannelId, guild_name: &str) -> String;
+
+    // -- Alerts
+
+    fn save_list_button(&self) -> &'static str;
+    fn watchlist_updated(&self) -> &'static str;
+    fn floor_watched(&self, floor: u8) -> String;
+    fn floor_unwatched(&self, floor: u8) -> String;
+    fn watch_list_title(&self) -> &'static str;
+    fn watched_floors_title(&self) -> &'static str;
+
+    // -- Menu Commands
+
+    fn menu_initialized(&self) -> &'static str;
+    fn no_menus(&self) -> &'static str;
+    fn menus_title(&self) -> &'static str;
+    fn all_escalators(&self) -> &'static str;
+    fn menu_missing_permissions(&self) -> &'static str;
+    fn menu_sync_failed(&self, err: &str) -> String;
+    fn menu_syncing(&self) -> &'static str;
+    fn invalid_message_id(&self) -> &'static str;
+    fn no_matching_menu(&self) -> &'static str;
+    fn menus_deleted(&self, count: usize) -> String;
+
+    // -- Webhook Commands
+
+    fn webhook_added(&self, webhook_id: i32) -> String;
+    fn invalid_webhook_url(&self) -> &'static str;
+    fn private_webhook_url(&self) -> &'static str;
+    fn too_many_webhooks(&self, max: usize) -> String;
+    fn no_webhooks(&self) -> &'static str;
+    fn webhooks_title(&self) -> &'static str;
+    fn webhook_signed(&self) -> &'static str;
+    /// How the last delivery to a webhook went, or that nothing has been delivered yet.
+    fn webhook_delivery(&self, delivery: Option<&WebhookDelivery>) -> String;
+    fn no_matching_webhook(&self) -> &'static str;
+    fn webhook_removed(&self) -> &'static str;
+
+    // -- Status Page
+
+    /// How long ago something happened, for places where Discord timestamps can't be used.
+    fn time_ago(&self, elapsed: chrono::TimeDelta) -> String;
+    fn page_refresh_note(&self) -> &'static str;
+    fn feed_title(&self) -> &'static str;
+
+    // -- Settings Commands
+
+    fn history_channel_set(&self, channel_id: serenity::ChannelId) -> String;
+    fn history_channel_removed(&self) -> &'static str;
+    fn announcement_mode(&self, mode: AnnouncementMode) -> &'static str;
+    fn setting_name(&self, setting: Setting) -> &'static str;
+    /// Every setting of a guild, grouped by what they affect.
+    fn config_view(&self, settings: &GuildSettings) -> String;
+    fn setting_updated(&self, setting: Setting) -> String;
+    /// Confirms a setting was reset, or every setting if none is given.
+    fn setting_reset(&self, setting: Option<Setting>) -> String;
+    fn invalid_value(&self, setting: Setting, err: InvalidValue) -> String;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discord_locales_are_matched_by_language() {
+        assert_eq!(Locale::from_discord("en-US"), Some(Locale::En));
+        assert_eq!(Locale::from_discord("es-419"), Some(Locale::Es));
+        assert_eq!(Locale::from_discord("fr"), None);
+    }
+
+    #[test]
+    fn user_locale_overrides_guild_locale() {
+        assert_eq!(Locale::resolve(Some("es-ES"), Some(Locale::En)), Locale::Es);
+        assert_eq!(Locale::resolve(Some("fr"), Some(Locale::Es)), Locale::Es);
+        assert_eq!(Locale::resolve(None, None), Locale::En);
+    }
+
+    #[test]
+    fn plurals_agree_with_the_escalator_count() {
+        let one = [EscalatorFloors::new(4, 6)];
+        let two = [EscalatorFloors::new(4, 6), EscalatorFloors::new(6, 4)];
+
+        let en = Locale::En.catalog();
+        assert_eq!(
+            en.escalators_status(Escalators::Listed(&one), Status::Down),
+            "The `4-6` escalator is `DOWN`"
+        );
+
+        let es = Locale::Es.catalog();
+        assert_eq!(
+            es.escalators_status(Escalators::Listed(&one), Status::Down),
+            "La escalera `4-6` está `AVERIADA`"
+        );
+        assert_eq!(
+            es.escalators_status(Escalators::Listed(&two), Status::Blocked),
+            "Las escaleras `4-6` y `6-4` están `BLOQUEADAS`"
+        );
+    }
+}
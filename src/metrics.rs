@@ -0,0 +1,317 @@
+//! Counters, gauges and histograms about what the bot is doing,
+//! exported in the Prometheus text format by `GET /metrics`.
+
+use std::{collections::BTreeMap, fmt::Write, future::Future, sync::LazyLock, time::Duration};
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+use crate::data::{escalator::Escalator, status::Status};
+
+/// The upper bounds of the latency histograms' buckets, in seconds.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+type Labels = Vec<(&'static str, &'static str)>;
+
+#[derive(Default)]
+struct Metrics {
+    reports: Counter,
+    alerts: Counter,
+    menu_syncs: Histogram,
+    menu_sync_failures: Counter,
+    lagged: Counter,
+    queries: Histogram,
+    tasks_up: Gauge,
+    task_restarts: Counter,
+    task_failures: Gauge,
+}
+
+#[derive(Default)]
+struct Counter(Mutex<BTreeMap<Labels, u64>>);
+
+#[derive(Default)]
+struct Gauge(Mutex<BTreeMap<Labels, i64>>);
+
+#[derive(Default)]
+struct Histogram(Mutex<BTreeMap<Labels, Observations>>);
+
+#[derive(Default, Clone)]
+struct Observations {
+    /// How many observations fell into each bucket, not including the smaller buckets.
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Counter {
+    fn add(&self, labels: Labels, n: u64) {
+        *self.0.lock().entry(labels).or_default() += n;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} counter");
+
+        for (labels, value) in self.0.lock().iter() {
+            let _ = writeln!(out, "{name}{} {value}", format_labels(labels, None));
+        }
+    }
+}
+
+impl Gauge {
+    fn set(&self, labels: Labels, value: i64) {
+        self.0.lock().insert(labels, value);
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} gauge");
+
+        for (labels, value) in self.0.lock().iter() {
+            let _ = writeln!(out, "{name}{} {value}", format_labels(labels, None));
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&self, labels: Labels, duration: Duration) {
+        let secs = duration.as_secs_f64();
+
+        let mut histogram = self.0.lock();
+        let observations = histogram.entry(labels).or_default();
+
+        if let Some(bucket) = BUCKETS.iter().position(|&bound| secs <= bound) {
+            observations.buckets[bucket] += 1;
+        }
+        observations.count += 1;
+        observations.sum += secs;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+
+        for (labels, observations) in self.0.lock().iter() {
+            let mut cumulative = 0;
+
+            for (bound, count) in BUCKETS.iter().zip(observations.buckets) {
+                cumulative += count;
+                let le = bound.to_string();
+                let labels = format_labels(labels, Some(&le));
+                let _ = writeln!(out, "{name}_bucket{labels} {cumulative}");
+            }
+
+            let _ = writeln!(
+                out,
+                "{name}_bucket{} {}",
+                format_labels(labels, Some("+Inf")),
+                observations.count
+            );
+
+            let labels = format_labels(labels, None);
+            let _ = writeln!(out, "{name}_sum{labels} {}", observations.sum);
+            let _ = writeln!(out, "{name}_count{labels} {}", observations.count);
+        }
+    }
+}
+
+/// Formats labels like `{status="down",source="menu"}`, adding the `le` label for buckets.
+/// Every label value is a fixed string, so none of them need to be escaped.
+fn format_labels(labels: &[(&str, &str)], le: Option<&str>) -> String {
+    let mut pairs = labels
+        .iter()
+        .map(|(name, value)| format!("{name}=\"{value}\""))
+        .collect::<Vec<_>>();
+
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn status_label(status: Status) -> &'static str {
+    match status {
+        Status::Open => "open",
+        Status::Down => "down",
+        Status::Blocked => "blocked",
+    }
+}
+
+/// Counts a submitted report, where the source is how it was made (eg. `menu`).
+pub fn report_submitted(status: Status, source: &'static str) {
+    let labels = vec![("status", status_label(status)), ("source", source)];
+    METRICS.reports.add(labels, 1);
+}
+
+/// Counts an alert DM, and whether or not it was sent.
+pub fn alert_sent(sent: bool) {
+    let result = if sent { "sent" } else { "failed" };
+    METRICS.alerts.add(vec![("result", result)], 1);
+}
+
+/// Records how long it took to edit a menu with the latest statuses.
+pub fn menu_synced(duration: Duration) {
+    METRICS.menu_syncs.observe(vec![], duration);
+}
+
+/// Counts a menu that couldn't be synced, and why (eg. `missing_permissions`).
+pub fn menu_sync_failed(reason: &'static str) {
+    METRICS.menu_sync_failures.add(vec![("reason", reason)], 1);
+}
+
+/// Counts a broadcast receiver falling behind, and missing messages because of it.
+pub fn receiver_lagged(receiver: &'static str) {
+    METRICS.lagged.add(vec![("receiver", receiver)], 1);
+}
+
+/// Records whether a bot task is currently running.
+pub fn task_running(task: &'static str, running: bool) {
+    METRICS
+        .tasks_up
+        .set(vec![("task", task)], i64::from(running));
+}
+
+/// Counts a bot task being restarted after it failed.
+pub fn task_restarted(task: &'static str) {
+    METRICS.task_restarts.add(vec![("task", task)], 1);
+}
+
+/// Records how many times in a row a bot task has failed.
+pub fn task_failures(task: &'static str, failures: u32) {
+    METRICS
+        .task_failures
+        .set(vec![("task", task)], i64::from(failures));
+}
+
+/// Runs a database query, recording how long it took.
+pub async fn time_query<T>(query: &'static str, fut: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let res = fut.await;
+    METRICS
+        .queries
+        .observe(vec![("query", query)], start.elapsed());
+    res
+}
+
+/// Renders every metric in the Prometheus text format,
+/// along with how many of the given escalators currently have each status.
+pub fn render(escalators: &[Escalator]) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "# HELP escalator_escalators Escalators currently in each status."
+    );
+    let _ = writeln!(out, "# TYPE escalator_escalators gauge");
+
+    for status in [Status::Open, Status::Down, Status::Blocked] {
+        let count = escalators
+            .iter()
+            .filter(|escalator| escalator.status == status)
+            .count();
+        let labels = format_labels(&[("status", status_label(status))], None);
+
+        let _ = writeln!(out, "escalator_escalators{labels} {count}");
+    }
+
+    let metrics = &*METRICS;
+
+    metrics.reports.render(
+        &mut out,
+        "escalator_reports_total",
+        "Reports submitted, by status and source.",
+    );
+    metrics.alerts.render(
+        &mut out,
+        "escalator_alert_dms_total",
+        "Alert DMs sent to users watching escalators, by result.",
+    );
+    metrics.menu_syncs.render(
+        &mut out,
+        "escalator_menu_sync_seconds",
+        "How long it took to edit a menu with the latest statuses.",
+    );
+    metrics.menu_sync_failures.render(
+        &mut out,
+        "escalator_menu_sync_failures_total",
+        "Menus that couldn't be synced, by reason.",
+    );
+    metrics.lagged.render(
+        &mut out,
+        "escalator_broadcast_lagged_total",
+        "Times a broadcast receiver fell behind and missed messages.",
+    );
+    metrics.queries.render(
+        &mut out,
+        "escalator_db_query_seconds",
+        "How long database queries took, by query.",
+    );
+    metrics.tasks_up.render(
+        &mut out,
+        "escalator_task_up",
+        "Whether each bot task is running (1) or waiting to be restarted (0).",
+    );
+    metrics.task_restarts.render(
+        &mut out,
+        "escalator_task_restarts_total",
+        "Times each bot task was restarted after failing.",
+    );
+    metrics.task_failures.render(
+        &mut out,
+        "escalator_task_consecutive_failures",
+        "Times each bot task has failed in a row, reset once it stays up.",
+    );
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histograms_are_cumulative() {
+        let histogram = Histogram::default();
+        histogram.observe(vec![("query", "test")], Duration::from_millis(20));
+        histogram.observe(vec![("query", "test")], Duration::from_millis(200));
+        histogram.observe(vec![("query", "test")], Duration::from_secs(60));
+
+        let mut out = String::new();
+        histogram.render(&mut out, "test_seconds", "Test.");
+
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"0.01\"} 0\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"0.025\"} 1\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"0.25\"} 2\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"10\"} 2\n"));
+        assert!(out.contains("test_seconds_bucket{query=\"test\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("test_seconds_count{query=\"test\"} 3\n"));
+    }
+
+    #[test]
+    fn counters_are_rendered_per_label() {
+        let counter = Counter::default();
+        counter.add(vec![("result", "sent")], 2);
+        counter.add(vec![("result", "failed")], 1);
+        counter.add(vec![("result", "sent")], 1);
+
+        let mut out = String::new();
+        counter.render(&mut out, "test_total", "Test.");
+
+        assert_eq!(
+            out,
+            "# HELP test_total Test.\n\
+            # TYPE test_total counter\n\
+            test_total{result=\"failed\"} 1\n\
+            test_total{result=\"sent\"} 3\n"
+        );
+    }
+}
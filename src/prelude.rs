@@ -2,6 +2,8 @@ pub use poise::serenity_prelude as serenity;
 
 pub use crate::data::Data;
 
+pub use crate::data::db::DbPool;
+
 pub use crate::data::escalator::{Escalator, EscalatorFloors};
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
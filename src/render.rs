@@ -0,0 +1,381 @@
+use std::convert::Infallible;
+
+use embedded_graphics::{
+    mono_font::{
+        iso_8859_1::{FONT_10X20, FONT_7X13},
+        MonoTextStyle,
+    },
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle, Triangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use poise::serenity_prelude::CreateAttachment;
+
+use crate::{data::status::Status, locale::Locale, prelude::*};
+
+const BACKGROUND: Rgb888 = Rgb888::new(255, 255, 255);
+const FOREGROUND: Rgb888 = Rgb888::new(40, 40, 40);
+const EMPTY: Rgb888 = Rgb888::new(235, 235, 235);
+
+const OPEN: Rgb888 = Rgb888::new(55, 220, 70);
+const WARNING: Rgb888 = Rgb888::new(240, 180, 40);
+const DOWN: Rgb888 = Rgb888::new(240, 60, 60);
+const BLOCKED: Rgb888 = Rgb888::new(90, 90, 90);
+
+const MARGIN: i32 = 12;
+const TITLE_HEIGHT: i32 = 32;
+
+/// An RGB image that can be drawn on and encoded as a PNG.
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut canvas = Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 3) as usize],
+        };
+
+        let _ = canvas.clear(BACKGROUND);
+
+        canvas
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut png = vec![];
+
+        let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+
+        Ok(png)
+    }
+
+    fn title(&mut self, title: &str) {
+        let style = MonoTextStyle::new(&FONT_10X20, FOREGROUND);
+
+        let _ =
+            Text::with_baseline(title, Point::new(MARGIN, MARGIN), style, Baseline::Top).draw(self);
+    }
+
+    fn label(&mut self, label: &str, position: Point, alignment: Alignment) {
+        let character_style = MonoTextStyle::new(&FONT_7X13, FOREGROUND);
+        let text_style = TextStyleBuilder::new()
+            .alignment(alignment)
+            .baseline(Baseline::Middle)
+            .build();
+
+        let _ = Text::with_text_style(label, position, character_style, text_style).draw(self);
+    }
+
+    /// Draws a vertical arrow, with the head at `to`.
+    fn arrow(&mut self, from: Point, to: Point, color: Rgb888) {
+        const HEAD_LENGTH: i32 = 12;
+        const HEAD_WIDTH: i32 = 8;
+
+        let direction = (to.y - from.y).signum();
+        let base = to.y - direction * HEAD_LENGTH;
+
+        let _ = Line::new(from, Point::new(to.x, base))
+            .into_styled(PrimitiveStyle::with_stroke(color, 4))
+            .draw(self);
+
+        let _ = Triangle::new(
+            to,
+            Point::new(to.x - HEAD_WIDTH, base),
+            Point::new(to.x + HEAD_WIDTH, base),
+        )
+        .into_styled(PrimitiveStyle::with_fill(color))
+        .draw(self);
+    }
+
+    fn fill(&mut self, top_left: Point, size: Size, color: Rgb888) {
+        let _ = Rectangle::new(top_left, size)
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(self);
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+
+            if x >= self.width || y >= self.height {
+                continue;
+            }
+
+            let i = ((y * self.width + x) * 3) as usize;
+            self.pixels[i..i + 3].copy_from_slice(&[color.r(), color.g(), color.b()]);
+        }
+
+        Ok(())
+    }
+}
+
+pub const DIAGRAM_FILENAME: &str = "escalators.png";
+pub const OUTAGES_FILENAME: &str = "outages.png";
+pub const UPTIME_FILENAME: &str = "uptime.png";
+
+/// Renders the outage heatmap and uptime bars as PNG attachments.
+pub fn reliability_charts(
+    outages: &[[u32; 24]; 7],
+    uptimes: &[(EscalatorFloors, f64)],
+    locale: Locale,
+) -> Result<Vec<CreateAttachment>, png::EncodingError> {
+    Ok(vec![
+        CreateAttachment::bytes(outage_heatmap(outages, locale).to_png()?, OUTAGES_FILENAME),
+        CreateAttachment::bytes(uptime_bars(uptimes, locale).to_png()?, UPTIME_FILENAME),
+    ])
+}
+
+/// Renders a heatmap of outages, with a row for every weekday and a column for every hour.
+pub fn outage_heatmap(outages: &[[u32; 24]; 7], locale: Locale) -> Canvas {
+    const CELL: i32 = 24;
+    const LABEL_WIDTH: i32 = 40;
+
+    let catalog = locale.catalog();
+
+    let width = MARGIN * 2 + LABEL_WIDTH + CELL * 24;
+    let height = MARGIN * 2 + TITLE_HEIGHT + CELL * 7 + CELL;
+
+    let mut canvas = Canvas::new(width as u32, height as u32);
+    canvas.title(catalog.outages_chart_title());
+
+    let max = outages.iter().flatten().copied().max().unwrap_or(0);
+
+    let left = MARGIN + LABEL_WIDTH;
+    let top = MARGIN + TITLE_HEIGHT;
+
+    for (row, (weekday, hours)) in catalog.weekdays().iter().zip(outages).enumerate() {
+        let y = top + CELL * row as i32;
+
+        canvas.label(weekday, Point::new(MARGIN, y + CELL / 2), Alignment::Left);
+
+        for (column, &count) in hours.iter().enumerate() {
+            let x = left + CELL * column as i32;
+
+            let color = if count == 0 {
+                EMPTY
+            } else {
+                blend(WARNING, DOWN, count as f64 / max as f64)
+            };
+
+            // leave a 1px gap between cells
+            canvas.fill(
+                Point::new(x, y),
+                Size::new(CELL as u32 - 1, CELL as u32 - 1),
+                color,
+            );
+        }
+    }
+
+    let y = top + CELL * 7 + CELL / 2;
+
+    for hour in (0..24).step_by(3) {
+        let x = left + CELL * hour + CELL / 2;
+        canvas.label(&hour.to_string(), Point::new(x, y), Alignment::Center);
+    }
+
+    canvas
+}
+
+/// Renders a bar for every escalator showing the percentage of time it was open.
+pub fn uptime_bars(uptimes: &[(EscalatorFloors, f64)], locale: Locale) -> Canvas {
+    const ROW: i32 = 20;
+    const LABEL_WIDTH: i32 = 40;
+    const BAR_WIDTH: i32 = 400;
+    const PERCENT_WIDTH: i32 = 56;
+
+    let width = MARGIN * 2 + LABEL_WIDTH + BAR_WIDTH + PERCENT_WIDTH;
+    let height = MARGIN * 2 + TITLE_HEIGHT + ROW * uptimes.len() as i32;
+
+    let mut canvas = Canvas::new(width as u32, height as u32);
+    canvas.title(locale.catalog().uptime_chart_title());
+
+    let left = MARGIN + LABEL_WIDTH;
+    let top = MARGIN + TITLE_HEIGHT;
+
+    for (row, &(floors, uptime)) in uptimes.iter().enumerate() {
+        let y = top + ROW * row as i32;
+        let middle = y + ROW / 2;
+
+        canvas.label(
+            &floors.to_string(),
+            Point::new(MARGIN, middle),
+            Alignment::Left,
+        );
+
+        let color = if uptime >= 0.95 {
+            OPEN
+        } else if uptime >= 0.75 {
+            WARNING
+        } else {
+            DOWN
+        };
+
+        let bar_height = ROW as u32 - 4;
+        let bar_width = (uptime.clamp(0.0, 1.0) * BAR_WIDTH as f64).round() as u32;
+
+        canvas.fill(
+            Point::new(left, y + 2),
+            Size::new(BAR_WIDTH as u32, bar_height),
+            EMPTY,
+        );
+        canvas.fill(
+            Point::new(left, y + 2),
+            Size::new(bar_width, bar_height),
+            color,
+        );
+
+        let percent = format!("{:.1}%", uptime * 100.0);
+        let x = left + BAR_WIDTH + PERCENT_WIDTH - 4;
+        canvas.label(&percent, Point::new(x, middle), Alignment::Right);
+    }
+
+    canvas
+}
+
+/// Renders the escalators floor by floor, with each escalator as an arrow colored by its status.
+/// Escalators going between the same floors are drawn next to each other.
+pub fn building_diagram(escalators: &[Escalator], locale: Locale) -> Canvas {
+    const FLOOR_HEIGHT: i32 = 48;
+    const LABEL_WIDTH: i32 = 72;
+    const COLUMN_WIDTH: i32 = 84;
+    const LEGEND_HEIGHT: i32 = 32;
+    const PADDING: i32 = 16;
+    /// Fits a status label of up to 10 characters.
+    const LEGEND_SPACING: i32 = 96;
+
+    let catalog = locale.catalog();
+
+    // group escalators with their pair, keeping the order they were given in
+    let mut columns: Vec<((u8, u8), Vec<Escalator>)> = vec![];
+    for &escalator in escalators {
+        let EscalatorFloors { start, end } = escalator.floors;
+        let key = (start.min(end), start.max(end));
+
+        match columns.iter_mut().find(|(pair, _)| *pair == key) {
+            Some((_, pair)) => pair.push(escalator),
+            None => columns.push((key, vec![escalator])),
+        }
+    }
+
+    let lowest = columns.iter().map(|((low, _), _)| *low).min().unwrap_or(1);
+    let highest = columns
+        .iter()
+        .map(|((_, high), _)| *high)
+        .max()
+        .unwrap_or(1);
+    let floor_count = (highest - lowest) as i32;
+
+    let width =
+        MARGIN * 2 + LABEL_WIDTH + (COLUMN_WIDTH * columns.len() as i32).max(LEGEND_SPACING * 3);
+    let height =
+        MARGIN * 2 + TITLE_HEIGHT + PADDING * 2 + FLOOR_HEIGHT * floor_count + LEGEND_HEIGHT;
+
+    let mut canvas = Canvas::new(width as u32, height as u32);
+    canvas.title(catalog.statuses_title());
+
+    let top = MARGIN + TITLE_HEIGHT + PADDING;
+    let left = MARGIN + LABEL_WIDTH;
+    let floor_y = |floor: u8| top + FLOOR_HEIGHT * (highest - floor) as i32;
+
+    for floor in lowest..=highest {
+        let y = floor_y(floor);
+
+        canvas.label(
+            &catalog.floor_label(floor),
+            Point::new(MARGIN, y),
+            Alignment::Left,
+        );
+        canvas.fill(
+            Point::new(left, y),
+            Size::new((width - left - MARGIN) as u32, 1),
+            EMPTY,
+        );
+    }
+
+    for (column, (_, pair)) in columns.iter().enumerate() {
+        let column_left = left + COLUMN_WIDTH * column as i32;
+
+        for (i, escalator) in pair.iter().enumerate() {
+            let x = column_left + 16 + 40 * i as i32;
+            let from = Point::new(x, floor_y(escalator.floors.start));
+            let to = Point::new(x, floor_y(escalator.floors.end));
+            let color = status_color(escalator.status);
+
+            canvas.arrow(from, to, color);
+            canvas.label(
+                &escalator.floors.to_string(),
+                Point::new(x + 8, (from.y + to.y) / 2),
+                Alignment::Left,
+            );
+        }
+    }
+
+    // legend
+    let y = height - MARGIN - LEGEND_HEIGHT / 2;
+    let mut x = left;
+
+    for status in [Status::Open, Status::Down, Status::Blocked] {
+        canvas.fill(
+            Point::new(x, y - 6),
+            Size::new(12, 12),
+            status_color(status),
+        );
+        canvas.label(
+            catalog.status_label(status),
+            Point::new(x + 18, y),
+            Alignment::Left,
+        );
+
+        x += LEGEND_SPACING;
+    }
+
+    canvas
+}
+
+fn status_color(status: Status) -> Rgb888 {
+    match status {
+        Status::Open => OPEN,
+        Status::Down => DOWN,
+        Status::Blocked => BLOCKED,
+    }
+}
+
+/// Linearly interpolates between two colors.
+fn blend(from: Rgb888, to: Rgb888, t: f64) -> Rgb888 {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+
+    Rgb888::new(
+        mix(from.r(), to.r()),
+        mix(from.g(), to.g()),
+        mix(from.b(), to.b()),
+    )
+}